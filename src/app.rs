@@ -1,6 +1,7 @@
 //! Main application state and event handling (winit 0.29 compat)
 
-use crate::decoder::Decoder;
+use crate::decoder::{AspLiveStats, AspSeek, Decoder};
+use crate::fidelity::FidelityReport;
 use crate::renderer::Renderer;
 use crate::ui::Ui;
 use glam::Vec3;
@@ -9,7 +10,7 @@ use winit::{
     dpi::{PhysicalPosition, PhysicalSize},
     event::{ElementState, Event, KeyEvent, WindowEvent},
     event_loop::EventLoopWindowTarget,
-    keyboard::{KeyCode, PhysicalKey},
+    keyboard::{KeyCode, ModifiersState, PhysicalKey},
     window::Window,
 };
 
@@ -126,6 +127,8 @@ pub struct App {
     // Mouse drag state
     mouse_pressed: bool,
     last_mouse_pos: Option<PhysicalPosition<f64>>,
+    // Held modifier keys (Shift+drop pairs a reference image)
+    modifiers: ModifiersState,
     // Configuration (for library usage)
     config: ViewerConfig,
 }
@@ -136,6 +139,8 @@ pub struct ViewerState {
     // 2D controls (legacy)
     pub zoom: f32,
     pub pan: [f32; 2],
+    /// Pointer position in window pixels (None when outside the window)
+    pub cursor_pos: Option<[f32; 2]>,
 
     // 3D camera
     pub camera: Camera3D,
@@ -146,6 +151,13 @@ pub struct ViewerState {
     pub xray_type: XRayType,
     pub show_stats: bool,
     pub paused: bool,
+    /// Outline the ROIs of ASP residual corrections
+    pub show_roi_outlines: bool,
+    pub raster_filter: RasterFilter,
+    /// How decoded content is shown against a paired reference image
+    pub compare_view: CompareView,
+    /// Split position as a fraction of the image width (decoded left, reference right)
+    pub compare_split: f32,
     pub stats: FrameStats,
 
     // SDF-specific options
@@ -169,18 +181,25 @@ impl ViewerState {
         Self {
             zoom: 1.0,
             pan: [0.0, 0.0],
+            cursor_pos: None,
             camera: Camera3D::default(),
             render_mode,
             xray_mode: false,
             xray_type: XRayType::default(),
             show_stats,
             paused: false,
+            show_roi_outlines: false,
+            raster_filter: RasterFilter::default(),
+            compare_view: CompareView::default(),
+            compare_split: 0.5,
             stats: FrameStats {
                 fps: 0.0,
                 decode_speed: 0.0,
                 compression_ratio: 1.0,
                 gpu_usage: 0.0,
                 resolution: "∞ (Procedural)".to_string(),
+                stream: None,
+                fidelity: None,
             },
             sdf_max_steps: 128,
             sdf_epsilon: 0.001,
@@ -205,6 +224,28 @@ pub enum XRayType {
     Wireframe,
 }
 
+/// Texture filtering for raster images
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RasterFilter {
+    /// Smooth interpolation
+    #[default]
+    Linear,
+    /// Hard pixel edges (for inspecting pixels up close)
+    Nearest,
+}
+
+/// Comparison of decoded content with a paired reference image
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareView {
+    /// Decoded content on the left of the split, reference on the right
+    #[default]
+    Split,
+    /// Absolute difference heatmap
+    Difference,
+    /// Decoded content only, at full procedural detail
+    Off,
+}
+
 /// Frame statistics
 #[derive(Default, Clone)]
 pub struct FrameStats {
@@ -215,6 +256,10 @@ pub struct FrameStats {
     #[allow(dead_code)]
     pub gpu_usage: f32,
     pub resolution: String,
+    /// Counters of the live stream being received
+    pub stream: Option<AspLiveStats>,
+    /// PSNR / SSIM against the paired reference image (once measured)
+    pub fidelity: Option<FidelityReport>,
 }
 
 /// Viewer configuration for library usage
@@ -239,6 +284,10 @@ pub struct ViewerConfig {
     pub paused: bool,
     /// Initial file to load (ASP/ALZ)
     pub initial_file: Option<String>,
+    /// Receive a live ASP stream on this address instead of a file
+    pub asp_listen: Option<String>,
+    /// Reference image to compare the initial .alice/.alz file against
+    pub compare_with: Option<String>,
     /// Window width
     pub width: u32,
     /// Window height
@@ -256,6 +305,8 @@ impl Default for ViewerConfig {
             show_stats: false,
             paused: false,
             initial_file: None,
+            asp_listen: None,
+            compare_with: None,
             width: 1280,
             height: 720,
         }
@@ -287,6 +338,7 @@ impl App {
             initial_file,
             mouse_pressed: false,
             last_mouse_pos: None,
+            modifiers: ModifiersState::empty(),
             config: ViewerConfig::default(),
         }
     }
@@ -313,6 +365,7 @@ impl App {
             initial_file: config.initial_file.clone(),
             mouse_pressed: false,
             last_mouse_pos: None,
+            modifiers: ModifiersState::empty(),
             config,
         }
     }
@@ -342,6 +395,20 @@ impl App {
             }
         }
 
+        // Reference image for fidelity comparison
+        if let Some(path) = self.config.compare_with.clone() {
+            if let Err(e) = self.decoder.pair_reference(&path) {
+                tracing::error!("Failed to pair reference image: {:#}", e);
+            }
+        }
+
+        // Live stream (takes over from the initial file)
+        if let Some(addr) = self.config.asp_listen.clone() {
+            if let Err(e) = self.decoder.listen_asp(&addr) {
+                tracing::error!("Failed to start ASP stream: {:#}", e);
+            }
+        }
+
         self.window = Some(window);
     }
 
@@ -419,6 +486,38 @@ impl App {
                 }
             }
 
+            // Raster image filtering
+            KeyCode::KeyF => {
+                self.state.raster_filter = match self.state.raster_filter {
+                    RasterFilter::Linear => RasterFilter::Nearest,
+                    RasterFilter::Nearest => RasterFilter::Linear,
+                };
+                tracing::info!("Raster filter: {:?}", self.state.raster_filter);
+            }
+
+            // Plot x axis labels: time / sample index
+            KeyCode::KeyT => self.ui.toggle_plot_x_axis(),
+
+            // Outline the ROIs of ASP residual corrections
+            KeyCode::KeyC => {
+                self.state.show_roi_outlines = !self.state.show_roi_outlines;
+                tracing::info!("ROI outlines: {}", self.state.show_roi_outlines);
+            }
+
+            // Fidelity comparison with a paired reference image
+            KeyCode::KeyV => {
+                self.state.compare_view = match self.state.compare_view {
+                    CompareView::Split => CompareView::Difference,
+                    CompareView::Difference => CompareView::Off,
+                    CompareView::Off => CompareView::Split,
+                };
+                tracing::info!("Compare view: {:?}", self.state.compare_view);
+            }
+            KeyCode::BracketLeft | KeyCode::BracketRight => {
+                let step = if key == KeyCode::BracketLeft { -0.05 } else { 0.05 };
+                self.state.compare_split = (self.state.compare_split + step).clamp(0.0, 1.0);
+            }
+
             // General controls
             KeyCode::F1 => {
                 self.state.xray_mode = !self.state.xray_mode;
@@ -451,6 +550,18 @@ impl App {
                 self.state.paused = !self.state.paused;
                 tracing::info!("Paused: {}", self.state.paused);
             }
+
+            // Stream and video playback: step (pauses), keyframe jumps, start/end
+            KeyCode::ArrowLeft | KeyCode::ArrowRight if self.decoder.has_playback() => {
+                self.state.paused = true;
+                let delta = if key == KeyCode::ArrowLeft { -1 } else { 1 };
+                self.decoder.seek_playback(AspSeek::Step(delta));
+            }
+            KeyCode::PageUp => self.decoder.seek_playback(AspSeek::PreviousKeyframe),
+            KeyCode::PageDown => self.decoder.seek_playback(AspSeek::NextKeyframe),
+            KeyCode::Home => self.decoder.seek_playback(AspSeek::Start),
+            KeyCode::End => self.decoder.seek_playback(AspSeek::End),
+            KeyCode::Minus | KeyCode::Equal => self.decoder.step_video_fps(key == KeyCode::Equal),
             KeyCode::Tab => {
                 self.state.xray_type = match self.state.xray_type {
                     XRayType::MotionVectors => XRayType::FftHeatmap,
@@ -563,11 +674,27 @@ impl App {
                         }
                     }
                     self.last_mouse_pos = Some(position);
+                    self.state.cursor_pos = Some([position.x as f32, position.y as f32]);
+                    // Keep hover readouts current while paused
+                    if let Some(window) = &self.window {
+                        window.request_redraw();
+                    }
+                }
+                WindowEvent::CursorLeft { .. } => {
+                    self.state.cursor_pos = None;
+                    self.last_mouse_pos = None;
+                }
+                WindowEvent::ModifiersChanged(modifiers) => {
+                    self.modifiers = modifiers.state();
                 }
                 WindowEvent::DroppedFile(path) => {
                     let path_str = path.to_string_lossy().to_string();
                     tracing::info!("File dropped: {}", path_str);
-                    self.ui.queue_file(path_str);
+                    if self.modifiers.shift_key() {
+                        self.ui.queue_reference(path_str);
+                    } else {
+                        self.ui.queue_file(path_str);
+                    }
                     if let Some(window) = &self.window {
                         window.request_redraw();
                     }
@@ -2,11 +2,24 @@
 //!
 //! Usage:
 //!   alice-create linear --slope 0.005 --intercept 25.0 --samples 1000 -o sensor_data.alice
+//!   alice-create polynomial --coef 25.0,0.01,-0.0001 --samples 1000 -o trend.alice
+//!   alice-create fourier --period 1440 --term 0:20 --term 1:5:0.3 -o daily.alice
+//!   alice-create sine --wave 0.5:2.0 --wave 3:0.25:1.57 --offset 10 -o wave.alice
+//!   alice-create segmented --linear 0:0.01:20 --poly 3600:56,0.002,-1e-6 --samples 86400 -o day.alice
+//!   alice-create composite --layer terrain.alice --layer cells.alice:max:0.5 -o combined.alice
 //!   alice-create mandelbrot --iterations 256 -o fractal.alice
 //!   alice-create julia --cx -0.7 --cy 0.27 -o julia.alice
 //!   alice-create perlin --seed 12345 --scale 5.0 -o terrain.alice
+//!   alice-create voronoi --seed 7 --density 8 --metric manhattan --feature f2-f1 -o cells.alice
+//!   alice-create asp --frames 300 --fps 30 --keyframe-interval 60 -o demo.asp
 
+use alice_view::convert;
 use alice_view::decoder::alice::*;
+use alice_view::decoder::{
+    AlzFile, AspRoi, AspWriter, CorrectionData, KeyframeData, MotionVectorCompact, SyncCommand, SyncData,
+};
+use alice_view::expand::{self, SampleGrid};
+use alice_view::fit::{self, FitModel};
 use std::fs;
 
 fn print_usage() {
@@ -16,10 +29,24 @@ fn print_usage() {
     println!("Usage:");
     println!("  alice-create linear --slope <f32> --intercept <f32> [--samples <u32>] [-o <file>]");
     println!("  alice-create linear-q16 --slope <i32> --intercept <i32> [--samples <u32>] [-o <file>]");
+    println!("  alice-create polynomial --coef <c0,c1,...> [--q16] [--samples <u32>] [-o <file>]");
+    println!("  alice-create fourier --period <f64> --term <k:amp[:phase]>... [--samples <u32>] [-o <file>]");
+    println!("  alice-create sine --wave <freq:amp[:phase]>... [--offset <f32>] [--samples <u32>] [-o <file>]");
+    println!("  alice-create segmented (--linear <start:slope:intercept> | --poly <start:c0,c1,...>)...");
+    println!("                         [--samples <u32>] [-o <file>]");
+    println!("  alice-create fit --input <samples.csv> --model linear|polynomial:N|fourier:K|auto");
+    println!("                   [--max-error <f64>] [--timestamp <rfc3339>] [--sample-interval <secs>] [-o <file>]");
+    println!("  alice-create expand <file.alice> --out <data.csv> [--range <a..b>] [--step <f64>]");
+    println!("  alice-create composite --layer <file.alice[:add|multiply|max|warp[:weight]]>... [-o <file>]");
     println!("  alice-create mandelbrot [--iterations <u32>] [--cx <f64>] [--cy <f64>] [-o <file>]");
     println!("  alice-create julia [--cx <f64>] [--cy <f64>] [--iterations <u32>] [-o <file>]");
     println!("  alice-create perlin [--seed <u64>] [--scale <f32>] [--octaves <u32>] [-o <file>]");
+    println!("  alice-create voronoi [--seed <u64>] [--density <f32>] [--metric euclidean|manhattan|chebyshev]");
+    println!("                       [--feature f1|f2|f2-f1] [--jitter <0..1>] [-o <file>]");
     println!("  alice-create demo [-o <file>]");
+    println!("  alice-create convert <in.alz> <out.alice>   (or <in.alice> <out.alz>)");
+    println!("  alice-create asp [--frames <u32>] [--fps <f32>] [--keyframe-interval <u32>] [--size <WxH>]");
+    println!("                   [--seed <u64>] [-o <file.asp>]   (animated Perlin demo stream)");
     println!();
     println!("Options:");
     println!("  -o, --output <file>   Output file path (default: output.alice)");
@@ -28,8 +55,17 @@ fn print_usage() {
     println!();
     println!("Examples:");
     println!("  alice-create linear --slope 0.005 --intercept 25.0 --samples 1000 -o temp.alice");
+    println!("  alice-create polynomial --coef 25.0,0.01,-0.0001 -o trend.alice");
+    println!("  alice-create fourier --period 1440 --term 0:20 --term 1:5:0.3 -o daily.alice");
+    println!("  alice-create sine --wave 0.5:2.0 --wave 3:0.25:1.57 --offset 10 -o wave.alice");
+    println!("  alice-create segmented --linear 0:0.01:20 --poly 3600:56,0.002,-1e-6 --samples 86400 -o day.alice");
+    println!("  alice-create fit --input sensor.csv --model auto --max-error 0.05 -o sensor.alice");
+    println!("  alice-create expand sensor.alice --out sensor.csv --range 0..3600 --step 10");
+    println!("  alice-create composite --layer warp.alice:warp:0.3 --layer terrain.alice --layer cells.alice:max:0.5");
     println!("  alice-create mandelbrot -o fractal.alice");
     println!("  alice-create demo --sensor-id TEMP-001 --unit °C -o demo.alice");
+    println!("  alice-create convert legacy.alz legacy.alice --unit °C");
+    println!("  alice-create asp --frames 300 --fps 30 -o demo.asp");
 }
 
 fn main() {
@@ -83,10 +119,35 @@ fn main() {
     let result = match command.as_str() {
         "linear" => create_linear(&args[2..], sensor_id, unit),
         "linear-q16" => create_linear_q16(&args[2..], sensor_id, unit),
+        "polynomial" => create_polynomial(&args[2..], sensor_id, unit),
+        "fourier" => create_fourier(&args[2..], sensor_id, unit),
+        "sine" => create_sine(&args[2..], sensor_id, unit),
+        "segmented" => create_segmented(&args[2..], sensor_id, unit),
+        "composite" => create_composite(&args[2..], sensor_id, unit),
+        "fit" => create_fit(&args[2..], sensor_id, unit),
         "mandelbrot" => create_mandelbrot(&args[2..]),
         "julia" => create_julia(&args[2..]),
         "perlin" => create_perlin(&args[2..]),
+        "voronoi" => create_voronoi(&args[2..]),
         "demo" => create_demo(sensor_id, unit),
+        "expand" => {
+            if let Err(e) = expand_file(&args[2..]) {
+                eprintln!("Error: {}", e);
+            }
+            return;
+        }
+        "asp" => {
+            if let Err(e) = create_asp(&args[2..]) {
+                eprintln!("Error: {}", e);
+            }
+            return;
+        }
+        "convert" => {
+            if let Err(e) = convert_file(&args[2..], sensor_id, unit) {
+                eprintln!("Error: {}", e);
+            }
+            return;
+        }
         "-h" | "--help" | "help" => {
             print_usage();
             return;
@@ -201,6 +262,379 @@ fn create_linear_q16(args: &[String], sensor_id: Option<String>, unit: Option<St
     builder.build()
 }
 
+fn create_polynomial(args: &[String], sensor_id: Option<String>, unit: Option<String>) -> anyhow::Result<AliceFile> {
+    let mut coefficients: Vec<f64> = Vec::new();
+    let mut samples: u32 = 1000;
+    let mut q16 = false;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--coef" if i + 1 < args.len() => {
+                // Accept "c0,c1,c2" or repeated --coef flags (ascending powers)
+                for part in args[i + 1].split(',').filter(|s| !s.trim().is_empty()) {
+                    coefficients.push(part.trim().parse()?);
+                }
+                i += 2;
+            }
+            "--samples" if i + 1 < args.len() => {
+                samples = args[i + 1].parse()?;
+                i += 2;
+            }
+            "--q16" => {
+                q16 = true;
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+
+    if coefficients.is_empty() {
+        anyhow::bail!("polynomial requires --coef <c0,c1,...>");
+    }
+    if coefficients.len() > PolynomialPayload::MAX_COEFFICIENTS {
+        anyhow::bail!("polynomial degree must be <= {}", PolynomialPayload::MAX_COEFFICIENTS - 1);
+    }
+
+    let mut builder = if q16 {
+        let coefs_q16 = coefficients
+            .iter()
+            .map(|&c| fit::to_q16(c).ok_or_else(|| anyhow::anyhow!("coefficient {} exceeds the Q16.16 range", c)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        AliceFileBuilder::polynomial_q16(coefs_q16, samples)
+    } else {
+        AliceFileBuilder::polynomial(coefficients, samples)
+    };
+    if let Some(id) = sensor_id {
+        builder = builder.sensor_id(&id);
+    }
+    if let Some(u) = unit {
+        builder = builder.unit(&u);
+    }
+
+    builder.build()
+}
+
+fn create_fourier(args: &[String], sensor_id: Option<String>, unit: Option<String>) -> anyhow::Result<AliceFile> {
+    let mut period: f64 = 1000.0;
+    let mut samples: u32 = 1000;
+    let mut terms: Vec<FourierTerm> = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--period" if i + 1 < args.len() => {
+                period = args[i + 1].parse()?;
+                i += 2;
+            }
+            "--samples" if i + 1 < args.len() => {
+                samples = args[i + 1].parse()?;
+                i += 2;
+            }
+            "--term" if i + 1 < args.len() => {
+                // k:amplitude[:phase]
+                let parts: Vec<&str> = args[i + 1].split(':').collect();
+                if parts.len() < 2 || parts.len() > 3 {
+                    anyhow::bail!("invalid --term '{}', expected k:amp[:phase]", args[i + 1]);
+                }
+                terms.push(FourierTerm {
+                    frequency: parts[0].parse()?,
+                    amplitude: parts[1].parse()?,
+                    phase: if parts.len() == 3 { parts[2].parse()? } else { 0.0 },
+                });
+                i += 2;
+            }
+            _ => i += 1,
+        }
+    }
+
+    if terms.is_empty() {
+        anyhow::bail!("fourier requires at least one --term <k:amp[:phase]>");
+    }
+    if terms.len() > FourierPayload::MAX_TERMS {
+        anyhow::bail!("fourier supports at most {} terms", FourierPayload::MAX_TERMS);
+    }
+    if period <= 0.0 {
+        anyhow::bail!("--period must be positive");
+    }
+
+    let mut builder = AliceFileBuilder::fourier(period, terms, samples);
+    if let Some(id) = sensor_id {
+        builder = builder.sensor_id(&id);
+    }
+    if let Some(u) = unit {
+        builder = builder.unit(&u);
+    }
+
+    builder.build()
+}
+
+fn create_sine(args: &[String], sensor_id: Option<String>, unit: Option<String>) -> anyhow::Result<AliceFile> {
+    let mut offset: f32 = 0.0;
+    let mut samples: u32 = 1000;
+    let mut components: Vec<SineComponent> = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--offset" if i + 1 < args.len() => {
+                offset = args[i + 1].parse()?;
+                i += 2;
+            }
+            "--samples" if i + 1 < args.len() => {
+                samples = args[i + 1].parse()?;
+                i += 2;
+            }
+            "--wave" if i + 1 < args.len() => {
+                // freq:amplitude[:phase]
+                let parts: Vec<&str> = args[i + 1].split(':').collect();
+                if parts.len() < 2 || parts.len() > 3 {
+                    anyhow::bail!("invalid --wave '{}', expected freq:amp[:phase]", args[i + 1]);
+                }
+                components.push(SineComponent {
+                    frequency: parts[0].parse()?,
+                    amplitude: parts[1].parse()?,
+                    phase: if parts.len() == 3 { parts[2].parse()? } else { 0.0 },
+                });
+                i += 2;
+            }
+            _ => i += 1,
+        }
+    }
+
+    if components.is_empty() {
+        anyhow::bail!("sine requires at least one --wave <freq:amp[:phase]>");
+    }
+    if components.len() > SineWavePayload::MAX_COMPONENTS {
+        anyhow::bail!("sine supports at most {} waves", SineWavePayload::MAX_COMPONENTS);
+    }
+
+    let mut builder = AliceFileBuilder::sine(offset, components, samples);
+    if let Some(id) = sensor_id {
+        builder = builder.sensor_id(&id);
+    }
+    if let Some(u) = unit {
+        builder = builder.unit(&u);
+    }
+
+    builder.build()
+}
+
+fn create_segmented(args: &[String], sensor_id: Option<String>, unit: Option<String>) -> anyhow::Result<AliceFile> {
+    let mut segments: Vec<Segment> = Vec::new();
+    let mut samples: Option<u32> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--samples" if i + 1 < args.len() => {
+                samples = Some(args[i + 1].parse()?);
+                i += 2;
+            }
+            "--linear" if i + 1 < args.len() => {
+                // start:slope:intercept (x relative to start)
+                let parts: Vec<&str> = args[i + 1].split(':').collect();
+                if parts.len() != 3 {
+                    anyhow::bail!("invalid --linear '{}', expected start:slope:intercept", args[i + 1]);
+                }
+                let q16 = |s: &str| -> anyhow::Result<i32> {
+                    let v: f64 = s.parse()?;
+                    fit::to_q16(v).ok_or_else(|| anyhow::anyhow!("{} exceeds the Q16.16 range", v))
+                };
+                segments.push(Segment {
+                    start_index: parts[0].parse()?,
+                    model: SegmentModel::Linear(LinearPayload {
+                        slope_q16: q16(parts[1])?,
+                        intercept_q16: q16(parts[2])?,
+                        sample_count: 0,
+                    }),
+                });
+                i += 2;
+            }
+            "--poly" if i + 1 < args.len() => {
+                // start:c0,c1,... (ascending powers, x relative to start)
+                let Some((start, coefs)) = args[i + 1].split_once(':') else {
+                    anyhow::bail!("invalid --poly '{}', expected start:c0,c1,...", args[i + 1]);
+                };
+                let coefficients = coefs
+                    .split(',')
+                    .filter(|s| !s.trim().is_empty())
+                    .map(|c| c.trim().parse::<f64>())
+                    .collect::<Result<Vec<_>, _>>()?;
+                let poly =
+                    PolynomialPayload { coefficients: PolynomialCoefficients::F64(coefficients), sample_count: 0 };
+                poly.validate().map_err(|_| {
+                    anyhow::anyhow!("--poly needs 1 to {} coefficients", PolynomialPayload::MAX_COEFFICIENTS)
+                })?;
+                segments.push(Segment { start_index: start.parse()?, model: SegmentModel::Polynomial(poly) });
+                i += 2;
+            }
+            _ => i += 1,
+        }
+    }
+
+    if segments.is_empty() {
+        anyhow::bail!("segmented requires at least one --linear or --poly segment");
+    }
+    segments.sort_by_key(|s| s.start_index);
+    if segments.windows(2).any(|w| w[0].start_index == w[1].start_index) {
+        anyhow::bail!("segment start indices must be unique");
+    }
+    let slots: usize = segments.iter().map(|s| s.model.term_slots()).sum();
+    if slots > SegmentedPayload::MAX_TERM_SLOTS {
+        anyhow::bail!(
+            "segments need {} term slots but at most {} render (a linear segment takes 2)",
+            slots,
+            SegmentedPayload::MAX_TERM_SLOTS
+        );
+    }
+
+    // Fill per-segment sample counts from the boundaries
+    let total = samples.unwrap_or_else(|| segments.last().map_or(0, |s| s.start_index + 1));
+    let ends: Vec<u32> = segments.iter().skip(1).map(|s| s.start_index).chain([total]).collect();
+    for (seg, end) in segments.iter_mut().zip(ends) {
+        let count = end.saturating_sub(seg.start_index);
+        match &mut seg.model {
+            SegmentModel::Linear(p) => p.sample_count = count,
+            SegmentModel::Polynomial(p) => p.sample_count = count,
+        }
+    }
+
+    let mut builder = AliceFileBuilder::segmented(segments, total);
+    if let Some(id) = sensor_id {
+        builder = builder.sensor_id(&id);
+    }
+    if let Some(u) = unit {
+        builder = builder.unit(&u);
+    }
+
+    builder.build()
+}
+
+fn create_composite(args: &[String], sensor_id: Option<String>, unit: Option<String>) -> anyhow::Result<AliceFile> {
+    let mut layers: Vec<Layer> = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--layer" if i + 1 < args.len() => {
+                // path[:op[:weight]]
+                let mut parts = args[i + 1].splitn(3, ':');
+                let path = parts.next().unwrap_or_default();
+                let blend = match parts.next() {
+                    Some(name) => BlendOp::from_name(name)
+                        .ok_or_else(|| anyhow::anyhow!("unknown blend op '{}'", name))?,
+                    None => BlendOp::Add,
+                };
+                let weight: f32 = match parts.next() {
+                    Some(w) => w.parse()?,
+                    None => 1.0,
+                };
+
+                let data = fs::read(path).map_err(|e| anyhow::anyhow!("cannot read layer '{}': {}", path, e))?;
+                let file = AliceFile::parse(&data).map_err(|e| anyhow::anyhow!("layer '{}': {}", path, e))?;
+                if matches!(file.payload, AlicePayload::Composite(_)) {
+                    anyhow::bail!("layer '{}' is itself a composite", path);
+                }
+                layers.push(Layer { blend, weight, payload: file.payload });
+                i += 2;
+            }
+            _ => i += 1,
+        }
+    }
+
+    if layers.is_empty() {
+        anyhow::bail!("composite requires at least one --layer <file.alice>");
+    }
+    if layers.len() > CompositePayload::MAX_LAYERS {
+        anyhow::bail!("composite supports at most {} layers", CompositePayload::MAX_LAYERS);
+    }
+    let slots: usize = layers.iter().map(|l| l.payload.term_slots()).sum();
+    if slots > CompositePayload::MAX_TERM_SLOTS {
+        anyhow::bail!(
+            "layers need {} term slots but at most {} render (one per Fourier term or sine wave)",
+            slots,
+            CompositePayload::MAX_TERM_SLOTS
+        );
+    }
+
+    let mut builder = AliceFileBuilder::composite(layers);
+    if let Some(id) = sensor_id {
+        builder = builder.sensor_id(&id);
+    }
+    if let Some(u) = unit {
+        builder = builder.unit(&u);
+    }
+
+    builder.build()
+}
+
+fn create_fit(args: &[String], sensor_id: Option<String>, unit: Option<String>) -> anyhow::Result<AliceFile> {
+    let mut input: Option<String> = None;
+    let mut model = FitModel::Auto;
+    let mut max_error: Option<f64> = None;
+    let mut timestamp: Option<String> = None;
+    let mut sample_interval: Option<f64> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--input" | "-i" if i + 1 < args.len() => {
+                input = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "--model" if i + 1 < args.len() => {
+                model = args[i + 1].parse()?;
+                i += 2;
+            }
+            "--max-error" if i + 1 < args.len() => {
+                max_error = Some(args[i + 1].parse()?);
+                i += 2;
+            }
+            "--timestamp" if i + 1 < args.len() => {
+                timestamp = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "--sample-interval" if i + 1 < args.len() => {
+                sample_interval = Some(args[i + 1].parse()?);
+                i += 2;
+            }
+            _ => i += 1,
+        }
+    }
+
+    let input = input.ok_or_else(|| anyhow::anyhow!("fit requires --input <samples.csv>"))?;
+    let text = fs::read_to_string(&input).map_err(|e| anyhow::anyhow!("cannot read '{}': {}", input, e))?;
+    let samples = fit::read_csv(&text)?;
+
+    // Default budget: 1% of the sample range
+    let (lo, hi) = samples.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+    let max_error = max_error.unwrap_or(((hi - lo) * 0.01).max(f64::EPSILON));
+
+    let result = fit::fit(&samples, model, max_error)?;
+    println!("   Model: {} ({} samples)", result.model, samples.len());
+    println!("   RMSE: {:.6}, max abs error: {:.6}", result.stats.rmse, result.stats.max_abs_error);
+    if model == FitModel::Auto && result.stats.max_abs_error > max_error {
+        println!("⚠ No model meets the {:.6} error budget; using the most accurate", max_error);
+    }
+
+    let mut builder = result.builder();
+    if let Some(ts) = timestamp {
+        builder = builder.timestamp(&ts);
+    }
+    if let Some(dt) = sample_interval {
+        builder = builder.sample_interval(dt);
+    }
+    if let Some(id) = sensor_id {
+        builder = builder.sensor_id(&id);
+    }
+    if let Some(u) = unit {
+        builder = builder.unit(&u);
+    }
+
+    builder.build()
+}
+
 fn create_mandelbrot(args: &[String]) -> anyhow::Result<AliceFile> {
     let mut iterations: u32 = 256;
     let mut cx: f64 = -0.75;
@@ -282,6 +716,45 @@ fn create_perlin(args: &[String]) -> anyhow::Result<AliceFile> {
     AliceFileBuilder::perlin(seed, scale, octaves).build()
 }
 
+fn create_voronoi(args: &[String]) -> anyhow::Result<AliceFile> {
+    let mut seed: u64 = 12345;
+    let mut density: f32 = 8.0;
+    let mut metric = VoronoiMetric::Euclidean;
+    let mut feature = VoronoiFeature::F1;
+    let mut jitter: f32 = 1.0;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--seed" if i + 1 < args.len() => {
+                seed = args[i + 1].parse()?;
+                i += 2;
+            }
+            "--density" if i + 1 < args.len() => {
+                density = args[i + 1].parse()?;
+                i += 2;
+            }
+            "--metric" if i + 1 < args.len() => {
+                metric = VoronoiMetric::from_name(&args[i + 1])
+                    .ok_or_else(|| anyhow::anyhow!("unknown metric: {}", args[i + 1]))?;
+                i += 2;
+            }
+            "--feature" if i + 1 < args.len() => {
+                feature = VoronoiFeature::from_name(&args[i + 1])
+                    .ok_or_else(|| anyhow::anyhow!("unknown feature: {}", args[i + 1]))?;
+                i += 2;
+            }
+            "--jitter" if i + 1 < args.len() => {
+                jitter = args[i + 1].parse()?;
+                i += 2;
+            }
+            _ => i += 1,
+        }
+    }
+
+    AliceFileBuilder::voronoi(seed, density, metric, feature, jitter.clamp(0.0, 1.0)).build()
+}
+
 fn create_demo(sensor_id: Option<String>, unit: Option<String>) -> anyhow::Result<AliceFile> {
     // Create a demo file similar to what ALICE-Edge outputs
     // slope = 0.005 (temperature increase per sample)
@@ -309,3 +782,233 @@ fn create_demo(sensor_id: Option<String>, unit: Option<String>) -> anyhow::Resul
 
     builder.build()
 }
+
+/// Convert between .alz and .alice; the direction follows the input format
+fn convert_file(args: &[String], sensor_id: Option<String>, unit: Option<String>) -> anyhow::Result<()> {
+    // Positional <in> <out>, skipping the shared --sensor-id/--unit options
+    let mut paths = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--sensor-id" | "--unit" => i += 2,
+            other => {
+                paths.push(other);
+                i += 1;
+            }
+        }
+    }
+    let [input, output] = paths[..] else {
+        anyhow::bail!("convert requires <input> <output> paths");
+    };
+
+    let data = fs::read(input).map_err(|e| anyhow::anyhow!("cannot read '{}': {}", input, e))?;
+
+    let bytes = match AliceFile::parse(&data) {
+        Ok(alice) => {
+            let alz = convert::alice_to_alz(&alice)?;
+            let dropped = convert::dropped_metadata(&alice.metadata);
+            if !dropped.is_empty() {
+                println!("⚠ .alz has no metadata; dropped: {}", dropped.join(", "));
+            }
+            println!("   {} → {}", alice.content_type_name(), alz.content_type_name());
+            alz.to_bytes()
+        }
+        Err(alice_err) => {
+            let alz = AlzFile::parse(&data).map_err(|alz_err| {
+                anyhow::anyhow!("'{}' is neither .alice ({}) nor .alz ({})", input, alice_err, alz_err)
+            })?;
+            let metadata = AliceMetadata { sensor_id, unit, ..Default::default() };
+            let alice = convert::alz_to_alice(&alz, metadata)?;
+            println!("   {} → {}", alz.content_type_name(), alice.content_type_name());
+            alice.to_bytes()
+        }
+    };
+
+    fs::write(output, &bytes).map_err(|e| anyhow::anyhow!("cannot write '{}': {}", output, e))?;
+    println!("✅ Converted: {} → {} ({} bytes)", input, output, bytes.len());
+    Ok(())
+}
+
+/// Evaluate a model over a sample grid and write it as CSV
+fn expand_file(args: &[String]) -> anyhow::Result<()> {
+    let mut input: Option<&str> = None;
+    let mut output: Option<&str> = None;
+    let mut range: Option<&str> = None;
+    let mut step: f64 = 1.0;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--out" | "-o" if i + 1 < args.len() => {
+                output = Some(&args[i + 1]);
+                i += 2;
+            }
+            "--range" if i + 1 < args.len() => {
+                range = Some(&args[i + 1]);
+                i += 2;
+            }
+            "--step" if i + 1 < args.len() => {
+                step = args[i + 1].parse()?;
+                i += 2;
+            }
+            other => {
+                input.get_or_insert(other);
+                i += 1;
+            }
+        }
+    }
+
+    let input = input.ok_or_else(|| anyhow::anyhow!("expand requires an input .alice file"))?;
+    let output = output.ok_or_else(|| anyhow::anyhow!("expand requires --out <data.csv>"))?;
+
+    let data = fs::read(input).map_err(|e| anyhow::anyhow!("cannot read '{}': {}", input, e))?;
+    let file = AliceFile::parse(&data)?;
+
+    let grid = match range {
+        Some(r) => SampleGrid::parse_range(r, step)?,
+        None => {
+            let mut grid = SampleGrid::for_payload(&file.payload)
+                .ok_or_else(|| anyhow::anyhow!("file has no sample count; pass --range <a..b>"))?;
+            grid.step = step;
+            grid
+        }
+    };
+
+    let rows = expand::expand(&file, &grid)?;
+    fs::write(output, expand::to_csv(&rows)).map_err(|e| anyhow::anyhow!("cannot write '{}': {}", output, e))?;
+    println!("✅ Expanded: {} → {} ({} samples)", input, output, rows.len());
+    Ok(())
+}
+
+/// Write a demo ASP stream: Perlin noise whose scale and persistence drift
+/// over time, with zoom motion vectors, a moving correction ROI and a
+/// heartbeat every second
+fn create_asp(args: &[String]) -> anyhow::Result<()> {
+    let mut output = "output.asp".to_string();
+    let mut frames: u32 = 120;
+    let mut fps: f32 = 30.0;
+    let mut keyframe_interval: u32 = 30;
+    let (mut width, mut height) = (256u32, 256u32);
+    let mut seed: u64 = 12345;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "-o" | "--output" if i + 1 < args.len() => {
+                output = args[i + 1].clone();
+                i += 2;
+            }
+            "--frames" if i + 1 < args.len() => {
+                frames = args[i + 1].parse()?;
+                i += 2;
+            }
+            "--fps" if i + 1 < args.len() => {
+                fps = args[i + 1].parse()?;
+                i += 2;
+            }
+            "--keyframe-interval" if i + 1 < args.len() => {
+                keyframe_interval = args[i + 1].parse()?;
+                i += 2;
+            }
+            "--size" if i + 1 < args.len() => {
+                let (w, h) = args[i + 1]
+                    .split_once('x')
+                    .ok_or_else(|| anyhow::anyhow!("size must look like WxH, got {}", args[i + 1]))?;
+                (width, height) = (w.parse()?, h.parse()?);
+                i += 2;
+            }
+            "--seed" if i + 1 < args.len() => {
+                seed = args[i + 1].parse()?;
+                i += 2;
+            }
+            _ => i += 1,
+        }
+    }
+    if !(fps.is_finite() && fps > 0.0) || frames == 0 || keyframe_interval == 0 {
+        anyhow::bail!("--frames, --fps and --keyframe-interval must be positive");
+    }
+    if !(1..=KeyframeData::MAX_DIMENSION).contains(&width) || !(1..=KeyframeData::MAX_DIMENSION).contains(&height) {
+        anyhow::bail!("--size must be 1 to {} pixels per side", KeyframeData::MAX_DIMENSION);
+    }
+
+    const BLOCK: u32 = 16;
+    const ROI: u16 = 16;
+    let mv_grid = (width.div_ceil(BLOCK) as u16, height.div_ceil(BLOCK) as u16);
+    let params_at = |frame: u32| {
+        let t = frame as f32 / fps;
+        PerlinPayload {
+            seed,
+            scale: 5.0 * (1.0 + 0.25 * (t * std::f32::consts::TAU / 4.0).sin()),
+            octaves: 6,
+            persistence: 0.5 + 0.1 * (t * std::f32::consts::TAU / 7.0).sin(),
+            lacunarity: 2.0,
+        }
+    };
+
+    let heartbeat_every = fps.round().max(1.0) as u32;
+    let mut writer = AspWriter::new(std::io::BufWriter::new(fs::File::create(&output)?));
+    let mut previous = params_at(0);
+    for frame in 0..frames {
+        let params = params_at(frame);
+        if frame % keyframe_interval == 0 {
+            writer.write_keyframe(&KeyframeData {
+                width,
+                height,
+                fps,
+                content_type: AliceContentType::Perlin,
+                params: params.to_bytes(),
+            })?;
+        } else {
+            // Noise is sampled at p·scale, so features move radially by
+            // (old/new − 1) of their distance from the centre
+            let zoom = previous.scale / params.scale - 1.0;
+            let vectors = (0..mv_grid.1 as u32)
+                .flat_map(|by| (0..mv_grid.0 as u32).map(move |bx| (bx, by)))
+                .map(|(bx, by)| {
+                    let cx = (bx * BLOCK + BLOCK / 2) as f32 - width as f32 / 2.0;
+                    let cy = (by * BLOCK + BLOCK / 2) as f32 - height as f32 / 2.0;
+                    MotionVectorCompact {
+                        dx: (cx * zoom).round().clamp(-128.0, 127.0) as i8,
+                        dy: (cy * zoom).round().clamp(-128.0, 127.0) as i8,
+                    }
+                })
+                .collect();
+            writer.write_frame(&params.to_bytes(), mv_grid, vectors)?;
+        }
+
+        // Residual patch sweeping along the diagonal
+        let span = (width.min(height).saturating_sub(ROI as u32)).max(1);
+        let offset = (frame * 4 % span) as u16;
+        let residuals = (0..ROI as usize * ROI as usize)
+            .flat_map(|p| {
+                let v = if (p / ROI as usize + p % ROI as usize) % 2 == 0 { 24 } else { -24 };
+                [v, v / 2, -v]
+            })
+            .collect();
+        writer.write_correction(&CorrectionData {
+            rois: vec![AspRoi { x: offset, y: offset, width: ROI, height: ROI, residuals }],
+        })?;
+
+        if (frame + 1) % heartbeat_every == 0 {
+            let ms = ((frame + 1) as f32 / fps * 1000.0) as u32;
+            writer.write_sync(SyncData { command: SyncCommand::Heartbeat, value: ms })?;
+        }
+        previous = params;
+    }
+    writer.write_sync(SyncData {
+        command: SyncCommand::EndOfStream,
+        value: (frames as f32 / fps * 1000.0) as u32,
+    })?;
+    let packets = writer.sequence();
+    writer.flush()?;
+    drop(writer);
+
+    let size = fs::metadata(&output)?.len();
+    let raw = width as u64 * height as u64 * 4 * frames as u64;
+    println!("✅ Created: {}", output);
+    println!("   Stream: {} frames of {}x{} @ {} fps, keyframe every {}", frames, width, height, fps, keyframe_interval);
+    println!("   Packets: {}", packets);
+    println!("   Size: {} bytes", size);
+    println!("   Compression: {:.0}x vs raw RGBA", raw as f64 / size.max(1) as f64);
+    Ok(())
+}
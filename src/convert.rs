@@ -0,0 +1,156 @@
+//! Conversion between legacy ALICE-Zip (.alz) and .alice files
+//!
+//! Procedural ALZ parameter blocks share the .alice payload encodings, so
+//! conversion re-labels the content type and carries the original size.
+//! ALZ has no metadata section: converting to .alice takes metadata from the
+//! caller, converting to .alz drops it (see [`dropped_metadata`]).
+
+use crate::decoder::alice::{
+    AliceContentType, AliceFile, AliceFileBuilder, AliceMetadata, AlicePayload,
+    PolynomialCoefficients, PolynomialPayload,
+};
+use crate::decoder::{AlzContent, AlzContentType, AlzFile};
+use anyhow::Result;
+
+/// Content that cannot be represented in the target format
+#[derive(Debug, thiserror::Error)]
+pub enum ConvertError {
+    #[error("{from} content has no {to} equivalent")]
+    NoEquivalent { from: &'static str, to: &'static str },
+}
+
+/// .alice content type matching an ALZ content type
+pub fn alice_type_for(content_type: AlzContentType) -> Option<AliceContentType> {
+    match content_type {
+        AlzContentType::RawLzma => None,
+        AlzContentType::Perlin => Some(AliceContentType::Perlin),
+        AlzContentType::Polynomial => Some(AliceContentType::Polynomial),
+        AlzContentType::Sine => Some(AliceContentType::SineWave),
+        AlzContentType::Fourier => Some(AliceContentType::Fourier),
+        AlzContentType::Fractal => Some(AliceContentType::Fractal),
+    }
+}
+
+/// ALZ content type matching an .alice content type
+///
+/// Linear models map to a degree-1 polynomial.
+pub fn alz_type_for(content_type: AliceContentType) -> Option<AlzContentType> {
+    match content_type {
+        AliceContentType::Linear | AliceContentType::Polynomial => Some(AlzContentType::Polynomial),
+        AliceContentType::Perlin => Some(AlzContentType::Perlin),
+        AliceContentType::SineWave => Some(AlzContentType::Sine),
+        AliceContentType::Fourier => Some(AlzContentType::Fourier),
+        AliceContentType::Fractal => Some(AlzContentType::Fractal),
+        AliceContentType::Voronoi | AliceContentType::Segmented | AliceContentType::Composite => None,
+    }
+}
+
+/// Convert a parsed .alz file to .alice, attaching `metadata`
+pub fn alz_to_alice(alz: &AlzFile, metadata: AliceMetadata) -> Result<AliceFile> {
+    let payload = alz.content.to_alice_payload().ok_or(ConvertError::NoEquivalent {
+        from: alz.content.content_type().name(),
+        to: ".alice",
+    })?;
+
+    AliceFileBuilder::from_payload(payload)
+        .original_size(alz.header.original_size)
+        .with_metadata(metadata)
+        .build()
+}
+
+/// Convert a parsed .alice file to .alz
+pub fn alice_to_alz(alice: &AliceFile) -> Result<AlzFile> {
+    let content = match &alice.payload {
+        AlicePayload::Linear(p) => AlzContent::Polynomial(PolynomialPayload {
+            coefficients: PolynomialCoefficients::Q16(vec![p.intercept_q16, p.slope_q16]),
+            sample_count: p.sample_count,
+        }),
+        AlicePayload::Polynomial(p) => AlzContent::Polynomial(p.clone()),
+        AlicePayload::Perlin(p) => AlzContent::Perlin(p.clone()),
+        AlicePayload::SineWave(p) => AlzContent::Sine(p.clone()),
+        AlicePayload::Fourier(p) => AlzContent::Fourier(p.clone()),
+        AlicePayload::Fractal(p) => AlzContent::Fractal(p.clone()),
+        AlicePayload::Voronoi(_) | AlicePayload::Segmented(_) | AlicePayload::Composite(_) => {
+            return Err(ConvertError::NoEquivalent {
+                from: alice.content_type_name(),
+                to: ".alz",
+            }
+            .into())
+        }
+    };
+
+    Ok(AlzFile::new(content).with_original_size(alice.header.original_size))
+}
+
+/// Names of metadata fields that a conversion to .alz would discard
+pub fn dropped_metadata(metadata: &AliceMetadata) -> Vec<String> {
+    let fields = [
+        ("sensor_id", metadata.sensor_id.is_some()),
+        ("timestamp", metadata.timestamp.is_some()),
+        ("location", metadata.location.is_some()),
+        ("unit", metadata.unit.is_some()),
+        ("description", metadata.description.is_some()),
+        ("sample_interval", metadata.sample_interval.is_some()),
+    ];
+    fields
+        .iter()
+        .filter(|(_, set)| *set)
+        .map(|(name, _)| name.to_string())
+        .chain(metadata.custom.keys().cloned())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::alice::{FourierTerm, VoronoiFeature, VoronoiMetric};
+
+    #[test]
+    fn test_alz_alice_roundtrip() {
+        let alz = AlzFile::new(AlzContent::Fourier(crate::decoder::alice::FourierPayload {
+            period: 1440.0,
+            sample_count: 1440,
+            terms: vec![FourierTerm { frequency: 1, amplitude: 5.0, phase: 0.3 }],
+        }))
+        .with_original_size(5760);
+        let alz = AlzFile::parse(&alz.to_bytes()).unwrap();
+
+        let meta = AliceMetadata { unit: Some("°C".to_string()), ..Default::default() };
+        let alice = alz_to_alice(&alz, meta).unwrap();
+        assert_eq!(alice.header.content_type, AliceContentType::Fourier);
+        assert_eq!(alice.header.original_size, 5760);
+        let alice = AliceFile::parse(&alice.to_bytes()).unwrap();
+        assert_eq!(alice.metadata.unit.as_deref(), Some("°C"));
+        assert_eq!(dropped_metadata(&alice.metadata), vec!["unit".to_string()]);
+        let timed = AliceMetadata { sample_interval: Some(0.5), ..alice.metadata.clone() };
+        assert_eq!(dropped_metadata(&timed), ["unit", "sample_interval"]);
+
+        let back = alice_to_alz(&alice).unwrap();
+        assert_eq!(back.to_bytes(), alz.to_bytes());
+    }
+
+    #[test]
+    fn test_linear_becomes_polynomial() {
+        let alice = AliceFileBuilder::from_linear(328, 25 << 16, 100).build().unwrap();
+        let alz = alice_to_alz(&alice).unwrap();
+        let AlzContent::Polynomial(p) = &alz.content else {
+            panic!("expected polynomial, got {:?}", alz.content.content_type());
+        };
+        assert_eq!(p.coefficients, PolynomialCoefficients::Q16(vec![25 << 16, 328]));
+        assert_eq!(alz.header.original_size, 400);
+    }
+
+    #[test]
+    fn test_no_equivalent() {
+        let raw = AlzFile::new(AlzContent::RawLzma(vec![1, 2, 3]));
+        let err = alz_to_alice(&raw, AliceMetadata::default()).unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(ConvertError::NoEquivalent { from: "Raw LZMA", .. })));
+        assert_eq!(err.to_string(), "Raw LZMA content has no .alice equivalent");
+
+        let voronoi = AliceFileBuilder::voronoi(1, 8.0, VoronoiMetric::Euclidean, VoronoiFeature::F1, 1.0)
+            .build()
+            .unwrap();
+        let err = alice_to_alz(&voronoi).unwrap_err();
+        assert!(err.downcast_ref::<ConvertError>().is_some());
+    }
+}
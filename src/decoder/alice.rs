@@ -13,22 +13,90 @@
 //! │   Original Size: u64 (LE)            │
 //! │   Compressed Size: u64 (LE)          │
 //! │   Metadata Length: u32 (LE)          │
-//! │   Reserved: 4 bytes                  │
+//! │   Checksum: u32 (LE, v2; reserved v1)│
+//! ├──────────────────────────────────────┤
+//! │ Payload Length: u64 (LE, big only)   │
 //! ├──────────────────────────────────────┤
 //! │ Payload (variable)                   │
 //! ├──────────────────────────────────────┤
 //! │ Metadata (JSON, optional)            │
 //! └──────────────────────────────────────┘
 //! ```
+//!
+//! Version 2 gives the flags byte meaning (see [`FLAG_CHECKSUM`],
+//! [`FLAG_COMPRESSED_METADATA`], [`FLAG_BIG_PAYLOAD`]). Version 1 files, whose
+//! flags and reserved bytes were never defined, are still read.
+//!
+//! Every `parse` in this module returns [`AliceError`] and never panics on
+//! malformed input: all offsets are bounds-checked before slicing.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Error produced when parsing .alice data
+#[derive(Debug, thiserror::Error)]
+pub enum AliceError {
+    #[error("invalid magic {0:?} (expected \"ALICE\")")]
+    BadMagic([u8; 5]),
+    #[error("unsupported format version {0}")]
+    UnsupportedVersion(u8),
+    #[error("data truncated: need {need} bytes, got {got}")]
+    Truncated { need: usize, got: usize },
+    #[error("unknown content type {0}")]
+    UnknownContentType(u8),
+    #[error("payload size mismatch: expected {expected} bytes, got {got}")]
+    PayloadSizeMismatch { expected: usize, got: usize },
+    #[error("invalid value {value} for {field}")]
+    InvalidField { field: &'static str, value: u64 },
+    #[error("invalid metadata: {0}")]
+    MetadataInvalid(#[from] MetadataError),
+}
 
-use anyhow::{bail, Context, Result};
+/// Read `N` bytes at `offset`, reporting truncation instead of panicking
+fn le_bytes<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N], AliceError> {
+    data.get(offset..offset + N)
+        .and_then(|b| b.try_into().ok())
+        .ok_or(AliceError::Truncated { need: offset + N, got: data.len() })
+}
+
+/// Ensure a fixed-size record is exactly `size` bytes long
+fn check_size(data: &[u8], size: usize) -> Result<(), AliceError> {
+    match data.len() {
+        n if n < size => Err(AliceError::Truncated { need: size, got: n }),
+        n if n > size => Err(AliceError::PayloadSizeMismatch { expected: size, got: n }),
+        _ => Ok(()),
+    }
+}
 
 /// ALICE file magic bytes
 pub const ALICE_MAGIC: &[u8; 5] = b"ALICE";
 
 /// Current format version
 #[allow(dead_code)] // Used in AliceFileBuilder::build() for file format versioning
-pub const ALICE_VERSION: u8 = 1;
+pub const ALICE_VERSION: u8 = 2;
+
+/// Header flag (v2): CRC32 of the payload is stored in bytes 28..32
+pub const FLAG_CHECKSUM: u8 = 0x01;
+/// Header flag (v2): metadata JSON is deflate-compressed
+pub const FLAG_COMPRESSED_METADATA: u8 = 0x02;
+/// Header flag (v2): a u64 payload length follows the header
+pub const FLAG_BIG_PAYLOAD: u8 = 0x04;
+const KNOWN_FLAGS: u8 = FLAG_CHECKSUM | FLAG_COMPRESSED_METADATA | FLAG_BIG_PAYLOAD;
+
+/// Metadata at least this long is written compressed (if that makes it smaller)
+const METADATA_COMPRESS_THRESHOLD: usize = 256;
+/// Upper bound on decompressed metadata, guards against deflate bombs
+const MAX_METADATA_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Result of verifying the payload checksum
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChecksumStatus {
+    /// File carries no checksum (v1, or flag not set)
+    #[default]
+    Absent,
+    Valid(u32),
+    Mismatch { stored: u32, computed: u32 },
+}
 
 /// Content types stored in .alice files
 #[repr(u8)]
@@ -48,12 +116,16 @@ pub enum AliceContentType {
     Voronoi = 5,
     /// Sine wave composition
     SineWave = 6,
+    /// Piecewise model: ordered linear / polynomial segments
+    Segmented = 7,
+    /// Layered composite of other payloads
+    Composite = 8,
 }
 
 impl TryFrom<u8> for AliceContentType {
-    type Error = anyhow::Error;
+    type Error = AliceError;
 
-    fn try_from(value: u8) -> Result<Self> {
+    fn try_from(value: u8) -> Result<Self, AliceError> {
         match value {
             0 => Ok(Self::Linear),
             1 => Ok(Self::Polynomial),
@@ -62,7 +134,9 @@ impl TryFrom<u8> for AliceContentType {
             4 => Ok(Self::Fourier),
             5 => Ok(Self::Voronoi),
             6 => Ok(Self::SineWave),
-            _ => bail!("Unknown content type: {}", value),
+            7 => Ok(Self::Segmented),
+            8 => Ok(Self::Composite),
+            _ => Err(AliceError::UnknownContentType(value)),
         }
     }
 }
@@ -77,6 +151,8 @@ impl AliceContentType {
             Self::Fourier => "Fourier Series",
             Self::Voronoi => "Voronoi",
             Self::SineWave => "Sine Wave",
+            Self::Segmented => "Segmented",
+            Self::Composite => "Composite",
         }
     }
 }
@@ -91,9 +167,14 @@ pub struct AliceHeader {
     pub original_size: u64,
     pub compressed_size: u64,
     pub metadata_length: u32,
+    /// Stored payload CRC32 (v2 with [`FLAG_CHECKSUM`], otherwise 0)
+    pub checksum: u32,
+    /// Explicit payload length (v2 with [`FLAG_BIG_PAYLOAD`])
+    pub payload_length: Option<u64>,
+    /// Outcome of checksum verification in [`AliceHeader::parse`]
+    pub checksum_status: ChecksumStatus,
 }
 
-
 // AliceHeader fields (magic, version, flags) are used in to_bytes() and by
 // alice-create binary. Clippy flags them because derived Clone/Debug are
 // excluded from dead-code analysis.
@@ -101,27 +182,47 @@ pub struct AliceHeader {
 impl AliceHeader {
     pub const SIZE: usize = 32;
 
-    /// Parse header from bytes
-    pub fn parse(data: &[u8]) -> Result<Self> {
+    /// Parse header from the start of a complete .alice file
+    ///
+    /// For v2 files the payload checksum is verified against `data`; a
+    /// mismatch is reported in `checksum_status` rather than as an error so
+    /// damaged files can still be inspected.
+    pub fn parse(data: &[u8]) -> Result<Self, AliceError> {
         if data.len() < Self::SIZE {
-            bail!("Header too short: {} bytes (need {})", data.len(), Self::SIZE);
+            return Err(AliceError::Truncated { need: Self::SIZE, got: data.len() });
         }
 
-        let mut magic = [0u8; 5];
-        magic.copy_from_slice(&data[0..5]);
-
+        let magic: [u8; 5] = le_bytes(data, 0)?;
         if &magic != ALICE_MAGIC {
-            bail!("Invalid magic: {:?} (expected {:?})", magic, ALICE_MAGIC);
+            return Err(AliceError::BadMagic(magic));
         }
 
         let version = data[5];
+        if version == 0 || version > ALICE_VERSION {
+            return Err(AliceError::UnsupportedVersion(version));
+        }
         let content_type = AliceContentType::try_from(data[6])?;
-        let flags = data[7];
-        let original_size = u64::from_le_bytes(data[8..16].try_into()?);
-        let compressed_size = u64::from_le_bytes(data[16..24].try_into()?);
-        let metadata_length = u32::from_le_bytes(data[24..28].try_into()?);
+        let original_size = u64::from_le_bytes(le_bytes(data, 8)?);
+        let compressed_size = u64::from_le_bytes(le_bytes(data, 16)?);
+        let metadata_length = u32::from_le_bytes(le_bytes(data, 24)?);
 
-        Ok(Self {
+        // v1 never defined flags or the reserved bytes: ignore them
+        let (flags, checksum) = if version == 1 {
+            (0, 0)
+        } else {
+            (data[7], u32::from_le_bytes(le_bytes(data, 28)?))
+        };
+        if flags & !KNOWN_FLAGS != 0 {
+            return Err(AliceError::InvalidField { field: "header flags", value: flags as u64 });
+        }
+
+        let payload_length = if flags & FLAG_BIG_PAYLOAD != 0 {
+            Some(u64::from_le_bytes(le_bytes(data, Self::SIZE)?))
+        } else {
+            None
+        };
+
+        let mut header = Self {
             magic,
             version,
             content_type,
@@ -129,7 +230,50 @@ impl AliceHeader {
             original_size,
             compressed_size,
             metadata_length,
-        })
+            checksum,
+            payload_length,
+            checksum_status: ChecksumStatus::Absent,
+        };
+
+        if header.has_checksum() {
+            let payload = &data[header.payload_range(data.len())?];
+            let computed = crc32fast::hash(payload);
+            header.checksum_status = if computed == checksum {
+                ChecksumStatus::Valid(computed)
+            } else {
+                ChecksumStatus::Mismatch { stored: checksum, computed }
+            };
+        }
+
+        Ok(header)
+    }
+
+    /// Byte range of the payload within a file of `file_len` bytes
+    pub fn payload_range(&self, file_len: usize) -> Result<std::ops::Range<usize>, AliceError> {
+        let metadata_length = self.metadata_length as usize;
+        match self.payload_length {
+            Some(len) => {
+                let start = Self::SIZE + 8;
+                let expected = usize::try_from(len)
+                    .ok()
+                    .and_then(|n| n.checked_add(start + metadata_length))
+                    .unwrap_or(usize::MAX);
+                if file_len < expected {
+                    return Err(AliceError::Truncated { need: expected, got: file_len });
+                }
+                if file_len > expected {
+                    return Err(AliceError::PayloadSizeMismatch { expected, got: file_len });
+                }
+                Ok(start..file_len - metadata_length)
+            }
+            None => match file_len.checked_sub(metadata_length) {
+                Some(end) if end >= Self::SIZE => Ok(Self::SIZE..end),
+                _ => Err(AliceError::Truncated {
+                    need: Self::SIZE + metadata_length,
+                    got: file_len,
+                }),
+            },
+        }
     }
 
     /// Serialize header to bytes
@@ -142,6 +286,7 @@ impl AliceHeader {
         buf[8..16].copy_from_slice(&self.original_size.to_le_bytes());
         buf[16..24].copy_from_slice(&self.compressed_size.to_le_bytes());
         buf[24..28].copy_from_slice(&self.metadata_length.to_le_bytes());
+        buf[28..32].copy_from_slice(&self.checksum.to_le_bytes());
         buf
     }
 
@@ -150,6 +295,16 @@ impl AliceHeader {
         self.metadata_length > 0
     }
 
+    /// Check if the payload is covered by a CRC32
+    pub fn has_checksum(&self) -> bool {
+        self.flags & FLAG_CHECKSUM != 0
+    }
+
+    /// Check if the metadata block is deflate-compressed
+    pub fn has_compressed_metadata(&self) -> bool {
+        self.flags & FLAG_COMPRESSED_METADATA != 0
+    }
+
     /// Get compression ratio
     pub fn compression_ratio(&self) -> f64 {
         if self.compressed_size > 0 {
@@ -179,14 +334,15 @@ impl LinearPayload {
     pub const SIZE: usize = 12;
 
     /// Parse from bytes
-    pub fn parse(data: &[u8]) -> Result<Self> {
-        if data.len() < 8 {
-            bail!("Linear payload too short");
-        }
-        let slope_q16 = i32::from_le_bytes(data[0..4].try_into()?);
-        let intercept_q16 = i32::from_le_bytes(data[4..8].try_into()?);
-        let sample_count = if data.len() >= 12 {
-            u32::from_le_bytes(data[8..12].try_into()?)
+    pub fn parse(data: &[u8]) -> Result<Self, AliceError> {
+        // The sample count is optional: 8 or 12 bytes
+        if data.len() != 8 {
+            check_size(data, Self::SIZE)?;
+        }
+        let slope_q16 = i32::from_le_bytes(le_bytes(data, 0)?);
+        let intercept_q16 = i32::from_le_bytes(le_bytes(data, 4)?);
+        let sample_count = if data.len() == Self::SIZE {
+            u32::from_le_bytes(le_bytes(data, 8)?)
         } else {
             0
         };
@@ -258,16 +414,14 @@ pub struct PerlinPayload {
 impl PerlinPayload {
     pub const SIZE: usize = 24;
 
-    pub fn parse(data: &[u8]) -> Result<Self> {
-        if data.len() < Self::SIZE {
-            bail!("Perlin payload too short");
-        }
+    pub fn parse(data: &[u8]) -> Result<Self, AliceError> {
+        check_size(data, Self::SIZE)?;
         Ok(Self {
-            seed: u64::from_le_bytes(data[0..8].try_into()?),
-            scale: f32::from_le_bytes(data[8..12].try_into()?),
-            octaves: u32::from_le_bytes(data[12..16].try_into()?),
-            persistence: f32::from_le_bytes(data[16..20].try_into()?),
-            lacunarity: f32::from_le_bytes(data[20..24].try_into()?),
+            seed: u64::from_le_bytes(le_bytes(data, 0)?),
+            scale: f32::from_le_bytes(le_bytes(data, 8)?),
+            octaves: u32::from_le_bytes(le_bytes(data, 12)?),
+            persistence: f32::from_le_bytes(le_bytes(data, 16)?),
+            lacunarity: f32::from_le_bytes(le_bytes(data, 20)?),
         })
     }
 
@@ -306,20 +460,18 @@ pub struct FractalPayload {
 // FractalPayload serialization used by alice-create binary.
 #[allow(dead_code)]
 impl FractalPayload {
-    pub const SIZE: usize = 45;
+    pub const SIZE: usize = 41;
 
-    pub fn parse(data: &[u8]) -> Result<Self> {
-        if data.len() < Self::SIZE {
-            bail!("Fractal payload too short");
-        }
+    pub fn parse(data: &[u8]) -> Result<Self, AliceError> {
+        check_size(data, Self::SIZE)?;
         Ok(Self {
             fractal_type: data[0],
-            max_iterations: u32::from_le_bytes(data[1..5].try_into()?),
-            escape_radius: f32::from_le_bytes(data[5..9].try_into()?),
-            center_x: f64::from_le_bytes(data[9..17].try_into()?),
-            center_y: f64::from_le_bytes(data[17..25].try_into()?),
-            julia_cx: f64::from_le_bytes(data[25..33].try_into()?),
-            julia_cy: f64::from_le_bytes(data[33..41].try_into()?),
+            max_iterations: u32::from_le_bytes(le_bytes(data, 1)?),
+            escape_radius: f32::from_le_bytes(le_bytes(data, 5)?),
+            center_x: f64::from_le_bytes(le_bytes(data, 9)?),
+            center_y: f64::from_le_bytes(le_bytes(data, 17)?),
+            julia_cx: f64::from_le_bytes(le_bytes(data, 25)?),
+            julia_cy: f64::from_le_bytes(le_bytes(data, 33)?),
         })
     }
 
@@ -368,232 +520,1421 @@ impl FractalPayload {
     }
 }
 
-/// Parsed content from .alice file
-#[derive(Debug, Clone)]
-pub enum AlicePayload {
-    Linear(LinearPayload),
-    Perlin(PerlinPayload),
-    Fractal(FractalPayload),
-    // TODO: Polynomial, Fourier, Voronoi, SineWave
+/// Superscript rendering for polynomial exponents
+fn superscript(n: usize) -> String {
+    const DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+    n.to_string()
+        .chars()
+        .map(|c| DIGITS[c.to_digit(10).unwrap_or(0) as usize])
+        .collect()
 }
 
-impl AlicePayload {
-    /// Get human-readable equation string
-    pub fn equation_string(&self) -> String {
-        match self {
-            Self::Linear(p) => p.equation_string(),
-            Self::Perlin(p) => p.equation_string(),
-            Self::Fractal(p) => p.equation_string(),
-        }
-    }
+/// Polynomial coefficient storage (ascending powers: c0 + c1·x + c2·x² + ...)
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolynomialCoefficients {
+    /// Q16.16 fixed point (ALICE-Edge output)
+    Q16(Vec<i32>),
+    /// Full double precision
+    F64(Vec<f64>),
 }
 
-/// Metadata stored in .alice file (JSON)
-#[derive(Debug, Clone, Default)]
-pub struct AliceMetadata {
-    /// Sensor ID
-    pub sensor_id: Option<String>,
-    /// Timestamp (ISO 8601)
-    pub timestamp: Option<String>,
-    /// Location
-    pub location: Option<String>,
-    /// Unit of measurement
-    pub unit: Option<String>,
-    /// Description
-    pub description: Option<String>,
-    /// Custom fields (JSON)
-    pub custom: Option<String>,
+/// Polynomial model payload: y = Σ(coef[i] * x^i)
+///
+/// Layout: encoding (u8, 0=Q16 1=f64), degree (u8), reserved (u16),
+/// sample count (u32), then degree + 1 coefficients.
+#[derive(Debug, Clone)]
+pub struct PolynomialPayload {
+    pub coefficients: PolynomialCoefficients,
+    /// Sample count (optional, for display)
+    pub sample_count: u32,
 }
 
-// AliceMetadata serialization used by alice-create binary.
+// PolynomialPayload serialization used by alice-create binary.
 #[allow(dead_code)]
-impl AliceMetadata {
-    /// Parse from JSON bytes
-    pub fn parse(data: &[u8]) -> Result<Self> {
-        if data.is_empty() {
-            return Ok(Self::default());
+impl PolynomialPayload {
+    pub const HEADER_SIZE: usize = 8;
+    pub const ENCODING_Q16: u8 = 0;
+    pub const ENCODING_F64: u8 = 1;
+    /// Coefficients the renderer draws (16 term slots of four); the u8
+    /// degree field could hold up to 256
+    pub const MAX_COEFFICIENTS: usize = 64;
+
+    pub fn parse(data: &[u8]) -> Result<Self, AliceError> {
+        if data.len() < Self::HEADER_SIZE {
+            return Err(AliceError::Truncated { need: Self::HEADER_SIZE, got: data.len() });
         }
+        let encoding = data[0];
+        let degree = data[1] as usize;
+        let sample_count = u32::from_le_bytes(le_bytes(data, 4)?);
+        let count = degree + 1;
+
+        let coefficients = match encoding {
+            Self::ENCODING_Q16 => {
+                check_size(data, Self::HEADER_SIZE + count * 4)?;
+                let coefs = (0..count)
+                    .map(|i| le_bytes(data, Self::HEADER_SIZE + i * 4).map(i32::from_le_bytes))
+                    .collect::<Result<Vec<_>, _>>()?;
+                PolynomialCoefficients::Q16(coefs)
+            }
+            Self::ENCODING_F64 => {
+                check_size(data, Self::HEADER_SIZE + count * 8)?;
+                let coefs = (0..count)
+                    .map(|i| le_bytes(data, Self::HEADER_SIZE + i * 8).map(f64::from_le_bytes))
+                    .collect::<Result<Vec<_>, _>>()?;
+                PolynomialCoefficients::F64(coefs)
+            }
+            other => {
+                return Err(AliceError::InvalidField {
+                    field: "polynomial encoding",
+                    value: other as u64,
+                })
+            }
+        };
+
+        Ok(Self {
+            coefficients,
+            sample_count,
+        })
+    }
 
-        let json_str = std::str::from_utf8(data).context("Invalid UTF-8 in metadata")?;
+    /// Check there are 1 to [`MAX_COEFFICIENTS`](Self::MAX_COEFFICIENTS)
+    /// coefficients
+    pub fn validate(&self) -> Result<(), AliceError> {
+        let count = self.len();
+        if count == 0 || count > Self::MAX_COEFFICIENTS {
+            return Err(AliceError::InvalidField { field: "polynomial coefficient count", value: count as u64 });
+        }
+        Ok(())
+    }
 
-        // Simple JSON parsing (no serde dependency)
-        let mut meta = Self::default();
+    /// Polynomial degree (highest power)
+    pub fn degree(&self) -> usize {
+        self.len().saturating_sub(1)
+    }
 
-        // Extract fields manually (simple approach)
-        if let Some(start) = json_str.find("\"sensor_id\":\"") {
-            let rest = &json_str[start + 13..];
-            if let Some(end) = rest.find('"') {
-                meta.sensor_id = Some(rest[..end].to_string());
-            }
+    fn len(&self) -> usize {
+        match &self.coefficients {
+            PolynomialCoefficients::Q16(c) => c.len(),
+            PolynomialCoefficients::F64(c) => c.len(),
         }
-        if let Some(start) = json_str.find("\"timestamp\":\"") {
-            let rest = &json_str[start + 13..];
-            if let Some(end) = rest.find('"') {
-                meta.timestamp = Some(rest[..end].to_string());
-            }
+    }
+
+    /// Encoding name for display
+    pub fn encoding_name(&self) -> &'static str {
+        match self.coefficients {
+            PolynomialCoefficients::Q16(_) => "Q16.16",
+            PolynomialCoefficients::F64(_) => "f64",
         }
-        if let Some(start) = json_str.find("\"location\":\"") {
-            let rest = &json_str[start + 12..];
-            if let Some(end) = rest.find('"') {
-                meta.location = Some(rest[..end].to_string());
-            }
+    }
+
+    /// Coefficients as f64 (ascending powers)
+    pub fn coefficients_f64(&self) -> Vec<f64> {
+        match &self.coefficients {
+            PolynomialCoefficients::Q16(c) => c.iter().map(|&v| v as f64 / 65536.0).collect(),
+            PolynomialCoefficients::F64(c) => c.clone(),
         }
-        if let Some(start) = json_str.find("\"unit\":\"") {
-            let rest = &json_str[start + 8..];
-            if let Some(end) = rest.find('"') {
-                meta.unit = Some(rest[..end].to_string());
+    }
+
+    /// Evaluate at point x (Horner's method)
+    pub fn evaluate(&self, x: f64) -> f64 {
+        self.coefficients_f64()
+            .iter()
+            .rev()
+            .fold(0.0, |acc, &c| acc * x + c)
+    }
+
+    /// Get human-readable equation string (highest power first)
+    pub fn equation_string(&self) -> String {
+        let coefs = self.coefficients_f64();
+        let mut out = String::from("y =");
+        let mut first = true;
+
+        for (power, &c) in coefs.iter().enumerate().rev() {
+            if c.abs() < 1e-10 {
+                continue;
+            }
+            let term = match power {
+                0 => format!("{:.4}", c.abs()),
+                1 => format!("{:.6}x", c.abs()),
+                _ => format!("{:.6}x{}", c.abs(), superscript(power)),
+            };
+            if first {
+                out.push_str(if c < 0.0 { " -" } else { " " });
+                out.push_str(&term);
+                first = false;
+            } else {
+                out.push_str(if c < 0.0 { " - " } else { " + " });
+                out.push_str(&term);
             }
         }
-        if let Some(start) = json_str.find("\"description\":\"") {
-            let rest = &json_str[start + 15..];
-            if let Some(end) = rest.find('"') {
-                meta.description = Some(rest[..end].to_string());
+
+        if first {
+            out.push_str(" 0");
+        }
+        out
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let degree = self.degree().min(u8::MAX as usize);
+        let mut buf = Vec::with_capacity(Self::HEADER_SIZE + (degree + 1) * 8);
+        let encoding = match self.coefficients {
+            PolynomialCoefficients::Q16(_) => Self::ENCODING_Q16,
+            PolynomialCoefficients::F64(_) => Self::ENCODING_F64,
+        };
+        buf.push(encoding);
+        buf.push(degree as u8);
+        buf.extend_from_slice(&[0, 0]);
+        buf.extend_from_slice(&self.sample_count.to_le_bytes());
+        match &self.coefficients {
+            PolynomialCoefficients::Q16(c) => {
+                for v in c.iter().take(degree + 1) {
+                    buf.extend_from_slice(&v.to_le_bytes());
+                }
+            }
+            PolynomialCoefficients::F64(c) => {
+                for v in c.iter().take(degree + 1) {
+                    buf.extend_from_slice(&v.to_le_bytes());
+                }
             }
         }
+        buf
+    }
+}
+
+/// Single Fourier term: amplitude · cos(2π · frequency · t / period + phase)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FourierTerm {
+    /// Harmonic index (0 = DC offset)
+    pub frequency: u32,
+    pub amplitude: f32,
+    /// Phase in radians
+    pub phase: f32,
+}
+
+/// Fourier series payload: y(t) = Σ aₖ·cos(2π·kₖ·t/T + φₖ)
+///
+/// Layout: base period T (f64), sample count (u32), term count (u32),
+/// then 12 bytes per term (frequency u32, amplitude f32, phase f32).
+#[derive(Debug, Clone)]
+pub struct FourierPayload {
+    /// Base period in samples
+    pub period: f64,
+    /// Sample count (optional, for display)
+    pub sample_count: u32,
+    pub terms: Vec<FourierTerm>,
+}
+
+// FourierPayload serialization used by alice-create binary.
+#[allow(dead_code)]
+impl FourierPayload {
+    pub const HEADER_SIZE: usize = 16;
+    pub const TERM_SIZE: usize = 12;
+    /// Maximum number of terms (matches the renderer's term slots)
+    pub const MAX_TERMS: usize = 16;
+
+    pub fn parse(data: &[u8]) -> Result<Self, AliceError> {
+        if data.len() < Self::HEADER_SIZE {
+            return Err(AliceError::Truncated { need: Self::HEADER_SIZE, got: data.len() });
+        }
+        let period = f64::from_le_bytes(le_bytes(data, 0)?);
+        let sample_count = u32::from_le_bytes(le_bytes(data, 8)?);
+        let term_count = u32::from_le_bytes(le_bytes(data, 12)?) as usize;
+        if term_count > Self::MAX_TERMS {
+            return Err(AliceError::InvalidField { field: "fourier term count", value: term_count as u64 });
+        }
+        check_size(data, Self::HEADER_SIZE + term_count * Self::TERM_SIZE)?;
+
+        let mut terms = Vec::with_capacity(term_count);
+        for chunk in data[Self::HEADER_SIZE..].chunks_exact(Self::TERM_SIZE) {
+            terms.push(FourierTerm {
+                frequency: u32::from_le_bytes(le_bytes(chunk, 0)?),
+                amplitude: f32::from_le_bytes(le_bytes(chunk, 4)?),
+                phase: f32::from_le_bytes(le_bytes(chunk, 8)?),
+            });
+        }
+
+        Ok(Self {
+            period,
+            sample_count,
+            terms,
+        })
+    }
+
+    /// Check the term count fits the renderer
+    pub fn validate(&self) -> Result<(), AliceError> {
+        if self.terms.len() > Self::MAX_TERMS {
+            return Err(AliceError::InvalidField { field: "fourier term count", value: self.terms.len() as u64 });
+        }
+        Ok(())
+    }
 
-        meta.custom = Some(json_str.to_string());
-        Ok(meta)
+    /// Evaluate at time t (in samples)
+    pub fn evaluate(&self, t: f64) -> f64 {
+        if self.period == 0.0 {
+            return 0.0;
+        }
+        let w = std::f64::consts::TAU / self.period;
+        self.terms
+            .iter()
+            .map(|term| term.amplitude as f64 * (w * term.frequency as f64 * t + term.phase as f64).cos())
+            .sum()
     }
 
-    /// Serialize to JSON bytes
-    pub fn to_json(&self) -> Vec<u8> {
+    /// Get human-readable equation string (first few terms)
+    pub fn equation_string(&self) -> String {
+        const MAX_SHOWN: usize = 3;
         let mut parts = Vec::new();
-        if let Some(ref id) = self.sensor_id {
-            parts.push(format!("\"sensor_id\":\"{}\"", id));
-        }
-        if let Some(ref ts) = self.timestamp {
-            parts.push(format!("\"timestamp\":\"{}\"", ts));
+        for term in self.terms.iter().take(MAX_SHOWN) {
+            if term.frequency == 0 {
+                parts.push(format!("{:.4}", term.amplitude * term.phase.cos()));
+            } else {
+                parts.push(format!(
+                    "{:.4}·cos(2π·{}t/{} {} {:.3})",
+                    term.amplitude,
+                    term.frequency,
+                    self.period,
+                    if term.phase < 0.0 { "-" } else { "+" },
+                    term.phase.abs()
+                ));
+            }
         }
-        if let Some(ref loc) = self.location {
-            parts.push(format!("\"location\":\"{}\"", loc));
+        if parts.is_empty() {
+            return "y = 0".to_string();
         }
-        if let Some(ref unit) = self.unit {
-            parts.push(format!("\"unit\":\"{}\"", unit));
+        let mut out = format!("y = {}", parts.join(" + "));
+        if self.terms.len() > MAX_SHOWN {
+            out.push_str(&format!(" + … ({} terms)", self.terms.len()));
         }
-        if let Some(ref desc) = self.description {
-            parts.push(format!("\"description\":\"{}\"", desc));
+        out
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(Self::HEADER_SIZE + self.terms.len() * Self::TERM_SIZE);
+        buf.extend_from_slice(&self.period.to_le_bytes());
+        buf.extend_from_slice(&self.sample_count.to_le_bytes());
+        buf.extend_from_slice(&(self.terms.len() as u32).to_le_bytes());
+        for term in &self.terms {
+            buf.extend_from_slice(&term.frequency.to_le_bytes());
+            buf.extend_from_slice(&term.amplitude.to_le_bytes());
+            buf.extend_from_slice(&term.phase.to_le_bytes());
         }
-        format!("{{{}}}", parts.join(",")).into_bytes()
+        buf
     }
 }
 
-/// Complete parsed .alice file
+/// Single sinusoid: amplitude · sin(2π · frequency · x + phase)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SineComponent {
+    /// Cycles per x unit
+    pub frequency: f32,
+    pub amplitude: f32,
+    /// Phase in radians
+    pub phase: f32,
+}
+
+/// Sine wave composition payload: y = offset + Σ aᵢ·sin(2π·fᵢ·x + φᵢ)
+///
+/// Layout: offset (f32), sample count (u32), component count (u8),
+/// reserved (3 bytes), then 12 bytes per component (frequency, amplitude, phase).
 #[derive(Debug, Clone)]
-pub struct AliceFile {
-    pub header: AliceHeader,
-    pub payload: AlicePayload,
-    pub metadata: AliceMetadata,
+pub struct SineWavePayload {
+    /// Constant offset
+    pub offset: f32,
+    /// Sample count (optional, for display)
+    pub sample_count: u32,
+    pub components: Vec<SineComponent>,
 }
 
-// AliceFile::to_bytes is used by alice-create binary.
+// SineWavePayload serialization used by alice-create binary.
 #[allow(dead_code)]
-impl AliceFile {
-    /// Parse .alice file from bytes
-    pub fn parse(data: &[u8]) -> Result<Self> {
-        let header = AliceHeader::parse(data)?;
+impl SineWavePayload {
+    pub const HEADER_SIZE: usize = 12;
+    pub const COMPONENT_SIZE: usize = 12;
+    /// Maximum number of summed sinusoids
+    pub const MAX_COMPONENTS: usize = 16;
+
+    pub fn parse(data: &[u8]) -> Result<Self, AliceError> {
+        if data.len() < Self::HEADER_SIZE {
+            return Err(AliceError::Truncated { need: Self::HEADER_SIZE, got: data.len() });
+        }
+        let offset = f32::from_le_bytes(le_bytes(data, 0)?);
+        let sample_count = u32::from_le_bytes(le_bytes(data, 4)?);
+        let count = data[8] as usize;
+        if count > Self::MAX_COMPONENTS {
+            return Err(AliceError::InvalidField { field: "sine component count", value: count as u64 });
+        }
+        check_size(data, Self::HEADER_SIZE + count * Self::COMPONENT_SIZE)?;
+
+        let mut components = Vec::with_capacity(count);
+        for chunk in data[Self::HEADER_SIZE..].chunks_exact(Self::COMPONENT_SIZE) {
+            components.push(SineComponent {
+                frequency: f32::from_le_bytes(le_bytes(chunk, 0)?),
+                amplitude: f32::from_le_bytes(le_bytes(chunk, 4)?),
+                phase: f32::from_le_bytes(le_bytes(chunk, 8)?),
+            });
+        }
 
-        let payload_start = AliceHeader::SIZE;
-        let payload_end = data.len() - header.metadata_length as usize;
+        Ok(Self {
+            offset,
+            sample_count,
+            components,
+        })
+    }
 
-        if payload_end < payload_start {
-            bail!("Invalid payload bounds");
+    /// Check the component count fits the renderer
+    pub fn validate(&self) -> Result<(), AliceError> {
+        if self.components.len() > Self::MAX_COMPONENTS {
+            return Err(AliceError::InvalidField {
+                field: "sine component count",
+                value: self.components.len() as u64,
+            });
         }
+        Ok(())
+    }
 
-        let payload_data = &data[payload_start..payload_end];
-        let payload = match header.content_type {
-            AliceContentType::Linear => AlicePayload::Linear(LinearPayload::parse(payload_data)?),
-            AliceContentType::Perlin => AlicePayload::Perlin(PerlinPayload::parse(payload_data)?),
-            AliceContentType::Fractal => AlicePayload::Fractal(FractalPayload::parse(payload_data)?),
-            _ => bail!("Unsupported content type: {:?}", header.content_type),
-        };
-
-        let metadata = if header.has_metadata() {
-            let meta_data = &data[payload_end..];
-            AliceMetadata::parse(meta_data)?
-        } else {
-            AliceMetadata::default()
-        };
+    /// Evaluate at point x
+    pub fn evaluate(&self, x: f64) -> f64 {
+        self.offset as f64
+            + self
+                .components
+                .iter()
+                .map(|c| {
+                    c.amplitude as f64
+                        * (std::f64::consts::TAU * c.frequency as f64 * x + c.phase as f64).sin()
+                })
+                .sum::<f64>()
+    }
 
-        Ok(Self {
-            header,
-            payload,
-            metadata,
-        })
+    /// Get human-readable equation string
+    pub fn equation_string(&self) -> String {
+        let mut parts: Vec<String> = self
+            .components
+            .iter()
+            .map(|c| {
+                if c.phase.abs() < 1e-6 {
+                    format!("{:.4}·sin(2π·{:.4}x)", c.amplitude, c.frequency)
+                } else {
+                    format!(
+                        "{:.4}·sin(2π·{:.4}x {} {:.3})",
+                        c.amplitude,
+                        c.frequency,
+                        if c.phase < 0.0 { "-" } else { "+" },
+                        c.phase.abs()
+                    )
+                }
+            })
+            .collect();
+        if self.offset.abs() >= 1e-6 || parts.is_empty() {
+            parts.push(format!("{:.4}", self.offset));
+        }
+        format!("y = {}", parts.join(" + "))
     }
 
-    /// Serialize to bytes
     pub fn to_bytes(&self) -> Vec<u8> {
-        let payload_bytes = match &self.payload {
-            AlicePayload::Linear(p) => p.to_bytes(),
-            AlicePayload::Perlin(p) => p.to_bytes(),
-            AlicePayload::Fractal(p) => p.to_bytes(),
-        };
+        let count = self.components.len().min(Self::MAX_COMPONENTS);
+        let mut buf = Vec::with_capacity(Self::HEADER_SIZE + count * Self::COMPONENT_SIZE);
+        buf.extend_from_slice(&self.offset.to_le_bytes());
+        buf.extend_from_slice(&self.sample_count.to_le_bytes());
+        buf.push(count as u8);
+        buf.extend_from_slice(&[0, 0, 0]);
+        for c in self.components.iter().take(count) {
+            buf.extend_from_slice(&c.frequency.to_le_bytes());
+            buf.extend_from_slice(&c.amplitude.to_le_bytes());
+            buf.extend_from_slice(&c.phase.to_le_bytes());
+        }
+        buf
+    }
+}
 
-        let meta_bytes = self.metadata.to_json();
+/// Voronoi distance metric
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoronoiMetric {
+    Euclidean = 0,
+    Manhattan = 1,
+    Chebyshev = 2,
+}
 
-        let mut header = self.header.clone();
-        header.metadata_length = meta_bytes.len() as u32;
+impl TryFrom<u8> for VoronoiMetric {
+    type Error = AliceError;
 
-        let mut out = Vec::new();
-        out.extend_from_slice(&header.to_bytes());
-        out.extend_from_slice(&payload_bytes);
-        out.extend_from_slice(&meta_bytes);
-        out
+    fn try_from(value: u8) -> Result<Self, AliceError> {
+        match value {
+            0 => Ok(Self::Euclidean),
+            1 => Ok(Self::Manhattan),
+            2 => Ok(Self::Chebyshev),
+            _ => Err(AliceError::InvalidField { field: "Voronoi metric", value: value as u64 }),
+        }
     }
+}
 
-    /// Get equation string
-    pub fn equation_string(&self) -> String {
-        self.payload.equation_string()
+// from_name is used by alice-create binary.
+#[allow(dead_code)]
+impl VoronoiMetric {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Euclidean => "Euclidean",
+            Self::Manhattan => "Manhattan",
+            Self::Chebyshev => "Chebyshev",
+        }
     }
 
-    /// Get content type name
-    pub fn content_type_name(&self) -> &'static str {
-        self.header.content_type.name()
+    /// Parse from a case-insensitive name (CLI)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "euclidean" => Some(Self::Euclidean),
+            "manhattan" => Some(Self::Manhattan),
+            "chebyshev" => Some(Self::Chebyshev),
+            _ => None,
+        }
     }
+}
 
-    /// Get compression ratio
-    pub fn compression_ratio(&self) -> f64 {
-        self.header.compression_ratio()
-    }
+/// Voronoi output feature (distance to nearest / second-nearest point)
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoronoiFeature {
+    F1 = 0,
+    F2 = 1,
+    F2MinusF1 = 2,
 }
 
-/// Builder for creating .alice files
-// Used exclusively by alice-create binary.
-#[allow(dead_code)]
-pub struct AliceFileBuilder {
-    content_type: AliceContentType,
-    original_size: u64,
-    payload: Option<AlicePayload>,
-    metadata: AliceMetadata,
+impl TryFrom<u8> for VoronoiFeature {
+    type Error = AliceError;
+
+    fn try_from(value: u8) -> Result<Self, AliceError> {
+        match value {
+            0 => Ok(Self::F1),
+            1 => Ok(Self::F2),
+            2 => Ok(Self::F2MinusF1),
+            _ => Err(AliceError::InvalidField { field: "Voronoi feature", value: value as u64 }),
+        }
+    }
 }
 
+// from_name is used by alice-create binary.
 #[allow(dead_code)]
-impl AliceFileBuilder {
-    pub fn new(content_type: AliceContentType) -> Self {
-        Self {
-            content_type,
-            original_size: 0,
-            payload: None,
-            metadata: AliceMetadata::default(),
+impl VoronoiFeature {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::F1 => "F1",
+            Self::F2 => "F2",
+            Self::F2MinusF1 => "F2-F1",
         }
     }
 
-    /// Create from ALICE-Edge linear model output
-    pub fn from_linear(slope_q16: i32, intercept_q16: i32, sample_count: u32) -> Self {
-        let mut builder = Self::new(AliceContentType::Linear);
-        builder.original_size = sample_count as u64 * 4; // 4 bytes per sample
-        builder.payload = Some(AlicePayload::Linear(LinearPayload {
-            slope_q16,
-            intercept_q16,
-            sample_count,
-        }));
-        builder
+    /// Parse from a case-insensitive name (CLI)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "f1" => Some(Self::F1),
+            "f2" => Some(Self::F2),
+            "f2-f1" | "f2f1" => Some(Self::F2MinusF1),
+            _ => None,
+        }
     }
+}
 
-    /// Create Mandelbrot fractal
-    pub fn mandelbrot(max_iterations: u32, center_x: f64, center_y: f64) -> Self {
-        let mut builder = Self::new(AliceContentType::Fractal);
-        builder.payload = Some(AlicePayload::Fractal(FractalPayload {
+/// Voronoi pattern payload
+#[derive(Debug, Clone)]
+pub struct VoronoiPayload {
+    pub seed: u64,
+    /// Cells per unit length
+    pub cell_density: f32,
+    pub metric: VoronoiMetric,
+    pub feature: VoronoiFeature,
+    /// Feature point displacement (0 = regular grid, 1 = fully random)
+    pub jitter: f32,
+}
+
+// VoronoiPayload serialization used by alice-create binary.
+#[allow(dead_code)]
+impl VoronoiPayload {
+    pub const SIZE: usize = 20;
+
+    pub fn parse(data: &[u8]) -> Result<Self, AliceError> {
+        check_size(data, Self::SIZE)?;
+        Ok(Self {
+            seed: u64::from_le_bytes(le_bytes(data, 0)?),
+            cell_density: f32::from_le_bytes(le_bytes(data, 8)?),
+            metric: VoronoiMetric::try_from(data[12])?,
+            feature: VoronoiFeature::try_from(data[13])?,
+            jitter: f32::from_le_bytes(le_bytes(data, 16)?),
+        })
+    }
+
+    pub fn equation_string(&self) -> String {
+        format!(
+            "Voronoi(seed={}, density={:.2}, metric={}, feature={}, jitter={:.2})",
+            self.seed,
+            self.cell_density,
+            self.metric.name(),
+            self.feature.name(),
+            self.jitter
+        )
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(Self::SIZE);
+        buf.extend_from_slice(&self.seed.to_le_bytes());
+        buf.extend_from_slice(&self.cell_density.to_le_bytes());
+        buf.push(self.metric as u8);
+        buf.push(self.feature as u8);
+        buf.extend_from_slice(&[0, 0]);
+        buf.extend_from_slice(&self.jitter.to_le_bytes());
+        buf
+    }
+}
+
+/// Model fitted to one segment of a [`SegmentedPayload`]
+#[derive(Debug, Clone)]
+pub enum SegmentModel {
+    Linear(LinearPayload),
+    Polynomial(PolynomialPayload),
+}
+
+impl SegmentModel {
+    fn content_type(&self) -> AliceContentType {
+        match self {
+            Self::Linear(_) => AliceContentType::Linear,
+            Self::Polynomial(_) => AliceContentType::Polynomial,
+        }
+    }
+
+    /// Evaluate at `x` samples past the segment start
+    pub fn evaluate(&self, x: f64) -> f64 {
+        match self {
+            Self::Linear(p) => p.slope_f32() as f64 * x + p.intercept_f32() as f64,
+            Self::Polynomial(p) => p.evaluate(x),
+        }
+    }
+
+    /// Coefficients in ascending powers (linear → [intercept, slope])
+    pub fn coefficients_f64(&self) -> Vec<f64> {
+        match self {
+            Self::Linear(p) => vec![p.intercept_f32() as f64, p.slope_f32() as f64],
+            Self::Polynomial(p) => p.coefficients_f64(),
+        }
+    }
+
+    pub fn equation_string(&self) -> String {
+        match self {
+            Self::Linear(p) => p.equation_string(),
+            Self::Polynomial(p) => p.equation_string(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), AliceError> {
+        match self {
+            Self::Linear(_) => Ok(()),
+            Self::Polynomial(p) => p.validate(),
+        }
+    }
+
+    /// Renderer term slots: a header plus the coefficients packed 4 per slot
+    pub fn term_slots(&self) -> usize {
+        let coefficients = match self {
+            Self::Linear(_) => 2,
+            Self::Polynomial(p) => p.len(),
+        };
+        1 + coefficients.div_ceil(4)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Linear(p) => p.to_bytes(),
+            Self::Polynomial(p) => p.to_bytes(),
+        }
+    }
+}
+
+/// One piece of a segmented model, valid from `start_index` up to the next segment
+#[derive(Debug, Clone)]
+pub struct Segment {
+    /// First sample index covered by this segment
+    pub start_index: u32,
+    /// Model evaluated with x relative to `start_index`
+    pub model: SegmentModel,
+}
+
+/// Piecewise time-series payload
+///
+/// Layout: segment count (u32), sample count (u32), then per segment:
+/// start index (u32), model content type (u8, Linear or Polynomial),
+/// reserved (3 bytes), model length (u32) and the model's own payload bytes.
+/// Segments are stored in strictly increasing `start_index` order.
+#[derive(Debug, Clone)]
+pub struct SegmentedPayload {
+    /// Total samples covered by all segments
+    pub sample_count: u32,
+    pub segments: Vec<Segment>,
+}
+
+// SegmentedPayload serialization used by alice-create binary.
+#[allow(dead_code)]
+impl SegmentedPayload {
+    pub const HEADER_SIZE: usize = 8;
+    pub const SEGMENT_HEADER_SIZE: usize = 12;
+    /// Term slots available to all segments (matches the renderer's term
+    /// slots; a linear segment takes 2)
+    pub const MAX_TERM_SLOTS: usize = 16;
+
+    pub fn parse(data: &[u8]) -> Result<Self, AliceError> {
+        let segment_count = u32::from_le_bytes(le_bytes(data, 0)?) as usize;
+        let sample_count = u32::from_le_bytes(le_bytes(data, 4)?);
+
+        // Every segment needs at least its own header: reject absurd counts
+        // before allocating.
+        let min_size = segment_count
+            .checked_mul(Self::SEGMENT_HEADER_SIZE)
+            .and_then(|n| n.checked_add(Self::HEADER_SIZE))
+            .unwrap_or(usize::MAX);
+        if data.len() < min_size {
+            return Err(AliceError::Truncated { need: min_size, got: data.len() });
+        }
+
+        let mut segments: Vec<Segment> = Vec::with_capacity(segment_count);
+        let mut offset = Self::HEADER_SIZE;
+        for _ in 0..segment_count {
+            let head: [u8; Self::SEGMENT_HEADER_SIZE] = le_bytes(data, offset)?;
+            let start_index = u32::from_le_bytes(le_bytes(&head, 0)?);
+            let kind = head[4];
+            let model_len = u32::from_le_bytes(le_bytes(&head, 8)?) as usize;
+            offset += Self::SEGMENT_HEADER_SIZE;
+
+            let model_end = offset.saturating_add(model_len);
+            let model_data = data
+                .get(offset..model_end)
+                .ok_or(AliceError::Truncated { need: model_end, got: data.len() })?;
+            let model = match AliceContentType::try_from(kind)? {
+                AliceContentType::Linear => SegmentModel::Linear(LinearPayload::parse(model_data)?),
+                AliceContentType::Polynomial => SegmentModel::Polynomial(PolynomialPayload::parse(model_data)?),
+                _ => return Err(AliceError::InvalidField { field: "segment model", value: kind as u64 }),
+            };
+            offset = model_end;
+
+            if segments.last().is_some_and(|prev| prev.start_index >= start_index) {
+                return Err(AliceError::InvalidField {
+                    field: "segment start index",
+                    value: start_index as u64,
+                });
+            }
+            segments.push(Segment { start_index, model });
+        }
+
+        if offset != data.len() {
+            return Err(AliceError::PayloadSizeMismatch { expected: offset, got: data.len() });
+        }
+
+        Ok(Self {
+            sample_count,
+            segments,
+        })
+    }
+
+    /// Renderer term slots used by all segments
+    pub fn term_slots(&self) -> usize {
+        self.segments.iter().map(|s| s.model.term_slots()).sum()
+    }
+
+    /// Check every segment is valid and all of them fit the renderer
+    pub fn validate(&self) -> Result<(), AliceError> {
+        self.segments.iter().try_for_each(|s| s.model.validate())?;
+        let slots = self.term_slots();
+        if slots > Self::MAX_TERM_SLOTS {
+            return Err(AliceError::InvalidField { field: "segment term slots", value: slots as u64 });
+        }
+        Ok(())
+    }
+
+    /// Segment covering sample `x` (the first one for x before its start)
+    pub fn segment_at(&self, x: f64) -> Option<&Segment> {
+        let i = self.segments.partition_point(|s| s.start_index as f64 <= x);
+        self.segments.get(i.saturating_sub(1))
+    }
+
+    /// Evaluate at sample x
+    pub fn evaluate(&self, x: f64) -> f64 {
+        match self.segment_at(x) {
+            Some(s) => s.model.evaluate(x - s.start_index as f64),
+            None => 0.0,
+        }
+    }
+
+    /// Sample range [0, end) covered by the model
+    pub fn domain_end(&self) -> u32 {
+        let last_start = self.segments.last().map_or(0, |s| s.start_index);
+        self.sample_count.max(last_start.saturating_add(1))
+    }
+
+    /// Min / max of the model sampled at `steps` points over its domain
+    pub fn value_range(&self, steps: u32) -> (f64, f64) {
+        let end = self.domain_end() as f64;
+        let steps = steps.max(2);
+        let mut range = (f64::INFINITY, f64::NEG_INFINITY);
+        // Include each segment start so short segments are not skipped
+        let starts = self.segments.iter().map(|s| s.start_index as f64);
+        let grid = (0..steps).map(|i| end * i as f64 / (steps - 1) as f64);
+        for x in starts.chain(grid) {
+            let y = self.evaluate(x.min(end));
+            if y.is_finite() {
+                range = (range.0.min(y), range.1.max(y));
+            }
+        }
+        if range.0 > range.1 {
+            (0.0, 0.0)
+        } else {
+            range
+        }
+    }
+
+    /// Get human-readable equation string
+    pub fn equation_string(&self) -> String {
+        let pieces: Vec<String> = self
+            .segments
+            .iter()
+            .map(|s| format!("[x ≥ {}] {}", s.start_index, s.model.equation_string()))
+            .collect();
+        format!("Piecewise({} segments): {}", self.segments.len(), pieces.join("; "))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&(self.segments.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&self.sample_count.to_le_bytes());
+        for s in &self.segments {
+            let model = s.model.to_bytes();
+            buf.extend_from_slice(&s.start_index.to_le_bytes());
+            buf.push(s.model.content_type() as u8);
+            buf.extend_from_slice(&[0, 0, 0]);
+            buf.extend_from_slice(&(model.len() as u32).to_le_bytes());
+            buf.extend_from_slice(&model);
+        }
+        buf
+    }
+}
+
+/// How a composite layer is combined with the layers below it
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendOp {
+    Add = 0,
+    Multiply = 1,
+    Max = 2,
+    /// Displace the sample position of all following layers by this layer
+    DomainWarp = 3,
+}
+
+impl TryFrom<u8> for BlendOp {
+    type Error = AliceError;
+
+    fn try_from(value: u8) -> Result<Self, AliceError> {
+        match value {
+            0 => Ok(Self::Add),
+            1 => Ok(Self::Multiply),
+            2 => Ok(Self::Max),
+            3 => Ok(Self::DomainWarp),
+            _ => Err(AliceError::InvalidField { field: "blend op", value: value as u64 }),
+        }
+    }
+}
+
+// from_name is used by alice-create binary.
+#[allow(dead_code)]
+impl BlendOp {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Add => "Add",
+            Self::Multiply => "Multiply",
+            Self::Max => "Max",
+            Self::DomainWarp => "Domain Warp",
+        }
+    }
+
+    /// Parse from a case-insensitive name (CLI)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "add" => Some(Self::Add),
+            "multiply" | "mul" => Some(Self::Multiply),
+            "max" => Some(Self::Max),
+            "warp" | "domain-warp" => Some(Self::DomainWarp),
+            _ => None,
+        }
+    }
+}
+
+/// One layer of a [`CompositePayload`]
+#[derive(Debug, Clone)]
+pub struct Layer {
+    /// Combination with the result of the layers below (ignored for the
+    /// first contributing layer, which initializes the result)
+    pub blend: BlendOp,
+    /// Scale applied to the layer value (warp strength for DomainWarp)
+    pub weight: f32,
+    pub payload: AlicePayload,
+}
+
+/// Layered composite payload: layers evaluated bottom to top
+///
+/// Layout: layer count (u32), then per layer: content type (u8), blend op
+/// (u8), reserved (2 bytes), weight (f32), payload length (u32) and the
+/// layer's own payload bytes. Composites cannot be nested.
+#[derive(Debug, Clone)]
+pub struct CompositePayload {
+    pub layers: Vec<Layer>,
+}
+
+// CompositePayload serialization used by alice-create binary.
+#[allow(dead_code)]
+impl CompositePayload {
+    pub const HEADER_SIZE: usize = 4;
+    pub const LAYER_HEADER_SIZE: usize = 12;
+    /// Maximum number of layers (matches the renderer's layer slots)
+    pub const MAX_LAYERS: usize = 8;
+    /// Term slots shared by all layers (matches the renderer's term slots)
+    pub const MAX_TERM_SLOTS: usize = 16;
+
+    pub fn parse(data: &[u8]) -> Result<Self, AliceError> {
+        let layer_count = u32::from_le_bytes(le_bytes(data, 0)?) as usize;
+        if layer_count > Self::MAX_LAYERS {
+            return Err(AliceError::InvalidField { field: "layer count", value: layer_count as u64 });
+        }
+
+        let mut layers = Vec::with_capacity(layer_count);
+        let mut offset = Self::HEADER_SIZE;
+        for _ in 0..layer_count {
+            let head: [u8; Self::LAYER_HEADER_SIZE] = le_bytes(data, offset)?;
+            let content_type = AliceContentType::try_from(head[0])?;
+            let blend = BlendOp::try_from(head[1])?;
+            let weight = f32::from_le_bytes(le_bytes(&head, 4)?);
+            let len = u32::from_le_bytes(le_bytes(&head, 8)?) as usize;
+            offset += Self::LAYER_HEADER_SIZE;
+
+            if content_type == AliceContentType::Composite {
+                return Err(AliceError::InvalidField { field: "nested composite", value: head[0] as u64 });
+            }
+            let end = offset.saturating_add(len);
+            let body = data
+                .get(offset..end)
+                .ok_or(AliceError::Truncated { need: end, got: data.len() })?;
+            layers.push(Layer {
+                blend,
+                weight,
+                payload: AlicePayload::parse(content_type, body)?,
+            });
+            offset = end;
+        }
+
+        if offset != data.len() {
+            return Err(AliceError::PayloadSizeMismatch { expected: offset, got: data.len() });
+        }
+
+        Ok(Self { layers })
+    }
+
+    /// Whether each layer fits the shared term slots; like the renderer,
+    /// a layer that does not fit is skipped and later ones still get a chance
+    pub fn layers_rendered(&self) -> Vec<bool> {
+        let mut used = 0;
+        self.layers
+            .iter()
+            .map(|layer| {
+                let slots = layer.payload.term_slots().min(Self::MAX_TERM_SLOTS);
+                let fits = used + slots <= Self::MAX_TERM_SLOTS;
+                if fits {
+                    used += slots;
+                }
+                fits
+            })
+            .collect()
+    }
+
+    /// Check every layer is valid and all of them fit the renderer
+    pub fn validate(&self) -> Result<(), AliceError> {
+        if self.layers.len() > Self::MAX_LAYERS {
+            return Err(AliceError::InvalidField { field: "layer count", value: self.layers.len() as u64 });
+        }
+        self.layers.iter().try_for_each(|l| l.payload.validate())?;
+        if self.layers_rendered().contains(&false) {
+            let slots: usize = self.layers.iter().map(|l| l.payload.term_slots()).sum();
+            return Err(AliceError::InvalidField { field: "composite term slots", value: slots as u64 });
+        }
+        Ok(())
+    }
+
+    /// Evaluate 1D layers along x, combined as in the 2D renderer
+    /// (domain-warp layers displace x for the layers above them)
+    pub fn evaluate(&self, x: f64) -> Option<f64> {
+        let mut x = x;
+        let mut acc: Option<f64> = None;
+        for layer in &self.layers {
+            let v = layer.weight as f64 * layer.payload.evaluate(x)?;
+            if layer.blend == BlendOp::DomainWarp {
+                x += v;
+                continue;
+            }
+            acc = Some(match (acc, layer.blend) {
+                (None, _) => v,
+                (Some(a), BlendOp::Multiply) => a * v,
+                (Some(a), BlendOp::Max) => a.max(v),
+                (Some(a), _) => a + v,
+            });
+        }
+        Some(acc.unwrap_or(0.0))
+    }
+
+    /// Get human-readable equation string
+    pub fn equation_string(&self) -> String {
+        let mut out = String::new();
+        let mut started = false;
+        for layer in &self.layers {
+            let term = if (layer.weight - 1.0).abs() < 1e-6 {
+                layer.payload.equation_string()
+            } else {
+                format!("{:.3}·{}", layer.weight, layer.payload.equation_string())
+            };
+            match (started, layer.blend) {
+                (_, BlendOp::DomainWarp) => out.push_str(&format!("warp[{}] ", term)),
+                (false, _) => out.push_str(&term),
+                (true, BlendOp::Add) => out.push_str(&format!(" + {}", term)),
+                (true, BlendOp::Multiply) => out.push_str(&format!(" × {}", term)),
+                (true, BlendOp::Max) => out.push_str(&format!(" max {}", term)),
+            }
+            started |= layer.blend != BlendOp::DomainWarp;
+        }
+        out
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let count = self.layers.len().min(Self::MAX_LAYERS);
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&(count as u32).to_le_bytes());
+        for layer in self.layers.iter().take(count) {
+            let body = layer.payload.to_bytes();
+            buf.push(layer.payload.content_type() as u8);
+            buf.push(layer.blend as u8);
+            buf.extend_from_slice(&[0, 0]);
+            buf.extend_from_slice(&layer.weight.to_le_bytes());
+            buf.extend_from_slice(&(body.len() as u32).to_le_bytes());
+            buf.extend_from_slice(&body);
+        }
+        buf
+    }
+}
+
+/// Parsed content from .alice file
+#[derive(Debug, Clone)]
+pub enum AlicePayload {
+    Linear(LinearPayload),
+    Polynomial(PolynomialPayload),
+    Perlin(PerlinPayload),
+    Fractal(FractalPayload),
+    Fourier(FourierPayload),
+    Voronoi(VoronoiPayload),
+    SineWave(SineWavePayload),
+    Segmented(SegmentedPayload),
+    Composite(CompositePayload),
+}
+
+impl AlicePayload {
+    /// Parse a payload body of the given content type
+    pub fn parse(content_type: AliceContentType, data: &[u8]) -> Result<Self, AliceError> {
+        Ok(match content_type {
+            AliceContentType::Linear => Self::Linear(LinearPayload::parse(data)?),
+            AliceContentType::Polynomial => Self::Polynomial(PolynomialPayload::parse(data)?),
+            AliceContentType::Perlin => Self::Perlin(PerlinPayload::parse(data)?),
+            AliceContentType::Fractal => Self::Fractal(FractalPayload::parse(data)?),
+            AliceContentType::Fourier => Self::Fourier(FourierPayload::parse(data)?),
+            AliceContentType::Voronoi => Self::Voronoi(VoronoiPayload::parse(data)?),
+            AliceContentType::SineWave => Self::SineWave(SineWavePayload::parse(data)?),
+            AliceContentType::Segmented => Self::Segmented(SegmentedPayload::parse(data)?),
+            AliceContentType::Composite => Self::Composite(CompositePayload::parse(data)?),
+        })
+    }
+
+    /// Content type this payload is stored as
+    pub fn content_type(&self) -> AliceContentType {
+        match self {
+            Self::Linear(_) => AliceContentType::Linear,
+            Self::Polynomial(_) => AliceContentType::Polynomial,
+            Self::Perlin(_) => AliceContentType::Perlin,
+            Self::Fractal(_) => AliceContentType::Fractal,
+            Self::Fourier(_) => AliceContentType::Fourier,
+            Self::Voronoi(_) => AliceContentType::Voronoi,
+            Self::SineWave(_) => AliceContentType::SineWave,
+            Self::Segmented(_) => AliceContentType::Segmented,
+            Self::Composite(_) => AliceContentType::Composite,
+        }
+    }
+
+    /// Get human-readable equation string
+    pub fn equation_string(&self) -> String {
+        match self {
+            Self::Linear(p) => p.equation_string(),
+            Self::Polynomial(p) => p.equation_string(),
+            Self::Perlin(p) => p.equation_string(),
+            Self::Fractal(p) => p.equation_string(),
+            Self::Fourier(p) => p.equation_string(),
+            Self::Voronoi(p) => p.equation_string(),
+            Self::SineWave(p) => p.equation_string(),
+            Self::Segmented(p) => p.equation_string(),
+            Self::Composite(p) => p.equation_string(),
+        }
+    }
+
+    /// Evaluate a one-dimensional model at sample position x
+    ///
+    /// Returns `None` for 2D fields (noise, fractals, cells) and for
+    /// composites containing one.
+    // Used by the fit/expand modules and alice-create binary.
+    #[allow(dead_code)]
+    pub fn evaluate(&self, x: f64) -> Option<f64> {
+        match self {
+            Self::Linear(p) => Some((p.slope_q16 as f64 * x + p.intercept_q16 as f64) / 65536.0),
+            Self::Polynomial(p) => Some(p.evaluate(x)),
+            Self::Fourier(p) => Some(p.evaluate(x)),
+            Self::SineWave(p) => Some(p.evaluate(x)),
+            Self::Segmented(p) => Some(p.evaluate(x)),
+            Self::Composite(p) => p.evaluate(x),
+            Self::Perlin(_) | Self::Fractal(_) | Self::Voronoi(_) => None,
+        }
+    }
+
+    /// Renderer term slots used by this payload (0 for 2D fields)
+    pub fn term_slots(&self) -> usize {
+        match self {
+            Self::Linear(_) => 1,
+            Self::Polynomial(p) => p.len().div_ceil(4),
+            Self::Fourier(p) => p.terms.len(),
+            Self::SineWave(p) => p.components.len(),
+            Self::Segmented(p) => p.term_slots(),
+            Self::Composite(p) => p.layers.iter().map(|l| l.payload.term_slots()).sum(),
+            Self::Perlin(_) | Self::Fractal(_) | Self::Voronoi(_) => 0,
+        }
+    }
+
+    /// Check the payload serializes without dropping data
+    pub fn validate(&self) -> Result<(), AliceError> {
+        match self {
+            Self::Polynomial(p) => p.validate(),
+            Self::Fourier(p) => p.validate(),
+            Self::SineWave(p) => p.validate(),
+            Self::Segmented(p) => p.validate(),
+            Self::Composite(p) => p.validate(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Serialize payload body
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Linear(p) => p.to_bytes(),
+            Self::Polynomial(p) => p.to_bytes(),
+            Self::Perlin(p) => p.to_bytes(),
+            Self::Fractal(p) => p.to_bytes(),
+            Self::Fourier(p) => p.to_bytes(),
+            Self::Voronoi(p) => p.to_bytes(),
+            Self::SineWave(p) => p.to_bytes(),
+            Self::Segmented(p) => p.to_bytes(),
+            Self::Composite(p) => p.to_bytes(),
+        }
+    }
+}
+
+/// Error produced when the metadata block cannot be decoded
+#[derive(Debug, thiserror::Error)]
+pub enum MetadataError {
+    #[error("metadata is not valid UTF-8: {0}")]
+    InvalidUtf8(#[from] std::str::Utf8Error),
+    #[error("malformed metadata JSON: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+    #[error("cannot decompress metadata: {0}")]
+    Decompress(#[from] std::io::Error),
+}
+
+/// Metadata stored in .alice file (JSON)
+///
+/// Well-known keys map to typed fields; any other keys are kept verbatim in
+/// `custom` (including numbers, arrays and nested objects) and written back
+/// unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AliceMetadata {
+    /// Sensor ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sensor_id: Option<String>,
+    /// Timestamp (ISO 8601)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    /// Location
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// Unit of measurement
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    /// Description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Seconds between consecutive samples (timestamp is the first sample)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_interval: Option<f64>,
+    /// Custom fields (any key not listed above)
+    #[serde(flatten)]
+    pub custom: serde_json::Map<String, serde_json::Value>,
+}
+
+// AliceMetadata serialization used by alice-create binary.
+#[allow(dead_code)]
+impl AliceMetadata {
+    /// Parse from JSON bytes
+    pub fn parse(data: &[u8]) -> std::result::Result<Self, MetadataError> {
+        let json_str = std::str::from_utf8(data)?;
+        if json_str.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(json_str)?)
+    }
+
+    /// Parse from deflate-compressed JSON bytes
+    pub fn parse_compressed(data: &[u8]) -> std::result::Result<Self, MetadataError> {
+        use std::io::Read;
+
+        let mut json = Vec::new();
+        flate2::read::DeflateDecoder::new(data)
+            .take(MAX_METADATA_SIZE + 1)
+            .read_to_end(&mut json)?;
+        if json.len() as u64 > MAX_METADATA_SIZE {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "decompressed metadata exceeds size limit",
+            )
+            .into());
+        }
+        Self::parse(&json)
+    }
+
+    /// Serialize to JSON bytes
+    pub fn to_json(&self) -> Vec<u8> {
+        // Serializing string fields and a JSON map cannot fail.
+        serde_json::to_vec(self).unwrap_or_else(|_| b"{}".to_vec())
+    }
+
+    /// Serialize for storage, compressing large metadata.
+    /// Returns the bytes and whether they are compressed.
+    pub fn encode(&self) -> (Vec<u8>, bool) {
+        use std::io::Write;
+
+        let json = self.to_json();
+        if json.len() < METADATA_COMPRESS_THRESHOLD {
+            return (json, false);
+        }
+        let mut encoder = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        match encoder.write_all(&json).and_then(|_| encoder.finish()) {
+            Ok(compressed) if compressed.len() < json.len() => (compressed, true),
+            _ => (json, false),
+        }
+    }
+}
+
+/// Metadata key holding the fitted model (`linear`, `polynomial:N`, `fourier:K`)
+pub const META_MODEL: &str = "fit_model";
+/// Metadata key holding the residual root-mean-square error
+pub const META_RMSE: &str = "fit_rmse";
+/// Metadata key holding the largest absolute residual
+pub const META_MAX_ABS_ERROR: &str = "fit_max_abs_error";
+
+/// Residual statistics of a model fitted to sample data
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FitStats {
+    pub rmse: f64,
+    pub max_abs_error: f64,
+}
+
+impl FitStats {
+    /// Read statistics recorded by `alice-create fit`, if present
+    pub fn from_metadata(metadata: &AliceMetadata) -> Option<Self> {
+        Some(Self {
+            rmse: metadata.custom.get(META_RMSE)?.as_f64()?,
+            max_abs_error: metadata.custom.get(META_MAX_ABS_ERROR)?.as_f64()?,
+        })
+    }
+}
+
+/// Complete parsed .alice file
+#[derive(Debug, Clone)]
+pub struct AliceFile {
+    pub header: AliceHeader,
+    pub payload: AlicePayload,
+    pub metadata: AliceMetadata,
+}
+
+// AliceFile::to_bytes is used by alice-create binary.
+#[allow(dead_code)]
+impl AliceFile {
+    /// Parse .alice file from bytes
+    pub fn parse(data: &[u8]) -> Result<Self, AliceError> {
+        let header = AliceHeader::parse(data)?;
+
+        let payload_range = header.payload_range(data.len())?;
+        let payload_end = payload_range.end;
+        let payload_data = &data[payload_range];
+        let payload = AlicePayload::parse(header.content_type, payload_data)?;
+
+        let metadata = if header.has_metadata() {
+            let meta_data = &data[payload_end..];
+            if header.has_compressed_metadata() {
+                AliceMetadata::parse_compressed(meta_data)?
+            } else {
+                AliceMetadata::parse(meta_data)?
+            }
+        } else {
+            AliceMetadata::default()
+        };
+
+        Ok(Self {
+            header,
+            payload,
+            metadata,
+        })
+    }
+
+    /// Serialize to bytes (always written as the current version)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let payload_bytes = self.payload.to_bytes();
+        let (meta_bytes, meta_compressed) = self.metadata.encode();
+        let header = Self::encoded_header(&self.header, &payload_bytes, &meta_bytes, meta_compressed);
+
+        let mut out = Vec::with_capacity(header.compressed_size as usize);
+        out.extend_from_slice(&header.to_bytes());
+        if let Some(len) = header.payload_length {
+            out.extend_from_slice(&len.to_le_bytes());
+        }
+        out.extend_from_slice(&payload_bytes);
+        out.extend_from_slice(&meta_bytes);
+        out
+    }
+
+    /// Header describing the given encoded payload and metadata
+    fn encoded_header(base: &AliceHeader, payload: &[u8], metadata: &[u8], meta_compressed: bool) -> AliceHeader {
+        let big = payload.len() as u64 > u32::MAX as u64;
+        let mut flags = FLAG_CHECKSUM;
+        if meta_compressed {
+            flags |= FLAG_COMPRESSED_METADATA;
+        }
+        if big {
+            flags |= FLAG_BIG_PAYLOAD;
+        }
+        let checksum = crc32fast::hash(payload);
+
+        AliceHeader {
+            magic: *ALICE_MAGIC,
+            version: ALICE_VERSION,
+            flags,
+            compressed_size: (AliceHeader::SIZE + if big { 8 } else { 0 } + payload.len() + metadata.len()) as u64,
+            metadata_length: metadata.len() as u32,
+            checksum,
+            payload_length: big.then_some(payload.len() as u64),
+            checksum_status: ChecksumStatus::Valid(checksum),
+            ..base.clone()
+        }
+    }
+
+    /// Get equation string
+    pub fn equation_string(&self) -> String {
+        self.payload.equation_string()
+    }
+
+    /// Get content type name
+    pub fn content_type_name(&self) -> &'static str {
+        self.header.content_type.name()
+    }
+
+    /// Get compression ratio
+    pub fn compression_ratio(&self) -> f64 {
+        self.header.compression_ratio()
+    }
+}
+
+/// Builder for creating .alice files
+// Used exclusively by alice-create binary.
+#[allow(dead_code)]
+pub struct AliceFileBuilder {
+    content_type: AliceContentType,
+    original_size: u64,
+    payload: Option<AlicePayload>,
+    metadata: AliceMetadata,
+}
+
+#[allow(dead_code)]
+impl AliceFileBuilder {
+    pub fn new(content_type: AliceContentType) -> Self {
+        Self {
+            content_type,
+            original_size: 0,
+            payload: None,
+            metadata: AliceMetadata::default(),
+        }
+    }
+
+    /// Create from ALICE-Edge linear model output
+    pub fn from_linear(slope_q16: i32, intercept_q16: i32, sample_count: u32) -> Self {
+        let mut builder = Self::new(AliceContentType::Linear);
+        builder.original_size = sample_count as u64 * 4; // 4 bytes per sample
+        builder.payload = Some(AlicePayload::Linear(LinearPayload {
+            slope_q16,
+            intercept_q16,
+            sample_count,
+        }));
+        builder
+    }
+
+    /// Create polynomial model (f64 coefficients, ascending powers)
+    pub fn polynomial(coefficients: Vec<f64>, sample_count: u32) -> Self {
+        let mut builder = Self::new(AliceContentType::Polynomial);
+        builder.original_size = sample_count as u64 * 4;
+        builder.payload = Some(AlicePayload::Polynomial(PolynomialPayload {
+            coefficients: PolynomialCoefficients::F64(coefficients),
+            sample_count,
+        }));
+        builder
+    }
+
+    /// Create piecewise model from segments ordered by start index
+    pub fn segmented(segments: Vec<Segment>, sample_count: u32) -> Self {
+        let mut builder = Self::new(AliceContentType::Segmented);
+        builder.original_size = sample_count as u64 * 4;
+        builder.payload = Some(AlicePayload::Segmented(SegmentedPayload {
+            sample_count,
+            segments,
+        }));
+        builder
+    }
+
+    /// Create polynomial model (Q16.16 coefficients, ascending powers)
+    pub fn polynomial_q16(coefficients: Vec<i32>, sample_count: u32) -> Self {
+        let mut builder = Self::new(AliceContentType::Polynomial);
+        builder.original_size = sample_count as u64 * 4;
+        builder.payload = Some(AlicePayload::Polynomial(PolynomialPayload {
+            coefficients: PolynomialCoefficients::Q16(coefficients),
+            sample_count,
+        }));
+        builder
+    }
+
+    /// Create Fourier series model
+    pub fn fourier(period: f64, terms: Vec<FourierTerm>, sample_count: u32) -> Self {
+        let mut builder = Self::new(AliceContentType::Fourier);
+        builder.original_size = sample_count as u64 * 4;
+        builder.payload = Some(AlicePayload::Fourier(FourierPayload {
+            period,
+            sample_count,
+            terms,
+        }));
+        builder
+    }
+
+    /// Create Mandelbrot fractal
+    pub fn mandelbrot(max_iterations: u32, center_x: f64, center_y: f64) -> Self {
+        let mut builder = Self::new(AliceContentType::Fractal);
+        builder.payload = Some(AlicePayload::Fractal(FractalPayload {
             fractal_type: 0,
             max_iterations,
             escape_radius: 2.0,
@@ -633,6 +1974,57 @@ impl AliceFileBuilder {
         builder
     }
 
+    /// Create sine wave composition
+    pub fn sine(offset: f32, components: Vec<SineComponent>, sample_count: u32) -> Self {
+        let mut builder = Self::new(AliceContentType::SineWave);
+        builder.original_size = sample_count as u64 * 4;
+        builder.payload = Some(AlicePayload::SineWave(SineWavePayload {
+            offset,
+            sample_count,
+            components,
+        }));
+        builder
+    }
+
+    /// Create layered composite (layers evaluated bottom to top)
+    pub fn composite(layers: Vec<Layer>) -> Self {
+        let mut builder = Self::new(AliceContentType::Composite);
+        builder.payload = Some(AlicePayload::Composite(CompositePayload { layers }));
+        builder
+    }
+
+    /// Create Voronoi pattern
+    pub fn voronoi(
+        seed: u64,
+        cell_density: f32,
+        metric: VoronoiMetric,
+        feature: VoronoiFeature,
+        jitter: f32,
+    ) -> Self {
+        let mut builder = Self::new(AliceContentType::Voronoi);
+        builder.payload = Some(AlicePayload::Voronoi(VoronoiPayload {
+            seed,
+            cell_density,
+            metric,
+            feature,
+            jitter,
+        }));
+        builder
+    }
+
+    /// Create from an already-decoded payload
+    pub fn from_payload(payload: AlicePayload) -> Self {
+        let mut builder = Self::new(payload.content_type());
+        builder.payload = Some(payload);
+        builder
+    }
+
+    /// Set the size of the data the payload describes
+    pub fn original_size(mut self, size: u64) -> Self {
+        self.original_size = size;
+        self
+    }
+
     /// Set metadata
     pub fn with_metadata(mut self, metadata: AliceMetadata) -> Self {
         self.metadata = metadata;
@@ -657,33 +2049,43 @@ impl AliceFileBuilder {
         self
     }
 
+    /// Set seconds between samples
+    pub fn sample_interval(mut self, seconds: f64) -> Self {
+        self.metadata.sample_interval = Some(seconds);
+        self
+    }
+
+    /// Set a custom metadata key
+    pub fn custom(mut self, key: &str, value: serde_json::Value) -> Self {
+        self.metadata.custom.insert(key.to_string(), value);
+        self
+    }
+
     /// Build the .alice file
     pub fn build(self) -> Result<AliceFile> {
         let payload = self.payload.context("Payload not set")?;
+        payload.validate()?;
 
-        let payload_bytes = match &payload {
-            AlicePayload::Linear(p) => p.to_bytes(),
-            AlicePayload::Perlin(p) => p.to_bytes(),
-            AlicePayload::Fractal(p) => p.to_bytes(),
-        };
-
-        let meta_bytes = self.metadata.to_json();
+        let payload_bytes = payload.to_bytes();
 
-        let compressed_size = AliceHeader::SIZE as u64 + payload_bytes.len() as u64 + meta_bytes.len() as u64;
+        let (meta_bytes, meta_compressed) = self.metadata.encode();
 
-        let header = AliceHeader {
+        let base = AliceHeader {
             magic: *ALICE_MAGIC,
             version: ALICE_VERSION,
             content_type: self.content_type,
             flags: 0,
-            original_size: if self.original_size > 0 {
-                self.original_size
-            } else {
-                compressed_size * 100 // Estimate for non-data content
-            },
-            compressed_size,
-            metadata_length: meta_bytes.len() as u32,
+            original_size: self.original_size,
+            compressed_size: 0,
+            metadata_length: 0,
+            checksum: 0,
+            payload_length: None,
+            checksum_status: ChecksumStatus::Absent,
         };
+        let mut header = AliceFile::encoded_header(&base, &payload_bytes, &meta_bytes, meta_compressed);
+        if header.original_size == 0 {
+            header.original_size = header.compressed_size * 100; // Estimate for non-data content
+        }
 
         Ok(AliceFile {
             header,
@@ -717,6 +2119,628 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_metadata_escaping_and_custom_keys() {
+        let json = br#"{ "sensor_id" : "A \"quoted\" \\ id", "unit": "\u00b0C",
+            "gain": 2.5, "tags": ["a", "b"], "calibration": {"offset": -1, "valid": true} }"#;
+        let meta = AliceMetadata::parse(json).unwrap();
+
+        assert_eq!(meta.sensor_id.as_deref(), Some("A \"quoted\" \\ id"));
+        assert_eq!(meta.unit.as_deref(), Some("°C"));
+        assert_eq!(meta.custom["gain"], serde_json::json!(2.5));
+        assert_eq!(meta.custom["calibration"]["valid"], serde_json::json!(true));
+        assert!(!meta.custom.contains_key("sensor_id"));
+
+        let file = AliceFileBuilder::from_linear(1 << 16, 0, 10)
+            .with_metadata(meta.clone())
+            .build()
+            .unwrap();
+        let parsed = AliceFile::parse(&file.to_bytes()).unwrap();
+        assert_eq!(parsed.metadata, meta);
+    }
+
+    #[test]
+    fn test_metadata_malformed() {
+        assert!(matches!(
+            AliceMetadata::parse(br#"{"sensor_id": "unterminated}"#),
+            Err(MetadataError::InvalidJson(_))
+        ));
+        assert!(matches!(
+            AliceMetadata::parse(br#"{"unit": 5}"#),
+            Err(MetadataError::InvalidJson(_))
+        ));
+        assert!(matches!(
+            AliceMetadata::parse(&[b'{', 0xff, b'}']),
+            Err(MetadataError::InvalidUtf8(_))
+        ));
+        assert_eq!(AliceMetadata::parse(b"").unwrap(), AliceMetadata::default());
+    }
+
+    #[test]
+    fn test_julia_roundtrip() {
+        let file = AliceFileBuilder::julia(300, -0.7, 0.27).build().unwrap();
+        let bytes = file.to_bytes();
+        let parsed = AliceFile::parse(&bytes).unwrap();
+
+        if let AlicePayload::Fractal(p) = &parsed.payload {
+            assert_eq!(p.fractal_type, 1);
+            assert_eq!(p.max_iterations, 300);
+            assert_eq!(p.julia_cx, -0.7);
+            assert_eq!(p.julia_cy, 0.27);
+        } else {
+            panic!("Wrong payload type");
+        }
+    }
+
+    #[test]
+    fn test_polynomial_roundtrip() {
+        let file = AliceFileBuilder::polynomial(vec![1.0, -2.0, 0.5], 500)
+            .unit("m")
+            .build()
+            .unwrap();
+        let parsed = AliceFile::parse(&file.to_bytes()).unwrap();
+
+        assert_eq!(parsed.header.content_type, AliceContentType::Polynomial);
+        if let AlicePayload::Polynomial(p) = &parsed.payload {
+            assert_eq!(p.degree(), 2);
+            assert_eq!(p.sample_count, 500);
+            assert_eq!(p.coefficients, PolynomialCoefficients::F64(vec![1.0, -2.0, 0.5]));
+            // 0.5·4² - 2·4 + 1 = 1
+            assert_eq!(p.evaluate(4.0), 1.0);
+            assert_eq!(p.equation_string(), "y = 0.500000x² - 2.000000x + 1.0000");
+        } else {
+            panic!("Wrong payload type");
+        }
+    }
+
+    #[test]
+    fn test_polynomial_q16() {
+        let payload = PolynomialPayload {
+            coefficients: PolynomialCoefficients::Q16(vec![65536, 32768]),
+            sample_count: 0,
+        };
+        let parsed = PolynomialPayload::parse(&payload.to_bytes()).unwrap();
+        assert_eq!(parsed.encoding_name(), "Q16.16");
+        assert_eq!(parsed.evaluate(2.0), 2.0);
+
+        // Degree claims more coefficients than present
+        let mut bytes = payload.to_bytes();
+        bytes[1] = 5;
+        assert!(PolynomialPayload::parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_polynomial_coefficient_limits() {
+        assert!(AliceFileBuilder::polynomial(vec![], 10).build().is_err());
+        assert!(AliceFileBuilder::polynomial_q16(vec![1; 65], 10).build().is_err());
+        assert!(AliceFileBuilder::polynomial(vec![0.5; 256], 10).build().is_err());
+
+        let file = AliceFileBuilder::polynomial(vec![0.5; 64], 10).build().unwrap();
+        let parsed = AliceFile::parse(&file.to_bytes()).unwrap();
+        let AlicePayload::Polynomial(p) = &parsed.payload else { panic!("Wrong payload type") };
+        assert_eq!(p.degree(), 63);
+
+        // Files with more still parse, but do not validate
+        let mut long = PolynomialPayload { coefficients: PolynomialCoefficients::F64(vec![0.5; 256]), sample_count: 10 };
+        let parsed = PolynomialPayload::parse(&long.to_bytes()).unwrap();
+        assert_eq!(parsed.degree(), 255);
+        assert!(parsed.validate().is_err());
+        long.coefficients = PolynomialCoefficients::F64(vec![0.5; 64]);
+        assert!(long.validate().is_ok());
+    }
+
+    #[test]
+    fn test_fourier_roundtrip() {
+        let terms = vec![
+            FourierTerm { frequency: 0, amplitude: 20.0, phase: 0.0 },
+            FourierTerm { frequency: 1, amplitude: 5.0, phase: 0.0 },
+            FourierTerm { frequency: 3, amplitude: 1.5, phase: -0.25 },
+        ];
+        let file = AliceFileBuilder::fourier(1440.0, terms.clone(), 2880).build().unwrap();
+        let parsed = AliceFile::parse(&file.to_bytes()).unwrap();
+
+        assert_eq!(parsed.header.content_type, AliceContentType::Fourier);
+        if let AlicePayload::Fourier(p) = &parsed.payload {
+            assert_eq!(p.period, 1440.0);
+            assert_eq!(p.sample_count, 2880);
+            assert_eq!(p.terms, terms);
+            // t = 0: 20 + 5 + 1.5·cos(-0.25)
+            let expected = 25.0 + 1.5 * (-0.25f64).cos();
+            assert!((p.evaluate(0.0) - expected).abs() < 1e-6);
+            // Half period: fundamental flips sign
+            assert!((p.evaluate(720.0) - (15.0 + 1.5 * (3.0 * std::f64::consts::PI - 0.25).cos())).abs() < 1e-4);
+        } else {
+            panic!("Wrong payload type");
+        }
+    }
+
+    #[test]
+    fn test_fourier_truncated() {
+        let payload = FourierPayload {
+            period: 10.0,
+            sample_count: 0,
+            terms: vec![FourierTerm { frequency: 1, amplitude: 1.0, phase: 0.0 }; 2],
+        };
+        let bytes = payload.to_bytes();
+        assert!(matches!(
+            FourierPayload::parse(&bytes[..bytes.len() - 1]),
+            Err(AliceError::Truncated { need: 40, got: 39 })
+        ));
+    }
+
+    #[test]
+    fn test_fourier_term_limit() {
+        let term = FourierTerm { frequency: 1, amplitude: 1.0, phase: 0.0 };
+        assert!(AliceFileBuilder::fourier(10.0, vec![term; FourierPayload::MAX_TERMS], 0).build().is_ok());
+        assert!(AliceFileBuilder::fourier(10.0, vec![term; FourierPayload::MAX_TERMS + 1], 0).build().is_err());
+
+        let payload = FourierPayload { period: 10.0, sample_count: 0, terms: vec![term; FourierPayload::MAX_TERMS + 1] };
+        assert!(matches!(
+            FourierPayload::parse(&payload.to_bytes()),
+            Err(AliceError::InvalidField { field: "fourier term count", value: 17 })
+        ));
+    }
+
+    #[test]
+    fn test_segmented_roundtrip() {
+        let segments = vec![
+            Segment {
+                start_index: 0,
+                model: SegmentModel::Linear(LinearPayload { slope_q16: 1 << 16, intercept_q16: 10 << 16, sample_count: 100 }),
+            },
+            Segment {
+                start_index: 100,
+                model: SegmentModel::Polynomial(PolynomialPayload {
+                    coefficients: PolynomialCoefficients::F64(vec![110.0, 0.0, -0.5]),
+                    sample_count: 50,
+                }),
+            },
+        ];
+        let file = AliceFileBuilder::segmented(segments, 150).build().unwrap();
+        let parsed = AliceFile::parse(&file.to_bytes()).unwrap();
+
+        assert_eq!(parsed.header.content_type, AliceContentType::Segmented);
+        if let AlicePayload::Segmented(p) = &parsed.payload {
+            assert_eq!(p.segments.len(), 2);
+            assert!((p.evaluate(0.0) - 10.0).abs() < 1e-4);
+            assert!((p.evaluate(99.0) - 109.0).abs() < 1e-4);
+            // Second segment evaluates relative to its start
+            assert!((p.evaluate(100.0) - 110.0).abs() < 1e-9);
+            assert!((p.evaluate(104.0) - 102.0).abs() < 1e-9);
+            assert_eq!(p.segment_at(149.0).unwrap().start_index, 100);
+        } else {
+            panic!("Wrong payload type");
+        }
+    }
+
+    #[test]
+    fn test_segmented_rejects_unordered() {
+        let linear = SegmentModel::Linear(LinearPayload { slope_q16: 0, intercept_q16: 0, sample_count: 0 });
+        let payload = SegmentedPayload {
+            sample_count: 10,
+            segments: vec![
+                Segment { start_index: 5, model: linear.clone() },
+                Segment { start_index: 5, model: linear },
+            ],
+        };
+        assert!(matches!(
+            SegmentedPayload::parse(&payload.to_bytes()),
+            Err(AliceError::InvalidField { field: "segment start index", .. })
+        ));
+    }
+
+    #[test]
+    fn test_segmented_term_slot_limit() {
+        let segments = |count: u32| -> Vec<Segment> {
+            (0..count)
+                .map(|i| Segment {
+                    start_index: i * 100,
+                    model: SegmentModel::Linear(LinearPayload { slope_q16: 1 << 16, intercept_q16: 0, sample_count: 100 }),
+                })
+                .collect()
+        };
+        assert!(AliceFileBuilder::segmented(segments(8), 800).build().is_ok());
+
+        // Nine linear segments need 18 slots: parseable, but not buildable
+        let payload = SegmentedPayload { sample_count: 900, segments: segments(9) };
+        assert_eq!(payload.term_slots(), 18);
+        assert!(SegmentedPayload::parse(&payload.to_bytes()).is_ok());
+        assert!(matches!(
+            payload.validate(),
+            Err(AliceError::InvalidField { field: "segment term slots", value: 18 })
+        ));
+        assert!(AliceFileBuilder::segmented(segments(9), 900).build().is_err());
+    }
+
+    fn terrain_layers() -> Vec<Layer> {
+        vec![
+            Layer {
+                blend: BlendOp::DomainWarp,
+                weight: 0.3,
+                payload: AlicePayload::Perlin(PerlinPayload {
+                    seed: 9,
+                    scale: 2.0,
+                    octaves: 2,
+                    persistence: 0.5,
+                    lacunarity: 2.0,
+                }),
+            },
+            Layer {
+                blend: BlendOp::Add,
+                weight: 1.0,
+                payload: AlicePayload::Perlin(PerlinPayload {
+                    seed: 1,
+                    scale: 4.0,
+                    octaves: 6,
+                    persistence: 0.5,
+                    lacunarity: 2.0,
+                }),
+            },
+            Layer {
+                blend: BlendOp::Max,
+                weight: 0.5,
+                payload: AlicePayload::Voronoi(VoronoiPayload {
+                    seed: 2,
+                    cell_density: 6.0,
+                    metric: VoronoiMetric::Euclidean,
+                    feature: VoronoiFeature::F2MinusF1,
+                    jitter: 1.0,
+                }),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_composite_roundtrip() {
+        let file = AliceFileBuilder::composite(terrain_layers()).build().unwrap();
+        let parsed = AliceFile::parse(&file.to_bytes()).unwrap();
+
+        assert_eq!(parsed.header.content_type, AliceContentType::Composite);
+        if let AlicePayload::Composite(p) = &parsed.payload {
+            assert_eq!(p.layers.len(), 3);
+            assert_eq!(p.layers[0].blend, BlendOp::DomainWarp);
+            assert_eq!(p.layers[2].blend, BlendOp::Max);
+            assert_eq!(p.layers[2].weight, 0.5);
+            assert!(matches!(&p.layers[2].payload, AlicePayload::Voronoi(v) if v.cell_density == 6.0));
+            assert!(p.equation_string().starts_with("warp[0.300·FBM(seed=9"));
+        } else {
+            panic!("Wrong payload type");
+        }
+    }
+
+    #[test]
+    fn test_composite_rejects_nesting() {
+        let inner = CompositePayload { layers: terrain_layers() };
+        let outer = CompositePayload {
+            layers: vec![Layer { blend: BlendOp::Add, weight: 1.0, payload: AlicePayload::Composite(inner) }],
+        };
+        assert!(matches!(
+            CompositePayload::parse(&outer.to_bytes()),
+            Err(AliceError::InvalidField { field: "nested composite", .. })
+        ));
+    }
+
+    #[test]
+    fn test_composite_term_slot_budget() {
+        let fourier = |frequency: u32| Layer {
+            blend: BlendOp::Add,
+            weight: 1.0,
+            payload: AlicePayload::Fourier(FourierPayload {
+                period: 10.0,
+                sample_count: 0,
+                terms: vec![FourierTerm { frequency, amplitude: 1.0, phase: 0.0 }; FourierPayload::MAX_TERMS],
+            }),
+        };
+        let mut layers = terrain_layers();
+        layers.push(fourier(1));
+        layers.push(fourier(2));
+        let payload = CompositePayload { layers };
+
+        // Both 16-term layers are individually valid but only the first fits
+        assert!(CompositePayload::parse(&payload.to_bytes()).is_ok());
+        assert_eq!(payload.layers_rendered(), vec![true, true, true, true, false]);
+        assert!(matches!(
+            payload.validate(),
+            Err(AliceError::InvalidField { field: "composite term slots", value: 32 })
+        ));
+        assert!(AliceFileBuilder::composite(payload.layers).build().is_err());
+        assert!(AliceFileBuilder::composite(terrain_layers()).build().is_ok());
+    }
+
+    #[test]
+    fn test_composite_layer_limit() {
+        // 2D layers take no term slots, so only the layer count limits them
+        let perlin = terrain_layers().swap_remove(1);
+        let payload = CompositePayload { layers: vec![perlin.clone(); CompositePayload::MAX_LAYERS] };
+        assert!(payload.validate().is_ok());
+
+        let payload = CompositePayload { layers: vec![perlin; CompositePayload::MAX_LAYERS + 1] };
+        assert!(matches!(payload.validate(), Err(AliceError::InvalidField { field: "layer count", value: 9 })));
+        assert!(AliceFileBuilder::composite(payload.layers).build().is_err());
+    }
+
+    #[test]
+    fn test_typed_errors() {
+        let valid = AliceFileBuilder::perlin(7, 10.0, 6).sensor_id("S1").build().unwrap().to_bytes();
+
+        let mut bad = valid.clone();
+        bad[0] = b'X';
+        assert!(matches!(AliceFile::parse(&bad), Err(AliceError::BadMagic(_))));
+
+        let mut bad = valid.clone();
+        bad[5] = 99;
+        assert!(matches!(AliceFile::parse(&bad), Err(AliceError::UnsupportedVersion(99))));
+
+        let mut bad = valid.clone();
+        bad[6] = 200;
+        assert!(matches!(AliceFile::parse(&bad), Err(AliceError::UnknownContentType(200))));
+
+        // Metadata length larger than the file must not underflow
+        let mut bad = valid.clone();
+        bad[24..28].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(AliceFile::parse(&bad), Err(AliceError::Truncated { .. })));
+
+        assert!(matches!(
+            AliceFile::parse(&valid[..10]),
+            Err(AliceError::Truncated { need: 32, got: 10 })
+        ));
+
+        // Extra byte between payload and metadata
+        let meta_len = u32::from_le_bytes(valid[24..28].try_into().unwrap()) as usize;
+        let mut bad = valid[..valid.len() - meta_len].to_vec();
+        bad.push(0);
+        bad.extend_from_slice(&valid[valid.len() - meta_len..]);
+        assert!(matches!(
+            AliceFile::parse(&bad),
+            Err(AliceError::PayloadSizeMismatch { expected: 24, got: 25 })
+        ));
+
+        let mut bad = valid.clone();
+        let last = bad.len() - 1;
+        bad[last] = b'x';
+        assert!(matches!(AliceFile::parse(&bad), Err(AliceError::MetadataInvalid(_))));
+    }
+
+    #[test]
+    fn test_v2_checksum() {
+        let bytes = AliceFileBuilder::from_linear(1 << 16, 0, 10).build().unwrap().to_bytes();
+        assert_eq!(bytes[5], 2);
+        assert_eq!(bytes[7] & FLAG_CHECKSUM, FLAG_CHECKSUM);
+
+        let parsed = AliceFile::parse(&bytes).unwrap();
+        let crc = crc32fast::hash(&bytes[32..44]);
+        assert_eq!(parsed.header.checksum_status, ChecksumStatus::Valid(crc));
+
+        // Corrupted payload still parses but reports the mismatch
+        let mut bad = bytes.clone();
+        bad[33] ^= 0x40;
+        let parsed = AliceFile::parse(&bad).unwrap();
+        assert!(matches!(
+            parsed.header.checksum_status,
+            ChecksumStatus::Mismatch { stored, .. } if stored == crc
+        ));
+
+        let mut bad = bytes.clone();
+        bad[7] |= 0x80;
+        assert!(matches!(AliceFile::parse(&bad), Err(AliceError::InvalidField { .. })));
+    }
+
+    #[test]
+    fn test_v1_still_readable() {
+        let mut bytes = AliceFileBuilder::perlin(3, 4.0, 2).unit("K").build().unwrap().to_bytes();
+        // Rewrite as a v1 file with garbage in the then-undefined fields
+        bytes[5] = 1;
+        bytes[7] = 0xff;
+        bytes[28..32].copy_from_slice(&[1, 2, 3, 4]);
+
+        let parsed = AliceFile::parse(&bytes).unwrap();
+        assert_eq!(parsed.header.version, 1);
+        assert_eq!(parsed.header.flags, 0);
+        assert_eq!(parsed.header.checksum_status, ChecksumStatus::Absent);
+        assert_eq!(parsed.metadata.unit.as_deref(), Some("K"));
+    }
+
+    #[test]
+    fn test_compressed_metadata() {
+        let mut meta = AliceMetadata {
+            description: Some("calibrated ".repeat(64)),
+            ..Default::default()
+        };
+        meta.custom.insert("channels".into(), serde_json::json!((0..32).collect::<Vec<_>>()));
+
+        let file = AliceFileBuilder::perlin(1, 2.0, 3).with_metadata(meta.clone()).build().unwrap();
+        let bytes = file.to_bytes();
+        assert_ne!(bytes[7] & FLAG_COMPRESSED_METADATA, 0);
+        assert!((file.header.metadata_length as usize) < meta.to_json().len());
+        assert_eq!(file.header.compressed_size, bytes.len() as u64);
+
+        let parsed = AliceFile::parse(&bytes).unwrap();
+        assert_eq!(parsed.metadata, meta);
+    }
+
+    #[test]
+    fn test_big_payload_framing() {
+        let file = AliceFileBuilder::from_linear(1 << 16, 0, 10).build().unwrap();
+        let payload = file.payload.to_bytes();
+        let mut header = file.header.clone();
+        header.flags |= FLAG_BIG_PAYLOAD;
+
+        let mut bytes = header.to_bytes().to_vec();
+        bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&payload);
+        bytes.extend_from_slice(&file.metadata.to_json());
+
+        let parsed = AliceFile::parse(&bytes).unwrap();
+        assert_eq!(parsed.header.payload_length, Some(payload.len() as u64));
+        assert!(matches!(parsed.header.checksum_status, ChecksumStatus::Valid(_)));
+
+        bytes.push(0);
+        assert!(matches!(AliceFile::parse(&bytes), Err(AliceError::PayloadSizeMismatch { .. })));
+    }
+
+    /// Feed random and mutated inputs to every parser; any panic fails the test.
+    #[test]
+    fn test_parse_never_panics() {
+        // xorshift64*: deterministic, dependency-free
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        let mut next = move || {
+            state ^= state >> 12;
+            state ^= state << 25;
+            state ^= state >> 27;
+            state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+        };
+
+        let seeds: Vec<Vec<u8>> = vec![
+            AliceFileBuilder::from_linear(1 << 16, 0, 10).unit("m").build().unwrap().to_bytes(),
+            AliceFileBuilder::polynomial(vec![1.0, 2.0, 3.0], 10).build().unwrap().to_bytes(),
+            AliceFileBuilder::polynomial_q16(vec![1, 2], 10).build().unwrap().to_bytes(),
+            AliceFileBuilder::segmented(
+                vec![
+                    Segment {
+                        start_index: 0,
+                        model: SegmentModel::Linear(LinearPayload { slope_q16: 1, intercept_q16: 2, sample_count: 3 }),
+                    },
+                    Segment {
+                        start_index: 3,
+                        model: SegmentModel::Polynomial(PolynomialPayload {
+                            coefficients: PolynomialCoefficients::Q16(vec![1, 2, 3]),
+                            sample_count: 3,
+                        }),
+                    },
+                ],
+                6,
+            )
+            .build()
+            .unwrap()
+            .to_bytes(),
+            AliceFileBuilder::composite(terrain_layers()).build().unwrap().to_bytes(),
+            AliceFileBuilder::perlin(1, 2.0, 3)
+                .with_metadata(AliceMetadata {
+                    description: Some("compressed ".repeat(40)),
+                    ..Default::default()
+                })
+                .build()
+                .unwrap()
+                .to_bytes(),
+            AliceFileBuilder::mandelbrot(100, -0.5, 0.0).build().unwrap().to_bytes(),
+            AliceFileBuilder::fourier(
+                8.0,
+                vec![FourierTerm { frequency: 1, amplitude: 1.0, phase: 0.0 }],
+                10,
+            )
+            .build()
+            .unwrap()
+            .to_bytes(),
+            AliceFileBuilder::voronoi(1, 4.0, VoronoiMetric::Euclidean, VoronoiFeature::F1, 1.0)
+                .build()
+                .unwrap()
+                .to_bytes(),
+            AliceFileBuilder::sine(0.0, vec![SineComponent { frequency: 1.0, amplitude: 1.0, phase: 0.0 }], 10)
+                .build()
+                .unwrap()
+                .to_bytes(),
+        ];
+
+        for _ in 0..20_000 {
+            let r = next();
+            let mut data = if r % 4 == 0 {
+                // Pure noise, sometimes with a valid magic prefix
+                let len = (next() % 128) as usize;
+                let mut d: Vec<u8> = (0..len).map(|_| next() as u8).collect();
+                if r % 8 == 0 && len >= 8 {
+                    d[..5].copy_from_slice(ALICE_MAGIC);
+                    d[5] = ALICE_VERSION;
+                    d[6] %= 9;
+                }
+                d
+            } else {
+                seeds[(r >> 8) as usize % seeds.len()].clone()
+            };
+
+            // Mutate: flip bytes, then maybe truncate or extend
+            for _ in 0..(next() % 6) {
+                if !data.is_empty() {
+                    let i = (next() as usize) % data.len();
+                    data[i] = next() as u8;
+                }
+            }
+            match next() % 3 {
+                0 if !data.is_empty() => data.truncate((next() as usize) % data.len()),
+                1 => data.extend((0..next() % 16).map(|_| next() as u8)),
+                _ => {}
+            }
+
+            let _ = AliceFile::parse(&data);
+            let body = data.get(AliceHeader::SIZE..).unwrap_or(&[]);
+            let _ = LinearPayload::parse(body);
+            let _ = PolynomialPayload::parse(body);
+            let _ = PerlinPayload::parse(body);
+            let _ = FractalPayload::parse(body);
+            let _ = FourierPayload::parse(body);
+            let _ = VoronoiPayload::parse(body);
+            let _ = SineWavePayload::parse(body);
+            let _ = SegmentedPayload::parse(body);
+            let _ = CompositePayload::parse(body);
+            let _ = AliceMetadata::parse(body);
+            let _ = AliceMetadata::parse_compressed(body);
+        }
+    }
+
+    #[test]
+    fn test_voronoi_roundtrip() {
+        let file = AliceFileBuilder::voronoi(42, 8.0, VoronoiMetric::Manhattan, VoronoiFeature::F2MinusF1, 0.75)
+            .build()
+            .unwrap();
+        let mut bytes = file.to_bytes();
+        let parsed = AliceFile::parse(&bytes).unwrap();
+
+        if let AlicePayload::Voronoi(p) = &parsed.payload {
+            assert_eq!(p.seed, 42);
+            assert_eq!(p.cell_density, 8.0);
+            assert_eq!(p.metric, VoronoiMetric::Manhattan);
+            assert_eq!(p.feature, VoronoiFeature::F2MinusF1);
+            assert_eq!(p.jitter, 0.75);
+        } else {
+            panic!("Wrong payload type");
+        }
+
+        // Unknown metric byte is rejected
+        bytes[AliceHeader::SIZE + 12] = 9;
+        assert!(AliceFile::parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_sine_roundtrip() {
+        let components = vec![
+            SineComponent { frequency: 0.25, amplitude: 2.0, phase: 0.0 },
+            SineComponent { frequency: 1.0, amplitude: 0.5, phase: std::f32::consts::FRAC_PI_2 },
+        ];
+        let file = AliceFileBuilder::sine(10.0, components.clone(), 100).build().unwrap();
+        let parsed = AliceFile::parse(&file.to_bytes()).unwrap();
+
+        assert_eq!(parsed.header.content_type, AliceContentType::SineWave);
+        if let AlicePayload::SineWave(p) = &parsed.payload {
+            assert_eq!(p.offset, 10.0);
+            assert_eq!(p.components, components);
+            // x = 1: 2·sin(π/2) + 0.5·sin(2π + π/2) + 10
+            assert!((p.evaluate(1.0) - 12.5).abs() < 1e-5);
+            assert!(p.equation_string().starts_with("y = 2.0000·sin(2π·0.2500x)"));
+        } else {
+            panic!("Wrong payload type");
+        }
+
+        let component = SineComponent { frequency: 1.0, amplitude: 1.0, phase: 0.0 };
+        let max = SineWavePayload::MAX_COMPONENTS;
+        assert!(AliceFileBuilder::sine(0.0, vec![component; max], 100).build().is_ok());
+        assert!(AliceFileBuilder::sine(0.0, vec![component; max + 1], 100).build().is_err());
+
+        let payload = SineWavePayload { offset: 0.0, sample_count: 100, components: vec![component; max + 1] };
+        assert!(matches!(
+            AlicePayload::SineWave(payload).validate(),
+            Err(AliceError::InvalidField { field: "sine component count", value: 17 })
+        ));
+    }
+
     #[test]
     fn test_equation_string() {
         let payload = LinearPayload {
@@ -1,12 +1,75 @@
 //! ALICE-Zip (.alz) decoder
+//!
+//! File Format:
+//! ```text
+//! ┌──────────────────────────────────────┐
+//! │ Header (24 bytes)                    │
+//! │   Magic: "ALICE" (5 bytes)           │
+//! │   Version: u8 (1)                    │
+//! │   Content Type: u8                   │
+//! │   Flags: u8 (reserved)               │
+//! │   Original Size: u64 (LE)            │
+//! │   Compressed Size: u64 (LE)          │
+//! ├──────────────────────────────────────┤
+//! │ Body (compressed size - 24 bytes)    │
+//! └──────────────────────────────────────┘
+//! ```
+//!
+//! Procedural bodies (Perlin, Polynomial, Sine, Fourier, Fractal) are
+//! parameter blocks with the same encoding as the matching .alice payload.
+//! `RawLzma` bodies are an LZMA-alone stream whose decompressed length must
+//! equal the header's original size.
+//!
+//! Compressed size is the length of the whole file, so a .alz file carries
+//! no trailing data.
 
-// AlzHeader, AlzContentType and their methods are the public ALZ wire format
-// API. Not all are instantiated within this crate but are required for
-// external ALZ stream consumers.
+// The writer and AlzFile accessors are the public ALZ wire format API; the
+// viewer itself only parses.
 #![allow(dead_code)]
 
+use super::alice::{
+    AliceError, AlicePayload, FourierPayload, FractalPayload, PerlinPayload, PolynomialPayload,
+    SineWavePayload,
+};
+use std::io::Write;
+
+/// Current ALZ format version
+pub const ALZ_VERSION: u8 = 1;
+
+/// Upper bound on decompressed `RawLzma` data (256 MiB)
+pub const MAX_RAW_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Error produced when parsing .alz data
+#[derive(Debug, thiserror::Error)]
+pub enum AlzError {
+    #[error("invalid magic {0:?} (expected \"ALICE\")")]
+    BadMagic([u8; 5]),
+    #[error("unsupported ALZ version {0}")]
+    UnsupportedVersion(u8),
+    #[error("data truncated: need {need} bytes, got {got}")]
+    Truncated { need: usize, got: usize },
+    #[error("unknown ALZ content type {0}")]
+    UnknownContentType(u8),
+    #[error("size mismatch: header says {expected} bytes, got {got}")]
+    SizeMismatch { expected: u64, got: u64 },
+    #[error("invalid value {value} for {field}")]
+    InvalidField { field: &'static str, value: u64 },
+    #[error("invalid parameter block: {0}")]
+    Block(#[from] AliceError),
+    #[error("LZMA decompression failed: {0}")]
+    Lzma(#[from] lzma_rs::error::Error),
+}
+
+/// Read a u64 at `offset`; callers check the header length first
+fn le_u64(data: &[u8], offset: usize) -> u64 {
+    let mut b = [0u8; 8];
+    b.copy_from_slice(&data[offset..offset + 8]);
+    u64::from_le_bytes(b)
+}
+
 /// ALICE-Zip file header
 #[repr(C)]
+#[derive(Debug, Clone, Copy)]
 pub struct AlzHeader {
     /// Magic bytes: "ALICE"
     pub magic: [u8; 5],
@@ -24,6 +87,51 @@ pub struct AlzHeader {
 
 impl AlzHeader {
     pub const MAGIC: [u8; 5] = *b"ALICE";
+    pub const SIZE: usize = 24;
+
+    /// Parse and validate the header against the full file contents
+    pub fn parse(data: &[u8]) -> Result<Self, AlzError> {
+        if data.len() < Self::SIZE {
+            return Err(AlzError::Truncated { need: Self::SIZE, got: data.len() });
+        }
+        let mut magic = [0u8; 5];
+        magic.copy_from_slice(&data[0..5]);
+        let header = Self {
+            magic,
+            version: data[5],
+            content_type: data[6],
+            flags: data[7],
+            original_size: le_u64(data, 8),
+            compressed_size: le_u64(data, 16),
+        };
+
+        if !header.is_valid() {
+            return Err(AlzError::BadMagic(magic));
+        }
+        if header.version != ALZ_VERSION {
+            return Err(AlzError::UnsupportedVersion(header.version));
+        }
+        AlzContentType::try_from(header.content_type)?;
+        if header.compressed_size != data.len() as u64 {
+            return Err(AlzError::SizeMismatch {
+                expected: header.compressed_size,
+                got: data.len() as u64,
+            });
+        }
+        Ok(header)
+    }
+
+    /// Serialize header to bytes
+    pub fn to_bytes(self) -> [u8; Self::SIZE] {
+        let mut buf = [0u8; Self::SIZE];
+        buf[0..5].copy_from_slice(&self.magic);
+        buf[5] = self.version;
+        buf[6] = self.content_type;
+        buf[7] = self.flags;
+        buf[8..16].copy_from_slice(&self.original_size.to_le_bytes());
+        buf[16..24].copy_from_slice(&self.compressed_size.to_le_bytes());
+        buf
+    }
 
     /// Validate header
     pub fn is_valid(&self) -> bool {
@@ -50,7 +158,7 @@ pub enum AlzContentType {
 }
 
 impl TryFrom<u8> for AlzContentType {
-    type Error = ();
+    type Error = AlzError;
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
@@ -60,7 +168,334 @@ impl TryFrom<u8> for AlzContentType {
             3 => Ok(Self::Sine),
             4 => Ok(Self::Fourier),
             5 => Ok(Self::Fractal),
-            _ => Err(()),
+            other => Err(AlzError::UnknownContentType(other)),
         }
     }
 }
+
+impl AlzContentType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::RawLzma => "Raw LZMA",
+            Self::Perlin => "Perlin Noise",
+            Self::Polynomial => "Polynomial",
+            Self::Sine => "Sine Wave",
+            Self::Fourier => "Fourier Series",
+            Self::Fractal => "Fractal",
+        }
+    }
+}
+
+/// Decoded ALZ body
+#[derive(Debug, Clone)]
+pub enum AlzContent {
+    /// Decompressed bytes of a `RawLzma` body
+    RawLzma(Vec<u8>),
+    Perlin(PerlinPayload),
+    Polynomial(PolynomialPayload),
+    Sine(SineWavePayload),
+    Fourier(FourierPayload),
+    Fractal(FractalPayload),
+}
+
+impl AlzContent {
+    pub fn content_type(&self) -> AlzContentType {
+        match self {
+            Self::RawLzma(_) => AlzContentType::RawLzma,
+            Self::Perlin(_) => AlzContentType::Perlin,
+            Self::Polynomial(_) => AlzContentType::Polynomial,
+            Self::Sine(_) => AlzContentType::Sine,
+            Self::Fourier(_) => AlzContentType::Fourier,
+            Self::Fractal(_) => AlzContentType::Fractal,
+        }
+    }
+
+    /// Equivalent .alice payload, or `None` for raw data
+    pub fn to_alice_payload(&self) -> Option<AlicePayload> {
+        match self {
+            Self::RawLzma(_) => None,
+            Self::Perlin(p) => Some(AlicePayload::Perlin(p.clone())),
+            Self::Polynomial(p) => Some(AlicePayload::Polynomial(p.clone())),
+            Self::Sine(p) => Some(AlicePayload::SineWave(p.clone())),
+            Self::Fourier(p) => Some(AlicePayload::Fourier(p.clone())),
+            Self::Fractal(p) => Some(AlicePayload::Fractal(p.clone())),
+        }
+    }
+
+    pub fn equation_string(&self) -> String {
+        match self {
+            Self::RawLzma(data) => format!("raw data ({} bytes)", data.len()),
+            Self::Perlin(p) => p.equation_string(),
+            Self::Polynomial(p) => p.equation_string(),
+            Self::Sine(p) => p.equation_string(),
+            Self::Fourier(p) => p.equation_string(),
+            Self::Fractal(p) => p.equation_string(),
+        }
+    }
+}
+
+/// Writer that fails once more than `limit` bytes are written,
+/// so a hostile LZMA stream cannot expand without bound
+struct LimitedWriter {
+    buf: Vec<u8>,
+    limit: usize,
+}
+
+impl Write for LimitedWriter {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        if self.buf.len() + data.len() > self.limit {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "decompressed data exceeds declared original size",
+            ));
+        }
+        self.buf.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Complete parsed .alz file
+#[derive(Debug, Clone)]
+pub struct AlzFile {
+    pub header: AlzHeader,
+    pub content: AlzContent,
+}
+
+impl AlzFile {
+    /// Create a file around `content`; raw data records its own length as
+    /// the original size, procedural blocks default to 0 until
+    /// [`with_original_size`](Self::with_original_size) is called
+    pub fn new(content: AlzContent) -> Self {
+        let original_size = match &content {
+            AlzContent::RawLzma(data) => data.len() as u64,
+            _ => 0,
+        };
+        Self {
+            header: AlzHeader {
+                magic: AlzHeader::MAGIC,
+                version: ALZ_VERSION,
+                content_type: content.content_type() as u8,
+                flags: 0,
+                original_size,
+                compressed_size: 0,
+            },
+            content,
+        }
+    }
+
+    /// Set the size of the data the parameters describe
+    pub fn with_original_size(mut self, size: u64) -> Self {
+        self.header.original_size = size;
+        self
+    }
+
+    /// Parse complete .alz file
+    pub fn parse(data: &[u8]) -> Result<Self, AlzError> {
+        let header = AlzHeader::parse(data)?;
+        let body = &data[AlzHeader::SIZE..];
+
+        let content = match AlzContentType::try_from(header.content_type)? {
+            AlzContentType::RawLzma => {
+                if header.original_size > MAX_RAW_SIZE {
+                    return Err(AlzError::InvalidField {
+                        field: "original size",
+                        value: header.original_size,
+                    });
+                }
+                let mut out = LimitedWriter {
+                    buf: Vec::new(),
+                    limit: header.original_size as usize,
+                };
+                lzma_rs::lzma_decompress(&mut &body[..], &mut out)?;
+                if out.buf.len() as u64 != header.original_size {
+                    return Err(AlzError::SizeMismatch {
+                        expected: header.original_size,
+                        got: out.buf.len() as u64,
+                    });
+                }
+                AlzContent::RawLzma(out.buf)
+            }
+            AlzContentType::Perlin => AlzContent::Perlin(PerlinPayload::parse(body)?),
+            AlzContentType::Polynomial => AlzContent::Polynomial(PolynomialPayload::parse(body)?),
+            AlzContentType::Sine => AlzContent::Sine(SineWavePayload::parse(body)?),
+            AlzContentType::Fourier => AlzContent::Fourier(FourierPayload::parse(body)?),
+            AlzContentType::Fractal => AlzContent::Fractal(FractalPayload::parse(body)?),
+        };
+
+        Ok(Self { header, content })
+    }
+
+    /// Serialize to bytes, compressing raw data with LZMA
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let body = match &self.content {
+            AlzContent::RawLzma(data) => {
+                let mut out = Vec::new();
+                // Reading from a slice into a Vec performs no fallible I/O
+                lzma_rs::lzma_compress(&mut &data[..], &mut out)
+                    .expect("in-memory LZMA compression cannot fail");
+                out
+            }
+            AlzContent::Perlin(p) => p.to_bytes(),
+            AlzContent::Polynomial(p) => p.to_bytes(),
+            AlzContent::Sine(p) => p.to_bytes(),
+            AlzContent::Fourier(p) => p.to_bytes(),
+            AlzContent::Fractal(p) => p.to_bytes(),
+        };
+
+        let mut header = self.header;
+        header.content_type = self.content.content_type() as u8;
+        header.compressed_size = (AlzHeader::SIZE + body.len()) as u64;
+        if let AlzContent::RawLzma(data) = &self.content {
+            header.original_size = data.len() as u64;
+        }
+
+        let mut buf = Vec::with_capacity(AlzHeader::SIZE + body.len());
+        buf.extend_from_slice(&header.to_bytes());
+        buf.extend_from_slice(&body);
+        buf
+    }
+
+    pub fn equation_string(&self) -> String {
+        self.content.equation_string()
+    }
+
+    pub fn content_type_name(&self) -> &'static str {
+        self.content.content_type().name()
+    }
+
+    /// Compression ratio (original / compressed)
+    pub fn compression_ratio(&self) -> f64 {
+        if self.header.compressed_size == 0 {
+            0.0
+        } else {
+            self.header.original_size as f64 / self.header.compressed_size as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::alice::{FourierTerm, PolynomialCoefficients, SineComponent};
+
+    fn roundtrip(content: AlzContent) -> AlzFile {
+        let bytes = AlzFile::new(content).with_original_size(4096).to_bytes();
+        let parsed = AlzFile::parse(&bytes).unwrap();
+        assert_eq!(parsed.header.compressed_size, bytes.len() as u64);
+        parsed
+    }
+
+    #[test]
+    fn test_procedural_roundtrip() {
+        let parsed = roundtrip(AlzContent::Perlin(PerlinPayload {
+            seed: 7,
+            scale: 4.0,
+            octaves: 5,
+            persistence: 0.5,
+            lacunarity: 2.0,
+        }));
+        assert_eq!(parsed.header.original_size, 4096);
+        assert!(matches!(parsed.content, AlzContent::Perlin(ref p) if p.seed == 7 && p.octaves == 5));
+
+        let parsed = roundtrip(AlzContent::Polynomial(PolynomialPayload {
+            coefficients: PolynomialCoefficients::F64(vec![1.0, -0.5, 0.25]),
+            sample_count: 1000,
+        }));
+        assert!(matches!(parsed.content, AlzContent::Polynomial(ref p)
+            if p.coefficients_f64() == vec![1.0, -0.5, 0.25]));
+
+        let parsed = roundtrip(AlzContent::Sine(SineWavePayload {
+            offset: 1.5,
+            sample_count: 100,
+            components: vec![SineComponent { frequency: 0.1, amplitude: 2.0, phase: 0.0 }],
+        }));
+        assert!(matches!(parsed.content, AlzContent::Sine(ref p)
+            if p.offset == 1.5 && p.components.len() == 1));
+
+        let parsed = roundtrip(AlzContent::Fourier(FourierPayload {
+            period: 64.0,
+            sample_count: 64,
+            terms: vec![FourierTerm { frequency: 1, amplitude: 1.0, phase: 0.5 }],
+        }));
+        assert!(matches!(parsed.content, AlzContent::Fourier(ref p)
+            if p.period == 64.0 && p.terms[0].phase == 0.5));
+
+        let parsed = roundtrip(AlzContent::Fractal(FractalPayload {
+            fractal_type: 1,
+            max_iterations: 300,
+            escape_radius: 2.0,
+            center_x: 0.0,
+            center_y: 0.0,
+            julia_cx: -0.7,
+            julia_cy: 0.27,
+        }));
+        assert!(matches!(parsed.content, AlzContent::Fractal(ref p)
+            if p.fractal_type == 1 && p.julia_cx == -0.7));
+    }
+
+    #[test]
+    fn test_raw_lzma_roundtrip() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+        let bytes = AlzFile::new(AlzContent::RawLzma(data.clone())).to_bytes();
+        assert!(bytes.len() < data.len());
+
+        let parsed = AlzFile::parse(&bytes).unwrap();
+        assert_eq!(parsed.header.original_size, data.len() as u64);
+        assert!(parsed.compression_ratio() > 1.0);
+        match parsed.content {
+            AlzContent::RawLzma(out) => assert_eq!(out, data),
+            other => panic!("expected raw data, got {:?}", other.content_type()),
+        }
+    }
+
+    #[test]
+    fn test_raw_lzma_size_checked() {
+        let mut bytes = AlzFile::new(AlzContent::RawLzma(vec![42; 1000])).to_bytes();
+        // Declared original size smaller than the stream expands to
+        bytes[8..16].copy_from_slice(&10u64.to_le_bytes());
+        assert!(matches!(AlzFile::parse(&bytes), Err(AlzError::Lzma(_))));
+
+        bytes[8..16].copy_from_slice(&(MAX_RAW_SIZE + 1).to_le_bytes());
+        assert!(matches!(AlzFile::parse(&bytes), Err(AlzError::InvalidField { .. })));
+    }
+
+    #[test]
+    fn test_header_errors() {
+        let good = roundtrip(AlzContent::Perlin(PerlinPayload {
+            seed: 1,
+            scale: 1.0,
+            octaves: 1,
+            persistence: 0.5,
+            lacunarity: 2.0,
+        }))
+        .to_bytes();
+
+        assert!(matches!(AlzFile::parse(&good[..10]), Err(AlzError::Truncated { need: 24, got: 10 })));
+
+        let mut bad = good.clone();
+        bad[0] = b'X';
+        assert!(matches!(AlzFile::parse(&bad), Err(AlzError::BadMagic(_))));
+
+        let mut bad = good.clone();
+        bad[5] = 9;
+        assert!(matches!(AlzFile::parse(&bad), Err(AlzError::UnsupportedVersion(9))));
+
+        let mut bad = good.clone();
+        bad[6] = 200;
+        assert!(matches!(AlzFile::parse(&bad), Err(AlzError::UnknownContentType(200))));
+
+        let mut bad = good.clone();
+        bad.push(0);
+        assert!(matches!(AlzFile::parse(&bad), Err(AlzError::SizeMismatch { .. })));
+
+        // Perlin header on a fractal-sized block
+        let mut bad = good[..AlzHeader::SIZE].to_vec();
+        bad[6] = AlzContentType::Fractal as u8;
+        bad.extend_from_slice(&good[AlzHeader::SIZE..]);
+        assert!(matches!(AlzFile::parse(&bad), Err(AlzError::Block(_))));
+    }
+}
@@ -1,9 +1,121 @@
 //! ALICE Streaming Protocol (.asp) decoder
+//!
+//! A stream (or .asp file) is a sequence of packets, each a 16-byte
+//! [`AspHeader`] followed by `payload_size` bytes. All integers are
+//! little-endian.
+//!
+//! Payload layouts:
+//! ```text
+//! I (keyframe)    width u32 │ height u32 │ fps f32 │ content type u8 │ 3 reserved
+//!                 │ params: .alice payload body of that content type
+//! D (delta)       patch count u16 │ mv width u16 │ mv height u16 │ reserved u16
+//!                 │ patches: offset u16, length u16, bytes (into the current params)
+//!                 │ mv width × mv height motion vectors (dx i8, dy i8)
+//! C (correction)  ROI count u16 │ reserved u16
+//!                 │ ROIs: x u16, y u16, width u16, height u16,
+//!                 │       width × height RGB residuals (i8, added as value / 255)
+//! S (sync)        command u8 │ 3 reserved │ value u32
+//! ```
+//!
+//! Deltas patch the parameter block of the previous frame, so a D-packet is
+//! only meaningful after the I-packet (and deltas) preceding it.
+//! Corrections apply to the current frame and are cleared by the next
+//! I- or D-packet.
+//!
+//! [`AspStreamState`] decodes packets; [`AspWriter`] produces them.
+//! [`AspRecording`] indexes a recorded stream for random access and
+//! [`AspPlayer`] plays it back; [`AspListener`](super::AspListener)
+//! receives a live stream.
 
-// ASP structs and enums define the streaming wire protocol.
-// Full decoding is not yet implemented; types are stubs for future work.
+// ASP structs and enums define the streaming wire protocol; the viewer uses
+// the decoding half, the writer is used by alice-create.
 #![allow(dead_code)]
 
+use super::alice::{AliceContentType, AliceError, AlicePayload};
+use super::ProceduralContent;
+use std::io::{self, Write};
+use std::ops::Range;
+
+/// Error produced when decoding ASP packets
+#[derive(Debug, thiserror::Error)]
+pub enum AspError {
+    #[error("invalid packet magic {0:?} (expected \"ASP\\x01\")")]
+    BadMagic([u8; 4]),
+    #[error("unknown packet type 0x{0:02x}")]
+    UnknownPacketType(u8),
+    #[error("packet truncated: need {need} bytes, got {got}")]
+    Truncated { need: usize, got: usize },
+    #[error("size mismatch: expected {expected} bytes, got {got}")]
+    SizeMismatch { expected: usize, got: usize },
+    #[error("out-of-order packet: sequence {got} after {last}")]
+    OutOfOrder { last: u32, got: u32 },
+    #[error("{0:?} before the first keyframe")]
+    NoKeyframe(AspPacketType),
+    #[error("invalid value {value} for {field}")]
+    InvalidField { field: &'static str, value: u64 },
+    #[error("invalid keyframe parameters: {0}")]
+    Params(#[from] AliceError),
+}
+
+/// Bounds-checked little-endian reader over a payload
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], AspError> {
+        let end = self.pos + n;
+        let bytes = self
+            .data
+            .get(self.pos..end)
+            .ok_or(AspError::Truncated { need: end, got: self.data.len() })?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], AspError> {
+        let mut b = [0u8; N];
+        b.copy_from_slice(self.bytes(N)?);
+        Ok(b)
+    }
+
+    fn u8(&mut self) -> Result<u8, AspError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, AspError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, AspError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn f32(&mut self) -> Result<f32, AspError> {
+        Ok(f32::from_le_bytes(self.array()?))
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        let rest = &self.data[self.pos..];
+        self.pos = self.data.len();
+        rest
+    }
+
+    /// Fail if bytes are left over after a fixed layout
+    fn finish(&self) -> Result<(), AspError> {
+        if self.pos == self.data.len() {
+            Ok(())
+        } else {
+            Err(AspError::SizeMismatch { expected: self.pos, got: self.data.len() })
+        }
+    }
+}
+
 /// ASP packet types
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -19,7 +131,7 @@ pub enum AspPacketType {
 }
 
 impl TryFrom<u8> for AspPacketType {
-    type Error = ();
+    type Error = AspError;
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
@@ -27,7 +139,7 @@ impl TryFrom<u8> for AspPacketType {
             0x44 => Ok(Self::DPacket),
             0x43 => Ok(Self::CPacket),
             0x53 => Ok(Self::SPacket),
-            _ => Err(()),
+            _ => Err(AspError::UnknownPacketType(value)),
         }
     }
 }
@@ -52,55 +164,509 @@ pub struct AspHeader {
 
 impl AspHeader {
     pub const MAGIC: [u8; 4] = *b"ASP\x01";
+    pub const SIZE: usize = 16;
+
+    /// Parse and validate a header from the start of `data`
+    ///
+    /// Fields are read byte-wise, never through a reference into the packed
+    /// struct.
+    pub fn parse(data: &[u8]) -> Result<Self, AspError> {
+        let mut r = Reader::new(data.get(..Self::SIZE).unwrap_or(data));
+        let header = Self {
+            magic: r.array()?,
+            packet_type: r.u8()?,
+            flags: r.u8()?,
+            reserved: r.u16()?,
+            sequence: r.u32()?,
+            payload_size: r.u32()?,
+        };
+        if !header.is_valid() {
+            return Err(AspError::BadMagic(header.magic));
+        }
+        header.kind()?;
+        Ok(header)
+    }
+
+    /// Serialize header to bytes
+    pub fn to_bytes(self) -> [u8; Self::SIZE] {
+        let mut buf = [0u8; Self::SIZE];
+        buf[0..4].copy_from_slice(&self.magic);
+        buf[4] = self.packet_type;
+        buf[5] = self.flags;
+        buf[6..8].copy_from_slice(&{ self.reserved }.to_le_bytes());
+        buf[8..12].copy_from_slice(&{ self.sequence }.to_le_bytes());
+        buf[12..16].copy_from_slice(&{ self.payload_size }.to_le_bytes());
+        buf
+    }
 
     /// Validate header
     pub fn is_valid(&self) -> bool {
         self.magic == Self::MAGIC
     }
+
+    /// Packet type
+    pub fn kind(&self) -> Result<AspPacketType, AspError> {
+        AspPacketType::try_from(self.packet_type)
+    }
+
+    /// Header plus payload length
+    pub fn packet_len(&self) -> usize {
+        Self::SIZE + self.payload_size as usize
+    }
+}
+
+/// Header + payload bytes of one packet
+pub fn encode_packet(kind: AspPacketType, sequence: u32, payload: &[u8]) -> Vec<u8> {
+    let header = AspHeader {
+        magic: AspHeader::MAGIC,
+        packet_type: kind as u8,
+        flags: 0,
+        reserved: 0,
+        sequence,
+        payload_size: payload.len() as u32,
+    };
+    let mut buf = Vec::with_capacity(AspHeader::SIZE + payload.len());
+    buf.extend_from_slice(&header.to_bytes());
+    buf.extend_from_slice(payload);
+    buf
+}
+
+/// Whether `sequence` comes after `last`, allowing for wrap-around
+///
+/// Serial number arithmetic (RFC 1982): anything up to 2^31 ahead counts as
+/// later, so numbering may run past `u32::MAX` back to 0.
+pub fn sequence_after(sequence: u32, last: u32) -> bool {
+    sequence.wrapping_sub(last) as i32 > 0
+}
+
+/// Split a byte stream (e.g. a whole .asp file) into packets
+pub fn packets(data: &[u8]) -> impl Iterator<Item = Result<&[u8], AspError>> {
+    let mut rest = data;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let packet = AspHeader::parse(rest).and_then(|h| {
+            rest.get(..h.packet_len())
+                .ok_or(AspError::Truncated { need: h.packet_len(), got: rest.len() })
+        });
+        rest = match packet {
+            Ok(p) => &rest[p.len()..],
+            Err(_) => &[],
+        };
+        Some(packet)
+    })
 }
 
 /// Motion vector (compact, 2 bytes)
 #[repr(C, packed)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct MotionVectorCompact {
     pub dx: i8,
     pub dy: i8,
 }
 
-/// Stream state for ASP decoding
-pub struct AspStreamState {
-    /// Last keyframe data
-    pub keyframe: Option<KeyframeData>,
-    /// Current sequence number
-    pub sequence: u32,
-    /// Accumulated motion vectors
-    pub motion_vectors: Vec<MotionVectorCompact>,
-}
-
-/// Keyframe data
+/// Keyframe data (I-packet payload)
 #[derive(Debug, Clone)]
 pub struct KeyframeData {
     pub width: u32,
     pub height: u32,
     pub fps: f32,
-    /// Procedural parameters
+    /// Encoding of `params`
+    pub content_type: AliceContentType,
+    /// Procedural parameters (.alice payload body)
     pub params: Vec<u8>,
 }
 
+impl KeyframeData {
+    pub const HEADER_SIZE: usize = 16;
+    /// Largest accepted frame width or height (pixels); wgpu's default
+    /// texture size limit, so corrections fit a GPU texture
+    pub const MAX_DIMENSION: u32 = 8192;
+
+    pub fn parse(data: &[u8]) -> Result<Self, AspError> {
+        let mut r = Reader::new(data);
+        let width = r.u32()?;
+        let height = r.u32()?;
+        let fps = r.f32()?;
+        let type_byte = r.u8()?;
+        r.bytes(3)?;
+        let content_type = AliceContentType::try_from(type_byte)?;
+        for (field, value) in [("width", width), ("height", height)] {
+            if value == 0 || value > Self::MAX_DIMENSION {
+                return Err(AspError::InvalidField { field, value: value as u64 });
+            }
+        }
+        if !(fps.is_finite() && fps > 0.0) {
+            return Err(AspError::InvalidField { field: "fps", value: fps.to_bits() as u64 });
+        }
+        Ok(Self { width, height, fps, content_type, params: r.rest().to_vec() })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(Self::HEADER_SIZE + self.params.len());
+        buf.extend_from_slice(&self.width.to_le_bytes());
+        buf.extend_from_slice(&self.height.to_le_bytes());
+        buf.extend_from_slice(&self.fps.to_le_bytes());
+        buf.extend_from_slice(&[self.content_type as u8, 0, 0, 0]);
+        buf.extend_from_slice(&self.params);
+        buf
+    }
+}
+
+/// Replacement of `bytes.len()` parameter bytes at `offset`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamPatch {
+    pub offset: u16,
+    pub bytes: Vec<u8>,
+}
+
+impl ParamPatch {
+    /// Patches turning `old` into `new`, or `None` if the lengths differ
+    ///
+    /// Changed runs separated by fewer than 4 equal bytes (the size of a
+    /// patch header) are merged.
+    pub fn diff(old: &[u8], new: &[u8]) -> Option<Vec<ParamPatch>> {
+        if old.len() != new.len() || new.len() > u16::MAX as usize {
+            return None;
+        }
+        let mut runs: Vec<(usize, usize)> = Vec::new();
+        for i in (0..new.len()).filter(|&i| old[i] != new[i]) {
+            match runs.last_mut() {
+                Some((_, end)) if i - *end < 4 => *end = i + 1,
+                _ => runs.push((i, i + 1)),
+            }
+        }
+        Some(
+            runs.into_iter()
+                .map(|(start, end)| ParamPatch { offset: start as u16, bytes: new[start..end].to_vec() })
+                .collect(),
+        )
+    }
+}
+
+/// Delta data (D-packet payload)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeltaData {
+    pub patches: Vec<ParamPatch>,
+    /// Motion vector grid size (blocks)
+    pub mv_width: u16,
+    pub mv_height: u16,
+    /// Row-major motion vectors, one per block
+    pub motion_vectors: Vec<MotionVectorCompact>,
+}
+
+impl DeltaData {
+    pub fn parse(data: &[u8]) -> Result<Self, AspError> {
+        let mut r = Reader::new(data);
+        let patch_count = r.u16()?;
+        let mv_width = r.u16()?;
+        let mv_height = r.u16()?;
+        r.u16()?;
+
+        let patches = (0..patch_count)
+            .map(|_| {
+                let offset = r.u16()?;
+                let len = r.u16()? as usize;
+                Ok(ParamPatch { offset, bytes: r.bytes(len)?.to_vec() })
+            })
+            .collect::<Result<Vec<_>, AspError>>()?;
+
+        let mv_count = mv_width as usize * mv_height as usize;
+        let motion_vectors = r
+            .bytes(mv_count * 2)?
+            .chunks_exact(2)
+            .map(|b| MotionVectorCompact { dx: b[0] as i8, dy: b[1] as i8 })
+            .collect();
+        r.finish()?;
+
+        Ok(Self { patches, mv_width, mv_height, motion_vectors })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&(self.patches.len() as u16).to_le_bytes());
+        buf.extend_from_slice(&self.mv_width.to_le_bytes());
+        buf.extend_from_slice(&self.mv_height.to_le_bytes());
+        buf.extend_from_slice(&0u16.to_le_bytes());
+        for patch in &self.patches {
+            buf.extend_from_slice(&patch.offset.to_le_bytes());
+            buf.extend_from_slice(&(patch.bytes.len() as u16).to_le_bytes());
+            buf.extend_from_slice(&patch.bytes);
+        }
+        for mv in &self.motion_vectors {
+            buf.extend_from_slice(&[mv.dx as u8, mv.dy as u8]);
+        }
+        buf
+    }
+
+    /// Apply the patches to a copy of `params`
+    fn apply(&self, params: &[u8]) -> Result<Vec<u8>, AspError> {
+        let mut out = params.to_vec();
+        for patch in &self.patches {
+            let start = patch.offset as usize;
+            let target = out.get_mut(start..start + patch.bytes.len()).ok_or(AspError::InvalidField {
+                field: "patch offset",
+                value: patch.offset as u64,
+            })?;
+            target.copy_from_slice(&patch.bytes);
+        }
+        Ok(out)
+    }
+}
+
+/// Rectangular residual correction in frame pixels
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AspRoi {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+    /// Row-major RGB residuals, added to the frame as value / 255
+    pub residuals: Vec<i8>,
+}
+
+impl AspRoi {
+    pub const HEADER_SIZE: usize = 8;
+}
+
+/// Residual corrections of one ROI as an RGBA image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorrectionImage {
+    /// Top-left corner in the frame (pixels)
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Row-major RGBA; RGB are the residuals, alpha is [`Self::ROI`]
+    pub texels: Vec<i8>,
+}
+
+impl CorrectionImage {
+    /// Alpha of pixels covered by an ROI (1.0 as snorm)
+    pub const ROI: i8 = 127;
+}
+
+/// Correction data (C-packet payload)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CorrectionData {
+    pub rois: Vec<AspRoi>,
+}
+
+impl CorrectionData {
+    pub fn parse(data: &[u8]) -> Result<Self, AspError> {
+        let mut r = Reader::new(data);
+        let count = r.u16()?;
+        r.u16()?;
+        let rois = (0..count)
+            .map(|_| {
+                let (x, y, width, height) = (r.u16()?, r.u16()?, r.u16()?, r.u16()?);
+                let len = width as usize * height as usize * 3;
+                let residuals = r.bytes(len)?.iter().map(|&b| b as i8).collect();
+                Ok(AspRoi { x, y, width, height, residuals })
+            })
+            .collect::<Result<Vec<_>, AspError>>()?;
+        r.finish()?;
+        Ok(Self { rois })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&(self.rois.len() as u16).to_le_bytes());
+        buf.extend_from_slice(&0u16.to_le_bytes());
+        for roi in &self.rois {
+            for v in [roi.x, roi.y, roi.width, roi.height] {
+                buf.extend_from_slice(&v.to_le_bytes());
+            }
+            buf.extend(roi.residuals.iter().map(|&r| r as u8));
+        }
+        buf
+    }
+}
+
+/// Sync (S-packet) commands
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncCommand {
+    /// Keep-alive; value = stream time in milliseconds
+    Heartbeat = 0,
+    /// Drop decoder state and wait for the next keyframe
+    Reset = 1,
+    /// Producer finished the stream
+    EndOfStream = 2,
+}
+
+/// Sync data (S-packet payload)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncData {
+    pub command: SyncCommand,
+    pub value: u32,
+}
+
+impl SyncData {
+    pub const SIZE: usize = 8;
+
+    pub fn parse(data: &[u8]) -> Result<Self, AspError> {
+        let mut r = Reader::new(data);
+        let command = match r.u8()? {
+            0 => SyncCommand::Heartbeat,
+            1 => SyncCommand::Reset,
+            2 => SyncCommand::EndOfStream,
+            other => return Err(AspError::InvalidField { field: "sync command", value: other as u64 }),
+        };
+        r.bytes(3)?;
+        let value = r.u32()?;
+        r.finish()?;
+        Ok(Self { command, value })
+    }
+
+    pub fn to_bytes(self) -> [u8; Self::SIZE] {
+        let mut buf = [0u8; Self::SIZE];
+        buf[0] = self.command as u8;
+        buf[4..8].copy_from_slice(&self.value.to_le_bytes());
+        buf
+    }
+}
+
+/// Stream state for ASP decoding
+#[derive(Debug, Clone)]
+pub struct AspStreamState {
+    /// Last keyframe data
+    pub keyframe: Option<KeyframeData>,
+    /// Sequence number of the last processed packet
+    pub sequence: u32,
+    /// Packets processed so far
+    pub packets: u64,
+    /// Frames (I- and D-packets) since the last keyframe
+    pub frames_since_keyframe: u32,
+    /// Motion vectors of the latest delta
+    pub motion_vectors: Vec<MotionVectorCompact>,
+    /// Motion vector grid size (blocks)
+    pub motion_grid: (u16, u16),
+    /// Corrections for the current frame
+    pub corrections: Vec<AspRoi>,
+    /// Set by an end-of-stream sync packet
+    pub ended: bool,
+    /// Keyframe parameters with the deltas since applied
+    params: Vec<u8>,
+    /// Decoded parameters of the current frame
+    payload: Option<AlicePayload>,
+}
+
 impl AspStreamState {
     pub fn new() -> Self {
         Self {
             keyframe: None,
             sequence: 0,
+            packets: 0,
+            frames_since_keyframe: 0,
             motion_vectors: Vec::new(),
+            motion_grid: (0, 0),
+            corrections: Vec::new(),
+            ended: false,
+            params: Vec::new(),
+            payload: None,
         }
     }
 
-    /// Process incoming packet
-    pub fn process_packet(&mut self, _data: &[u8]) -> Result<(), &'static str> {
-        log::warn!("process_packet() is a stub — ASP packet processing not yet implemented");
-        // TODO: Implement actual packet processing
-        Ok(())
+    /// Process one complete packet (header + payload)
+    ///
+    /// A rejected packet leaves the state unchanged.
+    pub fn process_packet(&mut self, data: &[u8]) -> Result<AspPacketType, AspError> {
+        let header = AspHeader::parse(data)?;
+        if data.len() != header.packet_len() {
+            return Err(AspError::SizeMismatch { expected: header.packet_len(), got: data.len() });
+        }
+        let sequence = header.sequence;
+        if self.packets > 0 && !sequence_after(sequence, self.sequence) {
+            return Err(AspError::OutOfOrder { last: self.sequence, got: sequence });
+        }
+
+        let kind = header.kind()?;
+        let payload = &data[AspHeader::SIZE..];
+        match kind {
+            AspPacketType::IPacket => {
+                let keyframe = KeyframeData::parse(payload)?;
+                let decoded = AlicePayload::parse(keyframe.content_type, &keyframe.params)?;
+                self.params = keyframe.params.clone();
+                self.payload = Some(decoded);
+                self.keyframe = Some(keyframe);
+                self.frames_since_keyframe = 0;
+                self.motion_vectors.clear();
+                self.motion_grid = (0, 0);
+                self.corrections.clear();
+                self.ended = false;
+            }
+            AspPacketType::DPacket => {
+                let keyframe = self.keyframe.as_ref().ok_or(AspError::NoKeyframe(kind))?;
+                let delta = DeltaData::parse(payload)?;
+                let params = delta.apply(&self.params)?;
+                let decoded = AlicePayload::parse(keyframe.content_type, &params)?;
+                self.params = params;
+                self.payload = Some(decoded);
+                self.frames_since_keyframe += 1;
+                self.motion_vectors = delta.motion_vectors;
+                self.motion_grid = (delta.mv_width, delta.mv_height);
+                self.corrections.clear();
+            }
+            AspPacketType::CPacket => {
+                self.keyframe.as_ref().ok_or(AspError::NoKeyframe(kind))?;
+                let correction = CorrectionData::parse(payload)?;
+                self.corrections.extend(correction.rois);
+            }
+            AspPacketType::SPacket => match SyncData::parse(payload)?.command {
+                SyncCommand::Heartbeat => {}
+                SyncCommand::Reset => {
+                    let (sequence, packets) = (self.sequence, self.packets);
+                    *self = Self { sequence, packets, ..Self::new() };
+                }
+                SyncCommand::EndOfStream => self.ended = true,
+            },
+        }
+
+        self.sequence = sequence;
+        self.packets += 1;
+        Ok(kind)
+    }
+
+    /// Decoded parameters of the current frame
+    pub fn payload(&self) -> Option<&AlicePayload> {
+        self.payload.as_ref()
+    }
+
+    /// Current frame as renderable content
+    pub fn content(&self) -> Option<ProceduralContent> {
+        self.payload.as_ref().map(super::content_from_payload)
+    }
+
+    /// Corrections of the current frame, one image per ROI clipped to the
+    /// keyframe
+    ///
+    /// Empty without a keyframe; ROIs outside the frame are left out. Later
+    /// ROIs are meant to be drawn over earlier ones.
+    pub fn correction_images(&self) -> Vec<CorrectionImage> {
+        let Some(keyframe) = &self.keyframe else {
+            return Vec::new();
+        };
+        self.corrections
+            .iter()
+            .filter_map(|roi| {
+                let (x, y) = (roi.x as u32, roi.y as u32);
+                let width = (roi.width as u32).min(keyframe.width.saturating_sub(x));
+                let height = (roi.height as u32).min(keyframe.height.saturating_sub(y));
+                if width == 0 || height == 0 {
+                    return None;
+                }
+                let mut texels = Vec::with_capacity(width as usize * height as usize * 4);
+                for row in roi.residuals.chunks_exact(roi.width as usize * 3).take(height as usize) {
+                    for rgb in row[..width as usize * 3].chunks_exact(3) {
+                        texels.extend_from_slice(rgb);
+                        texels.push(CorrectionImage::ROI);
+                    }
+                }
+                Some(CorrectionImage { x, y, width, height, texels })
+            })
+            .collect()
     }
 }
 
@@ -109,3 +675,703 @@ impl Default for AspStreamState {
         Self::new()
     }
 }
+
+/// One frame (I- or D-packet) of a recorded stream
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AspFrame {
+    /// Index of the frame's packet
+    pub packet: usize,
+    /// Index of the frame holding the keyframe this one decodes from
+    pub keyframe: usize,
+    pub sequence: u32,
+    /// Seconds since the first frame
+    pub time: f64,
+}
+
+/// Recorded stream indexed for random access
+pub struct AspRecording {
+    data: Vec<u8>,
+    /// Byte range of every packet
+    packets: Vec<Range<usize>>,
+    frames: Vec<AspFrame>,
+    /// Frame indices of the I-packets
+    keyframes: Vec<usize>,
+}
+
+impl AspRecording {
+    /// Index every packet and frame of `data`
+    ///
+    /// Frames before the first keyframe cannot be decoded and are skipped.
+    /// Frame times advance by 1 / fps of the governing keyframe. A truncated
+    /// final packet (a recording cut off mid-write) is left out.
+    pub fn index(data: Vec<u8>) -> Result<Self, AspError> {
+        let mut packets = Vec::new();
+        let mut frames: Vec<AspFrame> = Vec::new();
+        let mut keyframes = Vec::new();
+        let mut last_sequence: Option<u32> = None;
+        let mut frame_duration = 0.0;
+        let mut offset = 0;
+
+        for packet in self::packets(&data) {
+            let packet = match packet {
+                Ok(packet) => packet,
+                Err(AspError::Truncated { .. }) => {
+                    tracing::warn!("ASP recording: ignoring {} bytes of a truncated final packet", data.len() - offset);
+                    break;
+                }
+                Err(e) => return Err(e),
+            };
+            let header = AspHeader::parse(packet)?;
+            let sequence = header.sequence;
+            if let Some(last) = last_sequence.filter(|&last| !sequence_after(sequence, last)) {
+                return Err(AspError::OutOfOrder { last, got: sequence });
+            }
+            last_sequence = Some(sequence);
+
+            let time = frames.last().map_or(0.0, |f| f.time + frame_duration);
+            match header.kind()? {
+                AspPacketType::IPacket => {
+                    let keyframe = KeyframeData::parse(&packet[AspHeader::SIZE..])?;
+                    frame_duration = 1.0 / keyframe.fps as f64;
+                    keyframes.push(frames.len());
+                    frames.push(AspFrame { packet: packets.len(), keyframe: frames.len(), sequence, time });
+                }
+                AspPacketType::DPacket => {
+                    if let Some(&keyframe) = keyframes.last() {
+                        frames.push(AspFrame { packet: packets.len(), keyframe, sequence, time });
+                    }
+                }
+                AspPacketType::CPacket | AspPacketType::SPacket => {}
+            }
+            packets.push(offset..offset + packet.len());
+            offset += packet.len();
+        }
+
+        Ok(Self { data, packets, frames, keyframes })
+    }
+
+    pub fn frames(&self) -> &[AspFrame] {
+        &self.frames
+    }
+
+    /// Frame indices of the I-packets
+    pub fn keyframes(&self) -> &[usize] {
+        &self.keyframes
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Time of the last frame
+    pub fn duration(&self) -> f64 {
+        self.frames.last().map_or(0.0, |f| f.time)
+    }
+
+    /// Index of the last frame shown at `time`
+    pub fn frame_at(&self, time: f64) -> usize {
+        self.frames.partition_point(|f| f.time <= time).saturating_sub(1)
+    }
+
+    /// Packets belonging to `frame`: its I/D packet up to the next frame's
+    fn frame_packets(&self, frame: usize) -> Range<usize> {
+        let start = self.frames[frame].packet;
+        let end = self.frames.get(frame + 1).map_or(self.packets.len(), |f| f.packet);
+        start..end
+    }
+
+    fn packet(&self, index: usize) -> &[u8] {
+        &self.data[self.packets[index].clone()]
+    }
+
+    /// Decode `frame` from the nearest preceding keyframe
+    pub fn decode(&self, frame: usize) -> Result<AspStreamState, AspError> {
+        let mut state = AspStreamState::new();
+        let start = self.frames[frame].keyframe;
+        for f in start..=frame {
+            self.decode_into(&mut state, f)?;
+        }
+        Ok(state)
+    }
+
+    /// Apply the packets of `frame` to a state holding the frame before it
+    fn decode_into(&self, state: &mut AspStreamState, frame: usize) -> Result<(), AspError> {
+        for index in self.frame_packets(frame) {
+            state.process_packet(self.packet(index))?;
+        }
+        Ok(())
+    }
+}
+
+/// Frame navigation for [`AspPlayer::seek_to`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AspSeek {
+    /// Move by a number of frames
+    Step(isize),
+    PreviousKeyframe,
+    NextKeyframe,
+    Start,
+    End,
+    /// Jump to a frame index (clamped)
+    Frame(usize),
+}
+
+/// Playback of a recorded stream
+pub struct AspPlayer {
+    recording: AspRecording,
+    /// Decoded current frame
+    state: AspStreamState,
+    frame: usize,
+    /// Playback position in seconds
+    clock: f64,
+}
+
+impl AspPlayer {
+    /// Start at the first frame
+    pub fn new(recording: AspRecording) -> Result<Self, AspError> {
+        if recording.frames.is_empty() {
+            return Err(AspError::NoKeyframe(AspPacketType::DPacket));
+        }
+        let state = recording.decode(0)?;
+        Ok(Self { recording, state, frame: 0, clock: 0.0 })
+    }
+
+    pub fn recording(&self) -> &AspRecording {
+        &self.recording
+    }
+
+    /// Decoder state at the current frame
+    pub fn state(&self) -> &AspStreamState {
+        &self.state
+    }
+
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Index entry of the current frame
+    pub fn current(&self) -> &AspFrame {
+        &self.recording.frames[self.frame]
+    }
+
+    /// Jump to `frame` (clamped)
+    ///
+    /// Moving one frame forward applies that frame's packets; anything else
+    /// decodes from the nearest preceding keyframe.
+    pub fn seek(&mut self, frame: usize) -> Result<(), AspError> {
+        let frame = frame.min(self.recording.frames.len() - 1);
+        if frame == self.frame + 1 {
+            let mut state = std::mem::take(&mut self.state);
+            let result = self.recording.decode_into(&mut state, frame);
+            match result {
+                Ok(()) => self.state = state,
+                Err(e) => {
+                    self.state = self.recording.decode(self.frame)?;
+                    return Err(e);
+                }
+            }
+        } else if frame != self.frame {
+            self.state = self.recording.decode(frame)?;
+        }
+        self.frame = frame;
+        self.clock = self.recording.frames[frame].time;
+        Ok(())
+    }
+
+    /// Move `delta` frames (clamped)
+    pub fn step(&mut self, delta: isize) -> Result<(), AspError> {
+        self.seek(self.frame.saturating_add_signed(delta))
+    }
+
+    /// Jump to the previous (`forward = false`) or next keyframe
+    pub fn seek_keyframe(&mut self, forward: bool) -> Result<(), AspError> {
+        let keyframes = &self.recording.keyframes;
+        let target = if forward {
+            keyframes.iter().find(|&&k| k > self.frame).copied()
+        } else {
+            keyframes.iter().rev().find(|&&k| k < self.frame).copied()
+        };
+        match target {
+            Some(frame) => self.seek(frame),
+            None => Ok(()),
+        }
+    }
+
+    pub fn seek_to(&mut self, target: AspSeek) -> Result<(), AspError> {
+        match target {
+            AspSeek::Step(delta) => self.step(delta),
+            AspSeek::PreviousKeyframe => self.seek_keyframe(false),
+            AspSeek::NextKeyframe => self.seek_keyframe(true),
+            AspSeek::Start => self.seek(0),
+            AspSeek::End => self.seek(usize::MAX),
+            AspSeek::Frame(frame) => self.seek(frame),
+        }
+    }
+
+    /// Advance the playback clock by `dt` seconds, looping at the end
+    ///
+    /// Returns whether the frame changed.
+    pub fn advance(&mut self, dt: f64) -> Result<bool, AspError> {
+        let last = self.recording.frames.len() - 1;
+        let duration = self.recording.duration();
+        let mut clock = self.clock + dt.max(0.0);
+        if self.frame == last && clock > duration {
+            // Hold the last frame for one frame time, then loop
+            let hold = duration / last.max(1) as f64;
+            if clock - duration < hold {
+                self.clock = clock;
+                return Ok(false);
+            }
+            clock = 0.0;
+        }
+
+        let target = self.recording.frame_at(clock);
+        let changed = target != self.frame;
+        if changed {
+            self.seek(target)?;
+        }
+        self.clock = clock;
+        Ok(changed)
+    }
+}
+
+/// Writes ASP packets with consecutive sequence numbers
+pub struct AspWriter<W: Write> {
+    out: W,
+    /// Sequence number of the next packet
+    sequence: u32,
+    /// Parameters of the last frame written, for delta encoding
+    params: Option<Vec<u8>>,
+}
+
+impl<W: Write> AspWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self { out, sequence: 0, params: None }
+    }
+
+    /// Start numbering at `sequence`
+    pub fn with_sequence(mut self, sequence: u32) -> Self {
+        self.sequence = sequence;
+        self
+    }
+
+    /// Sequence number the next packet will carry
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+
+    /// Emit an I-packet; returns its sequence number
+    pub fn write_keyframe(&mut self, keyframe: &KeyframeData) -> io::Result<u32> {
+        let sequence = self.write_packet(AspPacketType::IPacket, &keyframe.to_bytes())?;
+        self.params = Some(keyframe.params.clone());
+        Ok(sequence)
+    }
+
+    /// Emit a D-packet; its patches must fit the current parameters
+    pub fn write_delta(&mut self, delta: &DeltaData) -> io::Result<u32> {
+        let params = self
+            .params
+            .as_ref()
+            .ok_or_else(|| invalid_input(AspError::NoKeyframe(AspPacketType::DPacket)))?;
+        let params = delta.apply(params).map_err(invalid_input)?;
+        let sequence = self.write_packet(AspPacketType::DPacket, &delta.to_bytes())?;
+        self.params = Some(params);
+        Ok(sequence)
+    }
+
+    /// Emit a D-packet moving from the current parameters to `params`
+    ///
+    /// `motion_vectors` is a row-major `mv_width × mv_height` grid.
+    pub fn write_frame(
+        &mut self,
+        params: &[u8],
+        (mv_width, mv_height): (u16, u16),
+        motion_vectors: Vec<MotionVectorCompact>,
+    ) -> io::Result<u32> {
+        let current = self
+            .params
+            .as_ref()
+            .ok_or_else(|| invalid_input(AspError::NoKeyframe(AspPacketType::DPacket)))?;
+        let patches = ParamPatch::diff(current, params).ok_or_else(|| {
+            invalid_input(AspError::SizeMismatch { expected: current.len(), got: params.len() })
+        })?;
+        if motion_vectors.len() != mv_width as usize * mv_height as usize {
+            return Err(invalid_input(AspError::SizeMismatch {
+                expected: mv_width as usize * mv_height as usize,
+                got: motion_vectors.len(),
+            }));
+        }
+        self.write_delta(&DeltaData { patches, mv_width, mv_height, motion_vectors })
+    }
+
+    /// Emit a C-packet for the current frame
+    pub fn write_correction(&mut self, correction: &CorrectionData) -> io::Result<u32> {
+        if let Some(roi) = correction
+            .rois
+            .iter()
+            .find(|r| r.residuals.len() != r.width as usize * r.height as usize * 3)
+        {
+            return Err(invalid_input(AspError::SizeMismatch {
+                expected: roi.width as usize * roi.height as usize * 3,
+                got: roi.residuals.len(),
+            }));
+        }
+        self.write_packet(AspPacketType::CPacket, &correction.to_bytes())
+    }
+
+    /// Emit an S-packet
+    pub fn write_sync(&mut self, sync: SyncData) -> io::Result<u32> {
+        if sync.command == SyncCommand::Reset {
+            self.params = None;
+        }
+        self.write_packet(AspPacketType::SPacket, &sync.to_bytes())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    fn write_packet(&mut self, kind: AspPacketType, payload: &[u8]) -> io::Result<u32> {
+        let sequence = self.sequence;
+        self.out.write_all(&encode_packet(kind, sequence, payload))?;
+        self.sequence = sequence.wrapping_add(1);
+        Ok(sequence)
+    }
+}
+
+fn invalid_input(err: AspError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, err)
+}
+
+/// Stream fixtures shared by the ASP test modules
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::AspStreamState;
+    use crate::decoder::alice::PerlinPayload;
+    use crate::decoder::ProceduralContent;
+
+    /// Perlin parameter block; the scale is at bytes 8..12
+    pub fn perlin_params(scale: f32) -> Vec<u8> {
+        PerlinPayload { seed: 7, scale, octaves: 4, persistence: 0.5, lacunarity: 2.0 }.to_bytes()
+    }
+
+    /// Scale of the current (Perlin) frame
+    pub fn scale_of(state: &AspStreamState) -> f32 {
+        match state.content() {
+            Some(ProceduralContent::Perlin { scale, .. }) => scale,
+            other => panic!("expected Perlin content, got {:?}", other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::{perlin_params, scale_of};
+    use super::*;
+
+    fn perlin_keyframe(scale: f32) -> Vec<u8> {
+        KeyframeData {
+            width: 64,
+            height: 32,
+            fps: 30.0,
+            content_type: AliceContentType::Perlin,
+            params: perlin_params(scale),
+        }
+        .to_bytes()
+    }
+
+    #[test]
+    fn test_keyframe_delta_correction() {
+        let mut state = AspStreamState::new();
+        assert!(state.content().is_none());
+
+        let kind = state.process_packet(&encode_packet(AspPacketType::IPacket, 1, &perlin_keyframe(2.0))).unwrap();
+        assert_eq!(kind, AspPacketType::IPacket);
+        assert_eq!(scale_of(&state), 2.0);
+
+        // Patch the scale field (bytes 8..12 of the Perlin block)
+        let delta = DeltaData {
+            patches: vec![ParamPatch { offset: 8, bytes: 3.5f32.to_le_bytes().to_vec() }],
+            mv_width: 2,
+            mv_height: 1,
+            motion_vectors: vec![MotionVectorCompact { dx: 1, dy: -2 }, MotionVectorCompact { dx: 0, dy: 3 }],
+        };
+        assert_eq!(DeltaData::parse(&delta.to_bytes()).unwrap(), delta);
+        state.process_packet(&encode_packet(AspPacketType::DPacket, 2, &delta.to_bytes())).unwrap();
+        assert_eq!(scale_of(&state), 3.5);
+        assert_eq!(state.motion_grid, (2, 1));
+        assert_eq!(state.motion_vectors[0], MotionVectorCompact { dx: 1, dy: -2 });
+
+        let correction = CorrectionData {
+            rois: vec![AspRoi { x: 4, y: 5, width: 2, height: 1, residuals: vec![1, -1, 2, -2, 3, -3] }],
+        };
+        assert_eq!(CorrectionData::parse(&correction.to_bytes()).unwrap(), correction);
+        state.process_packet(&encode_packet(AspPacketType::CPacket, 3, &correction.to_bytes())).unwrap();
+        assert_eq!(state.corrections, correction.rois);
+
+        let end = SyncData { command: SyncCommand::EndOfStream, value: 0 };
+        state.process_packet(&encode_packet(AspPacketType::SPacket, 4, &end.to_bytes())).unwrap();
+        assert!(state.ended);
+        assert_eq!((state.sequence, state.packets, state.frames_since_keyframe), (4, 4, 1));
+    }
+
+    #[test]
+    fn test_correction_image() {
+        let mut state = AspStreamState::new();
+        state.process_packet(&encode_packet(AspPacketType::IPacket, 1, &perlin_keyframe(2.0))).unwrap();
+        assert!(state.correction_images().is_empty());
+
+        let correction = CorrectionData {
+            rois: vec![
+                AspRoi { x: 4, y: 5, width: 2, height: 1, residuals: vec![1, -1, 2, -2, 3, -3] },
+                // Clipped to the bottom-right pixel of the 64x32 frame
+                AspRoi { x: 63, y: 31, width: 2, height: 2, residuals: (0..12).collect() },
+                // Entirely outside
+                AspRoi { x: 64, y: 0, width: 1, height: 1, residuals: vec![9; 3] },
+            ],
+        };
+        state.process_packet(&encode_packet(AspPacketType::CPacket, 2, &correction.to_bytes())).unwrap();
+        let roi = CorrectionImage::ROI;
+        assert_eq!(
+            state.correction_images(),
+            [
+                CorrectionImage { x: 4, y: 5, width: 2, height: 1, texels: vec![1, -1, 2, roi, -2, 3, -3, roi] },
+                CorrectionImage { x: 63, y: 31, width: 1, height: 1, texels: vec![0, 1, 2, roi] },
+            ]
+        );
+
+        // The next frame clears them
+        let delta = DeltaData::default();
+        state.process_packet(&encode_packet(AspPacketType::DPacket, 3, &delta.to_bytes())).unwrap();
+        assert!(state.correction_images().is_empty());
+    }
+
+    #[test]
+    fn test_packet_errors() {
+        let mut state = AspStreamState::new();
+        let key = encode_packet(AspPacketType::IPacket, 5, &perlin_keyframe(1.0));
+
+        let mut bad = key.clone();
+        bad[3] = 2;
+        assert!(matches!(state.process_packet(&bad), Err(AspError::BadMagic(_))));
+        assert!(matches!(
+            state.process_packet(&key[..key.len() - 1]),
+            Err(AspError::SizeMismatch { .. })
+        ));
+        assert!(matches!(state.process_packet(&key[..10]), Err(AspError::Truncated { .. })));
+        let delta = DeltaData::default().to_bytes();
+        assert!(matches!(
+            state.process_packet(&encode_packet(AspPacketType::DPacket, 6, &delta)),
+            Err(AspError::NoKeyframe(AspPacketType::DPacket))
+        ));
+        assert_eq!(state.packets, 0);
+
+        state.process_packet(&key).unwrap();
+        assert!(matches!(
+            state.process_packet(&encode_packet(AspPacketType::DPacket, 5, &delta)),
+            Err(AspError::OutOfOrder { last: 5, got: 5 })
+        ));
+
+        // Rejected deltas leave the current frame untouched
+        let past_end = DeltaData { patches: vec![ParamPatch { offset: 22, bytes: vec![0; 4] }], ..Default::default() };
+        assert!(matches!(
+            state.process_packet(&encode_packet(AspPacketType::DPacket, 6, &past_end.to_bytes())),
+            Err(AspError::InvalidField { field: "patch offset", .. })
+        ));
+        assert_eq!((scale_of(&state), state.sequence), (1.0, 5));
+    }
+
+    #[test]
+    fn test_sequence_wraparound() {
+        assert!(sequence_after(1, 0));
+        assert!(sequence_after(0, u32::MAX));
+        assert!(sequence_after(5, u32::MAX - 5));
+        assert!(!sequence_after(u32::MAX, 0));
+        assert!(!sequence_after(7, 7));
+
+        let mut state = AspStreamState::new();
+        state.process_packet(&encode_packet(AspPacketType::IPacket, u32::MAX, &perlin_keyframe(1.0))).unwrap();
+        let delta = DeltaData::default().to_bytes();
+        state.process_packet(&encode_packet(AspPacketType::DPacket, 0, &delta)).unwrap();
+        assert!(matches!(
+            state.process_packet(&encode_packet(AspPacketType::DPacket, u32::MAX, &delta)),
+            Err(AspError::OutOfOrder { last: 0, got: u32::MAX })
+        ));
+
+        let keyframe = KeyframeData::parse(&perlin_keyframe(1.0)).unwrap();
+        let mut writer = AspWriter::new(Vec::new()).with_sequence(u32::MAX - 2);
+        writer.write_keyframe(&keyframe).unwrap();
+        for scale in [2.0f32, 3.0, 4.0, 5.0] {
+            writer.write_frame(&perlin_params(scale), (0, 0), Vec::new()).unwrap();
+        }
+        assert_eq!(writer.sequence(), 2);
+        let recording = AspRecording::index(writer.into_inner()).unwrap();
+        assert_eq!(recording.frames().iter().map(|f| f.sequence).collect::<Vec<_>>(), [u32::MAX - 2, u32::MAX - 1, u32::MAX, 0, 1]);
+        assert_eq!(scale_of(&recording.decode(4).unwrap()), 5.0);
+    }
+
+    #[test]
+    fn test_keyframe_dimensions() {
+        let keyframe = KeyframeData::parse(&perlin_keyframe(1.0)).unwrap();
+        for (width, height) in [(0, 32), (64, 0), (65535, 65535), (KeyframeData::MAX_DIMENSION + 1, 1)] {
+            let bytes = KeyframeData { width, height, ..keyframe.clone() }.to_bytes();
+            assert!(matches!(KeyframeData::parse(&bytes), Err(AspError::InvalidField { .. })), "{}x{}", width, height);
+        }
+        let largest = KeyframeData { width: KeyframeData::MAX_DIMENSION, height: 1, ..keyframe };
+        assert!(KeyframeData::parse(&largest.to_bytes()).is_ok());
+
+        // A hostile size is rejected before any frame buffer is allocated
+        let mut state = AspStreamState::new();
+        let huge = KeyframeData { width: 65535, height: 65535, ..largest };
+        assert!(state.process_packet(&encode_packet(AspPacketType::IPacket, 1, &huge.to_bytes())).is_err());
+        assert!(state.keyframe.is_none());
+    }
+
+    #[test]
+    fn test_split_stream() {
+        let mut stream = encode_packet(AspPacketType::IPacket, 0, &perlin_keyframe(1.0));
+        stream.extend(encode_packet(AspPacketType::SPacket, 1, &SyncData { command: SyncCommand::Heartbeat, value: 33 }.to_bytes()));
+        let parts: Vec<_> = packets(&stream).collect::<Result<_, _>>().unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(AspHeader::parse(parts[1]).map(|h| h.sequence).unwrap(), 1);
+
+        stream.pop();
+        assert!(packets(&stream).last().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_writer_roundtrip() {
+        let keyframe = KeyframeData::parse(&perlin_keyframe(1.0)).unwrap();
+        let mut writer = AspWriter::new(Vec::new()).with_sequence(10);
+        assert!(writer.write_frame(&perlin_params(2.0), (0, 0), Vec::new()).is_err());
+
+        writer.write_keyframe(&keyframe).unwrap();
+        let mv = vec![MotionVectorCompact { dx: -1, dy: 1 }; 4];
+        for scale in [1.5f32, 2.0, 2.5] {
+            writer.write_frame(&perlin_params(scale), (2, 2), mv.clone()).unwrap();
+        }
+        writer
+            .write_correction(&CorrectionData {
+                rois: vec![AspRoi { x: 0, y: 0, width: 1, height: 1, residuals: vec![5, 0, -5] }],
+            })
+            .unwrap();
+        writer.write_sync(SyncData { command: SyncCommand::EndOfStream, value: 0 }).unwrap();
+        assert_eq!(writer.sequence(), 16);
+        assert!(writer.write_frame(&perlin_params(1.0)[..8], (0, 0), Vec::new()).is_err());
+
+        let stream = writer.into_inner();
+        let mut state = AspStreamState::new();
+        for packet in packets(&stream) {
+            state.process_packet(packet.unwrap()).unwrap();
+        }
+        assert_eq!(scale_of(&state), 2.5);
+        assert_eq!((state.sequence, state.frames_since_keyframe, state.motion_grid), (15, 3, (2, 2)));
+        assert_eq!(state.corrections.len(), 1);
+        assert!(state.ended);
+    }
+
+    #[test]
+    fn test_param_diff() {
+        let old = [0u8, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+        let mut new = old;
+        new[1] = 9;
+        new[3] = 9; // within 4 bytes of the previous change: merged
+        new[10] = 9;
+        let patches = ParamPatch::diff(&old, &new).unwrap();
+        assert_eq!(
+            patches,
+            vec![ParamPatch { offset: 1, bytes: vec![9, 2, 9] }, ParamPatch { offset: 10, bytes: vec![9] }]
+        );
+        assert!(ParamPatch::diff(&old, &old).unwrap().is_empty());
+        assert!(ParamPatch::diff(&old, &old[..4]).is_none());
+    }
+
+    /// Two GOPs of four frames each, scale = frame index + 1
+    fn recorded_stream() -> Vec<u8> {
+        let keyframe = KeyframeData::parse(&perlin_keyframe(1.0)).unwrap();
+        let mut writer = AspWriter::new(Vec::new());
+        for frame in 0..8 {
+            if frame % 4 == 0 {
+                let keyframe = KeyframeData { params: perlin_params(frame as f32 + 1.0), ..keyframe.clone() };
+                writer.write_keyframe(&keyframe).unwrap();
+            } else {
+                writer.write_frame(&perlin_params(frame as f32 + 1.0), (0, 0), Vec::new()).unwrap();
+            }
+            writer.write_sync(SyncData { command: SyncCommand::Heartbeat, value: frame }).unwrap();
+        }
+        writer.into_inner()
+    }
+
+    #[test]
+    fn test_recording_index() {
+        let recording = AspRecording::index(recorded_stream()).unwrap();
+        assert_eq!(recording.frame_count(), 8);
+        assert_eq!(recording.keyframes(), &[0, 4]);
+        assert_eq!(recording.frames()[6].keyframe, 4);
+        assert_eq!(recording.frames()[6].sequence, 12);
+        assert!((recording.duration() - 7.0 / 30.0).abs() < 1e-9);
+        assert_eq!(recording.frame_at(0.1), 3);
+        assert_eq!(recording.frame_at(10.0), 7);
+
+        for frame in 0..8 {
+            assert_eq!(scale_of(&recording.decode(frame).unwrap()), frame as f32 + 1.0);
+        }
+        // Decoding starts at the nearest keyframe
+        assert_eq!(recording.decode(6).unwrap().frames_since_keyframe, 2);
+
+        let mut broken = recorded_stream();
+        broken.extend(encode_packet(AspPacketType::SPacket, 3, &[0; 8]));
+        assert!(matches!(AspRecording::index(broken), Err(AspError::OutOfOrder { last: 15, got: 3 })));
+    }
+
+    #[test]
+    fn test_recording_truncated_tail() {
+        // Cut through the final heartbeat (24 bytes) and into frame 7
+        let mut cut = recorded_stream();
+        cut.truncate(cut.len() - 30);
+        let recording = AspRecording::index(cut).unwrap();
+        assert_eq!(recording.frame_count(), 7);
+        assert_eq!(scale_of(&recording.decode(6).unwrap()), 7.0);
+
+        // A partial header is skipped the same way
+        let mut partial = recorded_stream();
+        partial.extend_from_slice(&AspHeader::MAGIC);
+        assert_eq!(AspRecording::index(partial).unwrap().frame_count(), 8);
+
+        let mut bad_magic = recorded_stream();
+        bad_magic.extend_from_slice(&[0; AspHeader::SIZE]);
+        assert!(matches!(AspRecording::index(bad_magic), Err(AspError::BadMagic(_))));
+    }
+
+    #[test]
+    fn test_player_seek_and_advance() {
+        let mut player = AspPlayer::new(AspRecording::index(recorded_stream()).unwrap()).unwrap();
+        assert_eq!(scale_of(player.state()), 1.0);
+
+        player.seek_to(AspSeek::Step(1)).unwrap();
+        player.seek_to(AspSeek::Step(1)).unwrap();
+        assert_eq!((player.frame(), scale_of(player.state())), (2, 3.0));
+        player.seek_to(AspSeek::NextKeyframe).unwrap();
+        assert_eq!(player.frame(), 4);
+        player.seek_to(AspSeek::Step(-1)).unwrap();
+        assert_eq!((player.frame(), scale_of(player.state())), (3, 4.0));
+        player.seek_to(AspSeek::PreviousKeyframe).unwrap();
+        assert_eq!(player.frame(), 0);
+        player.seek_to(AspSeek::Step(-5)).unwrap();
+        assert_eq!(player.frame(), 0);
+        player.seek_to(AspSeek::End).unwrap();
+        assert_eq!((player.frame(), scale_of(player.state())), (7, 8.0));
+
+        // Hold the last frame, then loop back to the start
+        assert!(!player.advance(0.01).unwrap());
+        assert!(player.advance(0.05).unwrap());
+        assert_eq!(player.frame(), 0);
+        assert!(player.advance(2.5 / 30.0).unwrap());
+        assert_eq!((player.frame(), scale_of(player.state())), (2, 3.0));
+        assert_eq!(player.current().time, 2.0 / 30.0);
+    }
+}
@@ -0,0 +1,625 @@
+//! Live ASP reception over TCP and UDP
+//!
+//! [`AspListener`] binds the same local port for both transports and feeds
+//! every received packet into one [`AspStreamState`] from background
+//! threads. The render thread picks up the latest decoded frame with
+//! [`AspListener::take_frame`].
+//!
+//! - TCP: a byte stream of packets, one producer at a time. Each new
+//!   connection starts from a fresh stream state.
+//! - UDP: one or more whole packets per datagram, which may be lost or
+//!   arrive out of order; [`AspReceiver`] restores the order and recovers
+//!   from losses.
+
+use super::asp::{packets, sequence_after, AspError, AspHeader, AspPacketType, AspStreamState};
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// How often blocked network threads check for shutdown
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Largest accepted packet; anything bigger is treated as a corrupt stream
+const MAX_PACKET_SIZE: usize = 16 << 20;
+
+/// Largest UDP payload
+const MAX_DATAGRAM: usize = 65_507;
+
+/// Counters of a live stream
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AspLiveStats {
+    /// Bytes received on both transports
+    pub bytes: u64,
+    /// Packets decoded
+    pub packets: u64,
+    /// Frames (I- and D-packets) decoded
+    pub frames: u64,
+    /// Packets rejected by the decoder or framing errors
+    pub errors: u64,
+    /// TCP connections accepted
+    pub connections: u64,
+    /// Sequence numbers given up on as lost
+    pub dropped: u64,
+    /// Packets that arrived after a later one and were put back in order
+    pub reordered: u64,
+    /// Times decoding stopped until the next keyframe
+    pub resyncs: u64,
+}
+
+/// Puts received packets back in sequence order before decoding them
+///
+/// Packets that arrive early wait in a small buffer for the gap before them
+/// to fill. Once more than [`REORDER_DEPTH`](Self::REORDER_DEPTH) are
+/// waiting, the missing sequence numbers are counted as dropped. A lost
+/// packet may have been a delta, so after a gap (or a frame that fails to
+/// decode) D- and C-packets are skipped until the next I-packet.
+pub struct AspReceiver {
+    state: AspStreamState,
+    stats: AspLiveStats,
+    /// Sequence number expected next (None before the first packet)
+    next: Option<u32>,
+    /// Early packets by sequence number (all after `next`, possibly wrapped)
+    pending: HashMap<u32, Vec<u8>>,
+    /// Skipping deltas until the next I-packet
+    awaiting_keyframe: bool,
+}
+
+impl AspReceiver {
+    /// Early packets held before a gap is declared lost
+    pub const REORDER_DEPTH: usize = 8;
+
+    /// A packet this far behind the expected sequence means the producer
+    /// restarted
+    pub const RESTART_DISTANCE: u32 = 64;
+
+    pub fn new() -> Self {
+        Self {
+            state: AspStreamState::new(),
+            stats: AspLiveStats::default(),
+            next: None,
+            pending: HashMap::new(),
+            awaiting_keyframe: true,
+        }
+    }
+
+    /// Decoder state after the last delivered packet
+    pub fn state(&self) -> &AspStreamState {
+        &self.state
+    }
+
+    pub fn stats(&self) -> AspLiveStats {
+        self.stats
+    }
+
+    /// Start over for a new producer, keeping the counters
+    pub fn restart(&mut self) {
+        *self = Self { stats: self.stats, ..Self::new() };
+    }
+
+    /// Accept one received packet
+    ///
+    /// Returns whether any packet was decoded.
+    pub fn push(&mut self, packet: &[u8]) -> bool {
+        let header = match AspHeader::parse(packet) {
+            Ok(header) => header,
+            Err(e) => {
+                self.stats.errors += 1;
+                tracing::warn!("ASP live: bad packet: {}", e);
+                return false;
+            }
+        };
+        let sequence = header.sequence;
+
+        let next = match self.next {
+            Some(next) if sequence_after(next, sequence) && next.wrapping_sub(sequence) > Self::RESTART_DISTANCE => {
+                tracing::info!("ASP live: sequence restarted at {} (expected {})", sequence, next);
+                self.restart();
+                self.stats.resyncs += 1;
+                sequence
+            }
+            Some(next) if sequence_after(next, sequence) => {
+                tracing::debug!("ASP live: discarded late packet {} (expected {})", sequence, next);
+                return false;
+            }
+            Some(next) => next,
+            None => sequence,
+        };
+        self.next = Some(next);
+
+        if sequence_after(sequence, next) {
+            self.pending.insert(sequence, packet.to_vec());
+            if self.pending.len() <= Self::REORDER_DEPTH {
+                return false;
+            }
+            self.skip_gap();
+            return self.drain();
+        }
+
+        if !self.pending.is_empty() {
+            // A later packet overtook this one
+            self.stats.reordered += 1;
+        }
+        let decoded = self.deliver(packet);
+        self.drain() || decoded
+    }
+
+    /// Give up on the packets missing before the earliest pending one
+    fn skip_gap(&mut self) {
+        let Some(next) = self.next else {
+            return;
+        };
+        let Some((&first, packet)) = self.pending.iter().min_by_key(|(&sequence, _)| sequence.wrapping_sub(next)) else {
+            return;
+        };
+        self.stats.dropped += first.wrapping_sub(next) as u64;
+        self.next = Some(first);
+
+        let keyframe_follows = AspHeader::parse(packet).and_then(|h| h.kind()).ok() == Some(AspPacketType::IPacket);
+        if !keyframe_follows {
+            self.start_resync();
+        }
+    }
+
+    /// Deliver pending packets that are now in sequence
+    fn drain(&mut self) -> bool {
+        let mut decoded = false;
+        while let Some(packet) = self.next.and_then(|next| self.pending.remove(&next)) {
+            decoded |= self.deliver(&packet);
+        }
+        decoded
+    }
+
+    fn start_resync(&mut self) {
+        if !self.awaiting_keyframe {
+            self.awaiting_keyframe = true;
+            self.stats.resyncs += 1;
+        }
+    }
+
+    /// Decode the packet with the expected sequence number
+    fn deliver(&mut self, packet: &[u8]) -> bool {
+        self.next = self.next.map(|next| next.wrapping_add(1));
+
+        let kind = AspHeader::parse(packet).and_then(|h| h.kind());
+        if self.awaiting_keyframe && matches!(kind, Ok(AspPacketType::DPacket | AspPacketType::CPacket)) {
+            return false;
+        }
+        match self.state.process_packet(packet) {
+            Ok(kind) => {
+                self.stats.packets += 1;
+                if matches!(kind, AspPacketType::IPacket | AspPacketType::DPacket) {
+                    self.stats.frames += 1;
+                }
+                if kind == AspPacketType::IPacket {
+                    self.awaiting_keyframe = false;
+                }
+                true
+            }
+            Err(e) => {
+                self.stats.errors += 1;
+                tracing::warn!("ASP live: dropped packet: {}", e);
+                if matches!(kind, Ok(AspPacketType::IPacket | AspPacketType::DPacket)) {
+                    self.start_resync();
+                }
+                false
+            }
+        }
+    }
+}
+
+impl Default for AspReceiver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// State shared between the network threads and the render thread
+struct Shared {
+    receiver: AspReceiver,
+    /// A packet was decoded since the last `take_frame`
+    updated: bool,
+}
+
+impl Shared {
+    fn push(&mut self, packet: &[u8]) {
+        self.updated |= self.receiver.push(packet);
+    }
+}
+
+struct Live {
+    shared: Mutex<Shared>,
+    stop: AtomicBool,
+}
+
+impl Live {
+    fn lock(&self) -> MutexGuard<'_, Shared> {
+        // A panicking network thread leaves the state usable
+        self.shared.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+}
+
+/// Receives a live ASP stream in the background
+pub struct AspListener {
+    addr: SocketAddr,
+    live: Arc<Live>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl AspListener {
+    /// Listen for TCP connections and UDP datagrams on `addr`
+    ///
+    /// With port 0 the TCP port is chosen by the OS and UDP binds the same
+    /// one; see [`local_addr`](Self::local_addr).
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let tcp = TcpListener::bind(addr)?;
+        let addr = tcp.local_addr()?;
+        let udp = UdpSocket::bind(addr)?;
+        tcp.set_nonblocking(true)?;
+        udp.set_read_timeout(Some(POLL_INTERVAL))?;
+
+        let live = Arc::new(Live {
+            shared: Mutex::new(Shared { receiver: AspReceiver::new(), updated: false }),
+            stop: AtomicBool::new(false),
+        });
+        let tcp_live = live.clone();
+        let udp_live = live.clone();
+        let threads = vec![
+            thread::Builder::new().name("asp-tcp".into()).spawn(move || serve_tcp(tcp, &tcp_live))?,
+            thread::Builder::new().name("asp-udp".into()).spawn(move || serve_udp(udp, &udp_live))?,
+        ];
+        tracing::info!("ASP live: listening on {} (TCP + UDP)", addr);
+
+        Ok(Self { addr, live, threads })
+    }
+
+    // Needed to reach a listener bound to port 0; the viewer binds a fixed
+    // address.
+    #[allow(dead_code)]
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Decoder state after the latest packet, if anything arrived since the
+    /// last call
+    pub fn take_frame(&self) -> Option<AspStreamState> {
+        let mut shared = self.live.lock();
+        if !shared.updated {
+            return None;
+        }
+        shared.updated = false;
+        Some(shared.receiver.state().clone())
+    }
+
+    pub fn stats(&self) -> AspLiveStats {
+        self.live.lock().receiver.stats()
+    }
+}
+
+impl Drop for AspListener {
+    fn drop(&mut self) {
+        self.live.stop.store(true, Ordering::Relaxed);
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn serve_tcp(listener: TcpListener, live: &Live) {
+    while !live.stopped() {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                tracing::info!("ASP live: producer connected from {}", peer);
+                {
+                    let mut shared = live.lock();
+                    // A new producer numbers its packets from the start again
+                    shared.receiver.restart();
+                    shared.receiver.stats.connections += 1;
+                }
+                match receive_tcp(stream, live) {
+                    Ok(()) => tracing::info!("ASP live: producer {} disconnected", peer),
+                    Err(e) => {
+                        live.lock().receiver.stats.errors += 1;
+                        tracing::warn!("ASP live: connection from {} closed: {}", peer, e);
+                    }
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+            Err(e) => {
+                tracing::warn!("ASP live: accept failed: {}", e);
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+}
+
+/// Read packets from one connection until it closes or the listener stops
+fn receive_tcp(mut stream: TcpStream, live: &Live) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(POLL_INTERVAL))?;
+
+    let mut buf = Vec::new();
+    let mut chunk = vec![0u8; 64 * 1024];
+    while !live.stopped() {
+        let n = match stream.read(&mut chunk) {
+            Ok(0) => return Ok(()),
+            Ok(n) => n,
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => continue,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        buf.extend_from_slice(&chunk[..n]);
+
+        let mut shared = live.lock();
+        shared.receiver.stats.bytes += n as u64;
+        let consumed = split_packets(&buf, |packet| shared.push(packet))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        drop(shared);
+        buf.drain(..consumed);
+    }
+    Ok(())
+}
+
+/// Pass every complete packet at the start of `buf` to `f`
+///
+/// Returns the number of bytes consumed; a trailing partial packet is left
+/// for the next read. Fails if the stream cannot be framed any more.
+fn split_packets(buf: &[u8], mut f: impl FnMut(&[u8])) -> Result<usize, AspError> {
+    let mut start = 0;
+    while buf.len() - start >= AspHeader::SIZE {
+        let header = AspHeader::parse(&buf[start..])?;
+        let len = header.packet_len();
+        if len > MAX_PACKET_SIZE {
+            return Err(AspError::InvalidField { field: "payload size", value: header.payload_size as u64 });
+        }
+        if buf.len() - start < len {
+            break;
+        }
+        f(&buf[start..start + len]);
+        start += len;
+    }
+    Ok(start)
+}
+
+fn serve_udp(socket: UdpSocket, live: &Live) {
+    let mut datagram = vec![0u8; MAX_DATAGRAM];
+    while !live.stopped() {
+        let n = match socket.recv(&mut datagram) {
+            Ok(n) => n,
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => continue,
+            Err(e) => {
+                tracing::warn!("ASP live: UDP receive failed: {}", e);
+                thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+        };
+
+        let mut shared = live.lock();
+        shared.receiver.stats.bytes += n as u64;
+        for packet in packets(&datagram[..n]) {
+            match packet {
+                Ok(packet) => shared.push(packet),
+                Err(e) => {
+                    shared.receiver.stats.errors += 1;
+                    tracing::warn!("ASP live: bad datagram: {}", e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::alice::AliceContentType;
+    use crate::decoder::asp::test_support::{perlin_params, scale_of};
+    use crate::decoder::{encode_packet, AspWriter, DeltaData, KeyframeData, ParamPatch, SyncCommand, SyncData};
+    use std::io::Write;
+    use std::time::Instant;
+
+    /// Sends every write (one packet each from `AspWriter`) as a datagram
+    struct Datagrams(UdpSocket);
+
+    impl Write for Datagrams {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.send(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Stand-in producer: a keyframe, `frames - 1` deltas, end of stream
+    fn produce<W: Write>(out: W, frames: u32) -> W {
+        let mut writer = AspWriter::new(out);
+        let keyframe = KeyframeData {
+            width: 32,
+            height: 32,
+            fps: 60.0,
+            content_type: AliceContentType::Perlin,
+            params: perlin_params(1.0),
+        };
+        writer.write_keyframe(&keyframe).unwrap();
+        for frame in 1..frames {
+            writer.write_frame(&perlin_params(1.0 + frame as f32), (0, 0), Vec::new()).unwrap();
+        }
+        writer.write_sync(SyncData { command: SyncCommand::EndOfStream, value: 0 }).unwrap();
+        writer.flush().unwrap();
+        writer.into_inner()
+    }
+
+    /// Packets of `frames` frames with a keyframe every fourth, scale =
+    /// frame index + 1; packet `i` has sequence number `i`
+    fn gop_packets(frames: u32) -> Vec<Vec<u8>> {
+        gop_packets_from(0, frames)
+    }
+
+    /// Like [`gop_packets`], numbered from `first_sequence`
+    fn gop_packets_from(first_sequence: u32, frames: u32) -> Vec<Vec<u8>> {
+        let mut writer = AspWriter::new(Vec::new()).with_sequence(first_sequence);
+        for frame in 0..frames {
+            let params = perlin_params(frame as f32 + 1.0);
+            if frame % 4 == 0 {
+                let keyframe =
+                    KeyframeData { width: 32, height: 32, fps: 60.0, content_type: AliceContentType::Perlin, params };
+                writer.write_keyframe(&keyframe).unwrap();
+            } else {
+                writer.write_frame(&params, (0, 0), Vec::new()).unwrap();
+            }
+        }
+        packets(&writer.into_inner()).map(|p| p.unwrap().to_vec()).collect()
+    }
+
+    fn receive(packets: &[Vec<u8>], order: impl IntoIterator<Item = usize>) -> AspReceiver {
+        let mut receiver = AspReceiver::new();
+        for i in order {
+            receiver.push(&packets[i]);
+        }
+        receiver
+    }
+
+    /// Poll like the render loop until the stream has ended
+    fn wait_for_end(listener: &AspListener) -> AspStreamState {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut latest = None;
+        while Instant::now() < deadline {
+            if let Some(state) = listener.take_frame() {
+                let ended = state.ended;
+                latest = Some(state);
+                if ended {
+                    break;
+                }
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        let state = latest.expect("no frame received");
+        assert!(state.ended, "stream did not finish");
+        state
+    }
+
+    #[test]
+    fn test_tcp_stream() {
+        let listener = AspListener::bind("127.0.0.1:0").unwrap();
+        // Two producers in a row: the second restarts the sequence numbers
+        for frames in [5, 8] {
+            let stream = TcpStream::connect(listener.local_addr()).unwrap();
+            drop(produce(stream, frames));
+            let state = wait_for_end(&listener);
+            assert_eq!(scale_of(&state), frames as f32);
+            assert_eq!(state.sequence, frames);
+        }
+        let stats = listener.stats();
+        assert_eq!((stats.connections, stats.frames, stats.errors), (2, 13, 0));
+        assert!(listener.take_frame().is_none());
+    }
+
+    #[test]
+    fn test_udp_stream() {
+        let listener = AspListener::bind("127.0.0.1:0").unwrap();
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.connect(listener.local_addr()).unwrap();
+        let Datagrams(socket) = produce(Datagrams(socket), 6);
+
+        let state = wait_for_end(&listener);
+        assert_eq!(scale_of(&state), 6.0);
+        assert_eq!(listener.stats().packets, 7);
+
+        // Garbage is counted, not fatal
+        socket.send(b"not an ASP packet").unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while listener.stats().errors == 0 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(listener.stats().errors, 1);
+    }
+
+    #[test]
+    fn test_split_packets() {
+        let mut stream = produce(Vec::new(), 3);
+        let total = stream.len();
+        let mut count = 0;
+        assert_eq!(split_packets(&stream[..total - 1], |_| count += 1).unwrap(), total - 24);
+        assert_eq!(count, 3);
+        assert_eq!(split_packets(&stream[..10], |_| count += 1).unwrap(), 0);
+
+        stream[0] = b'X';
+        assert!(matches!(split_packets(&stream, |_| {}), Err(AspError::BadMagic(_))));
+    }
+
+    #[test]
+    fn test_receiver_reorder() {
+        let packets = gop_packets(8);
+        let receiver = receive(&packets, [0, 2, 1, 3, 4, 6, 5, 7]);
+        let stats = receiver.stats();
+        assert_eq!((stats.reordered, stats.dropped, stats.resyncs, stats.frames), (2, 0, 0, 8));
+        assert_eq!(scale_of(receiver.state()), 8.0);
+
+        // Duplicates and late packets are ignored
+        let mut receiver = receiver;
+        assert!(!receiver.push(&packets[5]));
+        assert_eq!(receiver.stats().frames, 8);
+    }
+
+    #[test]
+    fn test_receiver_loss_resync() {
+        let packets = gop_packets(16);
+        let mut receiver = AspReceiver::new();
+        for i in (0..16).filter(|&i| i != 2) {
+            receiver.push(&packets[i]);
+            if i == 10 {
+                // Still waiting for the gap to fill
+                assert_eq!(scale_of(receiver.state()), 2.0);
+            }
+        }
+        // Delta 3 is skipped; decoding resumes at the keyframe 4
+        let stats = receiver.stats();
+        assert_eq!((stats.dropped, stats.resyncs, stats.frames, stats.errors), (1, 1, 14, 0));
+        assert_eq!(scale_of(receiver.state()), 16.0);
+
+        // Losing the packet just before a keyframe needs no resync
+        let receiver = receive(&packets, (0..16).filter(|&i| i != 3));
+        assert_eq!((receiver.stats().dropped, receiver.stats().resyncs), (1, 0));
+        assert_eq!(scale_of(receiver.state()), 16.0);
+    }
+
+    #[test]
+    fn test_receiver_sequence_wraparound() {
+        // Packet 3 is u32::MAX and packet 4 wraps to 0
+        let packets = gop_packets_from(u32::MAX - 3, 20);
+        let order = [0, 1, 2, 4, 3].into_iter().chain((5..20).filter(|&i| i != 9));
+        let receiver = receive(&packets, order);
+        let stats = receiver.stats();
+        assert_eq!((stats.reordered, stats.dropped, stats.resyncs, stats.frames), (1, 1, 1, 17));
+        assert_eq!(scale_of(receiver.state()), 20.0);
+        assert_eq!(receiver.state().sequence, 15);
+    }
+
+    #[test]
+    fn test_receiver_bad_delta_and_restart() {
+        let packets = gop_packets(80);
+        let bad_delta = DeltaData { patches: vec![ParamPatch { offset: 1000, bytes: vec![1] }], ..Default::default() };
+        let mut receiver = receive(&packets, 0..2);
+        receiver.push(&encode_packet(AspPacketType::DPacket, 2, &bad_delta.to_bytes()));
+        for packet in &packets[3..] {
+            receiver.push(packet);
+        }
+        let stats = receiver.stats();
+        assert_eq!((stats.errors, stats.resyncs, stats.frames), (1, 1, 78));
+        assert_eq!(scale_of(receiver.state()), 80.0);
+
+        // A producer starting over is followed instead of discarded as late
+        for packet in &packets[..6] {
+            receiver.push(packet);
+        }
+        assert_eq!(receiver.stats().resyncs, 2);
+        assert_eq!(scale_of(receiver.state()), 6.0);
+    }
+}
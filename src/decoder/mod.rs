@@ -8,6 +8,8 @@ pub mod alice;
 pub mod asdf;
 mod alz;
 mod asp;
+mod asp_live;
+mod video;
 
 // Re-export format modules so crate consumers can use types without full paths.
 // Some items are unused within this crate but form the public protocol API.
@@ -19,6 +21,10 @@ pub use alz::*;
 pub use asdf::*;
 #[allow(unused_imports)]
 pub use asp::*;
+#[allow(unused_imports)]
+pub use asp_live::*;
+#[allow(unused_imports)]
+pub use video::*;
 
 use anyhow::{Context, Result};
 use glam::DVec2;
@@ -27,9 +33,6 @@ use std::sync::Arc;
 use tokio::fs;
 
 /// Content type
-// Video variant is defined for completeness; actual video decoding is not yet
-// implemented but the variant is required for format compatibility.
-#[allow(dead_code)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ContentType {
     /// No content loaded
@@ -42,12 +45,11 @@ pub enum ContentType {
     AliceSdf,
     /// Standard image (fallback)
     Image,
-    /// Standard video (fallback)
+    /// Image sequence or animated GIF
     Video,
 }
 
 /// Decoded procedural content
-// Polynomial/SineWave/Fourier variants are defined for future format support.
 // Fields inside Perlin/Fractal/Raster are used by the shader pipeline indirectly.
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
@@ -60,19 +62,33 @@ pub enum ProceduralContent {
         persistence: f32,
         lacunarity: f32,
     },
-    /// Polynomial coefficients
+    /// Polynomial coefficients (ascending powers)
     Polynomial {
         coefficients: Vec<f64>,
+        /// Samples covered ([0, domain_end)); 0 = unbounded, plotted in view units
+        domain_end: u32,
+        /// Approximate (min, max) of the model over its domain
+        value_range: (f64, f64),
     },
-    /// Sine wave parameters
+    /// Sum of sine waves
     SineWave {
-        frequency: f32,
-        amplitude: f32,
-        phase: f32,
+        components: Vec<(f32, f32, f32)>, // (frequency, amplitude, phase)
+        offset: f32,
+    },
+    /// Piecewise model: (start sample, coefficients in ascending powers,
+    /// evaluated relative to the start) per segment
+    Segmented {
+        segments: Vec<(u32, Vec<f64>)>,
+        /// Samples covered ([0, domain_end))
+        domain_end: u32,
+        /// Approximate (min, max) of the model over its domain
+        value_range: (f64, f64),
     },
     /// Fourier series
     Fourier {
         coefficients: Vec<(usize, f32, f32)>, // (frequency, amplitude, phase)
+        /// Base period (x units per fundamental cycle)
+        period: f32,
     },
     /// Fractal parameters (Mandelbrot, Julia, etc.)
     Fractal {
@@ -82,6 +98,18 @@ pub enum ProceduralContent {
         center: DVec2,
         julia_c: Option<DVec2>,
     },
+    /// Voronoi cells
+    Voronoi {
+        seed: u64,
+        cell_density: f32,
+        metric: VoronoiMetric,
+        feature: VoronoiFeature,
+        jitter: f32,
+    },
+    /// Layers (blend op, weight, content) evaluated bottom to top
+    Composite {
+        layers: Vec<(BlendOp, f32, ProceduralContent)>,
+    },
     /// Raster image data (RGBA8) - Arc for zero-copy sharing
     Raster {
         width: u32,
@@ -90,6 +118,62 @@ pub enum ProceduralContent {
     },
 }
 
+/// Placement of a sampled series in the 2D view (view units, y up)
+///
+/// The full domain spans the view width at zoom 1 with sample 0 at the left
+/// edge; the value range is centred and fills 3/4 of the height.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlotFrame {
+    /// Samples covered ([0, domain_end))
+    pub domain_end: f64,
+    pub y_center: f64,
+    /// View units per data unit
+    pub y_scale: f64,
+}
+
+impl PlotFrame {
+    pub fn new(domain_end: u32, (min, max): (f64, f64)) -> Self {
+        Self {
+            domain_end: domain_end.max(1) as f64,
+            y_center: (min + max) * 0.5,
+            y_scale: 3.0 / (max - min).max(1e-6),
+        }
+    }
+
+    /// View x of sample position `x`
+    pub fn view_x(&self, x: f64) -> f64 {
+        x * 4.0 / self.domain_end - 2.0
+    }
+
+    /// Sample position at view x
+    pub fn sample_x(&self, view_x: f64) -> f64 {
+        (view_x + 2.0) * self.domain_end / 4.0
+    }
+
+    /// View y of value `v`
+    pub fn view_y(&self, v: f64) -> f64 {
+        (v - self.y_center) * self.y_scale
+    }
+
+    /// Value at view y
+    pub fn value(&self, view_y: f64) -> f64 {
+        view_y / self.y_scale + self.y_center
+    }
+}
+
+impl ProceduralContent {
+    /// Frame for content plotted against sample position, if any
+    pub fn plot_frame(&self) -> Option<PlotFrame> {
+        match self {
+            Self::Polynomial { domain_end, value_range, .. } if *domain_end > 0 => {
+                Some(PlotFrame::new(*domain_end, *value_range))
+            }
+            Self::Segmented { domain_end, value_range, .. } => Some(PlotFrame::new(*domain_end, *value_range)),
+            _ => None,
+        }
+    }
+}
+
 /// Fractal types
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FractalType {
@@ -111,6 +195,19 @@ pub struct Decoder {
     alice_file: Option<alice::AliceFile>,
     /// Loaded SDF content (for 3D visualization)
     sdf_content: Option<asdf::SdfContent>,
+    /// Playback of a loaded .asp recording
+    asp_player: Option<asp::AspPlayer>,
+    /// Playback of a loaded image sequence or animated GIF
+    video_player: Option<video::VideoPlayer>,
+    /// Live stream being received (replaces any loaded file)
+    asp_listener: Option<AspListener>,
+    /// Latest frame of the live stream
+    live_state: Option<asp::AspStreamState>,
+    /// Image the loaded .alice/.alz content replaces (`ProceduralContent::Raster`)
+    reference: Option<ProceduralContent>,
+    reference_path: Option<String>,
+    /// Bumped whenever `content` or `alice_file` changes
+    revision: u64,
 }
 
 // Several Decoder methods are public API for library consumers even if not
@@ -126,9 +223,27 @@ impl Decoder {
             compressed_size: 0,
             alice_file: None,
             sdf_content: None,
+            asp_player: None,
+            video_player: None,
+            asp_listener: None,
+            live_state: None,
+            reference: None,
+            reference_path: None,
+            revision: 0,
         }
     }
 
+    /// Counter that changes whenever [`content`](Self::content) or
+    /// [`alice_file`](Self::alice_file) does
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn set_content(&mut self, content: Option<ProceduralContent>) {
+        self.content = content;
+        self.revision += 1;
+    }
+
     /// Get loaded SDF content (if available)
     pub fn sdf_content(&self) -> Option<&asdf::SdfContent> {
         self.sdf_content.as_ref()
@@ -139,6 +254,189 @@ impl Decoder {
         self.alice_file.as_ref()
     }
 
+    /// Playback of the loaded .asp recording (if any)
+    pub fn asp_player(&self) -> Option<&asp::AspPlayer> {
+        self.asp_player.as_ref()
+    }
+
+    /// Playback of the loaded image sequence or animated GIF (if any)
+    pub fn video_player(&self) -> Option<&video::VideoPlayer> {
+        self.video_player.as_ref()
+    }
+
+    /// Whether the loaded content plays over time (.asp recording or video)
+    pub fn has_playback(&self) -> bool {
+        self.asp_player.is_some() || self.video_player.is_some()
+    }
+
+    /// Receive a live ASP stream on `addr` (TCP and UDP) instead of a file
+    pub fn listen_asp(&mut self, addr: &str) -> Result<()> {
+        let listener = AspListener::bind(addr).with_context(|| format!("Failed to listen on {}", addr))?;
+
+        self.file_path = None;
+        self.alice_file = None;
+        self.sdf_content = None;
+        self.asp_player = None;
+        self.video_player = None;
+        self.live_state = None;
+        self.clear_reference();
+        self.content_type = ContentType::AspStream;
+        self.set_content(None);
+        self.original_size = 0;
+        self.compressed_size = 0;
+        self.asp_listener = Some(listener);
+        Ok(())
+    }
+
+    /// Live stream being received (if any)
+    pub fn asp_listener(&self) -> Option<&AspListener> {
+        self.asp_listener.as_ref()
+    }
+
+    /// Show the latest frame of the live stream
+    ///
+    /// Returns whether a new frame arrived since the last poll.
+    pub fn poll_stream(&mut self) -> bool {
+        let Some(listener) = &self.asp_listener else {
+            return false;
+        };
+        let Some(state) = listener.take_frame() else {
+            return false;
+        };
+        let stats = listener.stats();
+        if let Some(keyframe) = &state.keyframe {
+            self.original_size = keyframe.width as u64 * keyframe.height as u64 * 4 * stats.frames;
+        }
+        self.compressed_size = stats.bytes;
+        if let Some(content) = state.content() {
+            self.set_content(Some(content));
+        }
+        self.live_state = Some(state);
+        true
+    }
+
+    /// Decoder state of the .asp frame being shown (recording or live stream)
+    pub fn asp_state(&self) -> Option<&asp::AspStreamState> {
+        self.asp_player.as_ref().map(|player| player.state()).or(self.live_state.as_ref())
+    }
+
+    /// Advance .asp or video playback by `dt` seconds
+    pub fn advance_playback(&mut self, dt: f64) {
+        if let Some(player) = &mut self.video_player {
+            match player.advance(dt) {
+                Ok(true) => {
+                    let content = player.content().clone();
+                    self.set_content(Some(content));
+                }
+                Ok(false) => {}
+                Err(e) => tracing::warn!("Video playback: {:#}", e),
+            }
+            return;
+        }
+        let Some(player) = &mut self.asp_player else {
+            return;
+        };
+        match player.advance(dt) {
+            Ok(true) => {
+                let content = player.state().content();
+                self.set_content(content);
+            }
+            Ok(false) => {}
+            Err(e) => tracing::warn!("ASP playback: {}", e),
+        }
+    }
+
+    /// Show a video frame that finished decoding after a seek while paused
+    pub fn poll_playback(&mut self) {
+        let Some(player) = &mut self.video_player else {
+            return;
+        };
+        match player.poll() {
+            Ok(true) => {
+                let content = player.content().clone();
+                self.set_content(Some(content));
+            }
+            Ok(false) => {}
+            Err(e) => tracing::warn!("Video playback: {:#}", e),
+        }
+    }
+
+    /// Move .asp or video playback to another frame
+    ///
+    /// Video frames are all keyframes, so keyframe jumps step one frame.
+    pub fn seek_playback(&mut self, target: asp::AspSeek) {
+        if let Some(player) = &mut self.video_player {
+            let result = match target {
+                asp::AspSeek::Step(delta) => player.step(delta),
+                asp::AspSeek::PreviousKeyframe => player.step(-1),
+                asp::AspSeek::NextKeyframe => player.step(1),
+                asp::AspSeek::Start => player.seek(0),
+                asp::AspSeek::End => player.seek(usize::MAX),
+                asp::AspSeek::Frame(frame) => player.seek(frame),
+            };
+            match result {
+                Ok(true) => {
+                    let content = player.content().clone();
+                    self.set_content(Some(content));
+                }
+                Ok(false) => {}
+                Err(e) => tracing::warn!("Video seek: {:#}", e),
+            }
+            return;
+        }
+        let Some(player) = &mut self.asp_player else {
+            return;
+        };
+        match player.seek_to(target) {
+            Ok(()) => {
+                let content = player.state().content();
+                self.set_content(content);
+            }
+            Err(e) => tracing::warn!("ASP seek: {}", e),
+        }
+    }
+
+    /// Switch video playback to the next faster (or slower) frame rate
+    pub fn step_video_fps(&mut self, faster: bool) {
+        if let Some(player) = &mut self.video_player {
+            player.step_fps(faster);
+            tracing::info!("Video playback: {} fps", player.fps());
+        }
+    }
+
+    /// Residual statistics of the loaded model, if it was fitted to data
+    pub fn fit_stats(&self) -> Option<alice::FitStats> {
+        self.alice_file.as_ref().and_then(|f| alice::FitStats::from_metadata(&f.metadata))
+    }
+
+    /// Pair the loaded .alice/.alz content with the image it replaces
+    ///
+    /// The pairing lasts until another file is loaded.
+    pub fn pair_reference(&mut self, path: &str) -> Result<()> {
+        if self.content_type != ContentType::AliceZip {
+            anyhow::bail!("Load a .alice or .alz file before pairing a reference image");
+        }
+        let (reference, _, _) = decode_image(Path::new(path))?;
+        self.reference = Some(reference);
+        self.reference_path = Some(path.to_string());
+        Ok(())
+    }
+
+    /// Reference image paired with the loaded content (always `ProceduralContent::Raster`)
+    pub fn reference(&self) -> Option<&ProceduralContent> {
+        self.reference.as_ref()
+    }
+
+    /// Path of the paired reference image
+    pub fn reference_path(&self) -> Option<&str> {
+        self.reference_path.as_deref()
+    }
+
+    fn clear_reference(&mut self) {
+        self.reference = None;
+        self.reference_path = None;
+    }
+
     /// Load content from file path (synchronous wrapper for compatibility)
     /// For async loading, use load_async() instead
     pub fn load(&mut self, path: &str) -> Result<()> {
@@ -162,7 +460,13 @@ impl Decoder {
 
         self.file_path = Some(path.to_string());
         self.alice_file = None;
+        self.revision += 1;
         self.sdf_content = None;
+        self.asp_player = None;
+        self.video_player = None;
+        self.asp_listener = None;
+        self.live_state = None;
+        self.clear_reference();
 
         let sdf_content = asdf::SdfContent::load(p)?;
 
@@ -179,7 +483,7 @@ impl Decoder {
 
         self.sdf_content = Some(sdf_content);
         self.content_type = ContentType::AliceSdf;
-        self.content = None;
+        self.set_content(None);
         self.original_size = estimated_original;
         self.compressed_size = file_size;
 
@@ -198,7 +502,13 @@ impl Decoder {
 
         self.file_path = Some(path.to_string_lossy().to_string());
         self.alice_file = None; // Reset
+        self.revision += 1;
         self.sdf_content = None; // Reset
+        self.asp_player = None; // Reset
+        self.video_player = None;
+        self.asp_listener = None;
+        self.live_state = None;
+        self.clear_reference();
 
         // Check for SDF files first (compound extension .asdf.json, binary .asdf, or plain .json)
         let path_str = path.to_string_lossy();
@@ -206,27 +516,44 @@ impl Decoder {
             return self.load_asdf_async(path_buf).await;
         }
 
+        // Numbered image sequences (a directory or a `%04d` pattern)
+        if video::is_sequence(path) {
+            let (content, o_size, c_size, player) = Self::load_sequence_async(path_buf).await?;
+            self.video_player = Some(player);
+            self.set_content(Some(content));
+            self.content_type = ContentType::Video;
+            self.original_size = o_size;
+            self.compressed_size = c_size;
+            return Ok(());
+        }
+
         let (content, c_type, o_size, c_size, alice_file) = match extension.as_str() {
             "alz" | "alice" => Self::load_alice_async(path_buf).await?,
             "asp" => {
-                let (c, t, o, s) = Self::load_asp_async(path_buf).await?;
+                let (c, t, o, s, player) = Self::load_asp_async(path_buf).await?;
+                self.asp_player = Some(player);
+                (c, t, o, s, None)
+            }
+            "gif" => {
+                let (c, t, o, s, player) = Self::load_gif_async(path_buf).await?;
+                self.video_player = player;
                 (c, t, o, s, None)
             }
-            "png" | "jpg" | "jpeg" | "bmp" | "gif" => {
+            "png" | "jpg" | "jpeg" | "bmp" => {
                 let (c, t, o, s) = Self::load_image_async(path_buf).await?;
                 (c, t, o, s, None)
             }
             "mp4" | "webm" | "avi" | "mov" => {
-                anyhow::bail!("Video playback not yet implemented");
+                anyhow::bail!("Video files are not supported; load an image sequence or animated GIF instead");
             }
             _ => anyhow::bail!("Unknown file format: {}", extension),
         };
 
-        self.content = Some(content);
+        self.alice_file = alice_file;
+        self.set_content(Some(content));
         self.content_type = c_type;
         self.original_size = o_size;
         self.compressed_size = c_size;
-        self.alice_file = alice_file;
 
         Ok(())
     }
@@ -259,7 +586,7 @@ impl Decoder {
 
         self.sdf_content = Some(sdf_content);
         self.content_type = ContentType::AliceSdf;
-        self.content = None; // SDF uses separate content
+        self.set_content(None); // SDF uses separate content
         self.original_size = estimated_original;
         self.compressed_size = file_size;
 
@@ -273,93 +600,85 @@ impl Decoder {
         // Read file contents
         let data = fs::read(&path).await.context("Failed to read file")?;
 
-        // Try to parse as .alice format first
-        if data.len() >= 5 && &data[0..5] == b"ALICE" {
-            let alice_file = alice::AliceFile::parse(&data)?;
-            tracing::info!("Parsed ALICE file: {}", alice_file.equation_string());
-
-            let content = match &alice_file.payload {
-                alice::AlicePayload::Linear(p) => {
-                    // Convert to Perlin visualization with slope as scale
-                    ProceduralContent::Perlin {
-                        seed: p.slope_q16 as u64,
-                        scale: p.slope_f32().abs() * 10.0 + 1.0,
-                        octaves: 6,
-                        persistence: 0.5,
-                        lacunarity: 2.0,
-                    }
+        // .alice and .alz share the "ALICE" magic: try the .alice layout
+        // first, then the 24-byte ALZ header
+        let alice_err = match alice::AliceFile::parse(&data) {
+            Ok(alice_file) => {
+                tracing::info!("Parsed ALICE file: {}", alice_file.equation_string());
+                if let alice::ChecksumStatus::Mismatch { stored, computed } = alice_file.header.checksum_status {
+                    tracing::warn!("ALICE payload checksum mismatch: stored {:08x}, computed {:08x}", stored, computed);
+                }
+                if let Err(e) = alice_file.payload.validate() {
+                    tracing::warn!("ALICE payload only partly renders: {}", e);
                 }
-                alice::AlicePayload::Perlin(p) => ProceduralContent::Perlin {
-                    seed: p.seed,
-                    scale: p.scale,
-                    octaves: p.octaves,
-                    persistence: p.persistence,
-                    lacunarity: p.lacunarity,
-                },
-                alice::AlicePayload::Fractal(p) => ProceduralContent::Fractal {
-                    fractal_type: match p.fractal_type {
-                        0 => FractalType::Mandelbrot,
-                        1 => FractalType::Julia,
-                        2 => FractalType::BurningShip,
-                        3 => FractalType::Tricorn,
-                        _ => FractalType::Mandelbrot,
-                    },
-                    max_iterations: p.max_iterations,
-                    escape_radius: p.escape_radius,
-                    center: DVec2::new(p.center_x, p.center_y),
-                    julia_c: if p.fractal_type == 1 {
-                        Some(DVec2::new(p.julia_cx, p.julia_cy))
-                    } else {
-                        None
-                    },
-                },
-            };
 
-            let o_size = alice_file.header.original_size;
-            let c_size = alice_file.header.compressed_size;
+                let content = content_from_payload(&alice_file.payload);
 
-            return Ok((
-                content,
-                ContentType::AliceZip,
-                o_size,
-                c_size,
-                Some(alice_file),
-            ));
-        }
+                let o_size = alice_file.header.original_size;
+                let c_size = alice_file.header.compressed_size;
+
+                return Ok((
+                    content,
+                    ContentType::AliceZip,
+                    o_size,
+                    c_size,
+                    Some(alice_file),
+                ));
+            }
+            Err(e) => e,
+        };
 
-        // Fallback: legacy ALZ format or demo content
-        let metadata = fs::metadata(&path).await.context("Failed to read metadata")?;
-        let compressed_size = metadata.len();
+        let alz_file = alz::AlzFile::parse(&data).map_err(|alz_err| {
+            anyhow::anyhow!("Not a valid .alice file ({}) or .alz file ({})", alice_err, alz_err)
+        })?;
+        tracing::info!("Parsed ALZ file: {}", alz_file.equation_string());
 
-        let content = ProceduralContent::Fractal {
-            fractal_type: FractalType::Mandelbrot,
-            max_iterations: 256,
-            escape_radius: 2.0,
-            center: DVec2::new(-0.75, 0.0),
-            julia_c: None,
+        let content = if let alz::AlzContent::RawLzma(raw) = &alz_file.content {
+            content_from_raw(raw)
+        } else {
+            let payload = alz_file
+                .content
+                .to_alice_payload()
+                .context("ALZ body has no procedural form")?;
+            content_from_payload(&payload)
         };
 
-        Ok((content, ContentType::AliceZip, compressed_size * 500, compressed_size, None))
+        Ok((
+            content,
+            ContentType::AliceZip,
+            alz_file.header.original_size,
+            alz_file.header.compressed_size,
+            None,
+        ))
     }
 
     /// Load ASP stream file (Async)
-    async fn load_asp_async(path: PathBuf) -> Result<(ProceduralContent, ContentType, u64, u64)> {
+    ///
+    /// Indexes the recording and starts playback at its first frame.
+    async fn load_asp_async(path: PathBuf) -> Result<(ProceduralContent, ContentType, u64, u64, asp::AspPlayer)> {
         tracing::info!("Loading ASP stream (Async): {:?}", path);
 
-        let metadata = fs::metadata(&path).await.context("Failed to read metadata")?;
-        let compressed_size = metadata.len();
-
-        // TODO: Implement actual ASP parsing
-        log::warn!("ASP stream parsing not yet implemented — returning placeholder Perlin content");
-        let content = ProceduralContent::Perlin {
-            seed: 12345,
-            scale: 5.0,
-            octaves: 8,
-            persistence: 0.5,
-            lacunarity: 2.0,
-        };
+        let data = fs::read(&path).await.context("Failed to read file")?;
+        let compressed_size = data.len() as u64;
+
+        let recording = asp::AspRecording::index(data).context("Invalid ASP stream")?;
+        let player = asp::AspPlayer::new(recording).context("ASP stream contains no keyframe")?;
+        let content = player.state().content().context("ASP stream contains no keyframe")?;
+        let keyframe = player.state().keyframe.as_ref().context("ASP stream contains no keyframe")?;
 
-        Ok((content, ContentType::AspStream, compressed_size * 1000, compressed_size))
+        let recording = player.recording();
+        tracing::info!(
+            "ASP stream indexed: {} frames ({} keyframes) of {}x{}, {:.2}s",
+            recording.frame_count(),
+            recording.keyframes().len(),
+            keyframe.width,
+            keyframe.height,
+            recording.duration()
+        );
+
+        // Equivalent raw RGBA video
+        let original_size = keyframe.width as u64 * keyframe.height as u64 * 4 * recording.frame_count() as u64;
+        Ok((content, ContentType::AspStream, original_size, compressed_size, player))
     }
 
     /// Load standard image (Async + spawn_blocking for heavy decode)
@@ -367,30 +686,62 @@ impl Decoder {
         tracing::info!("Loading image (Async): {:?}", path);
 
         // Offload heavy image decoding to blocking thread pool
-        let result = tokio::task::spawn_blocking(move || -> Result<(ProceduralContent, u64, u64)> {
-            let img = image::open(&path).context("Failed to open image")?;
-            let rgba = img.to_rgba8(); // Convert to RGBA for GPU upload
-            let (width, height) = rgba.dimensions();
-            let raw_data = rgba.into_raw();
-            let original_size = (width * height * 4) as u64;
-            let compressed_size = std::fs::metadata(&path)?.len();
-
-            tracing::info!("Image decoded: {}x{}, {} bytes", width, height, original_size);
-
-            Ok((
-                ProceduralContent::Raster {
-                    width,
-                    height,
-                    data: Arc::new(raw_data), // Zero-copy sharing
-                },
-                original_size,
-                compressed_size,
-            ))
+        let result = tokio::task::spawn_blocking(move || decode_image(&path))
+            .await
+            .context("Spawn blocking task failed")??;
+
+        Ok((result.0, ContentType::Image, result.1, result.2))
+    }
+
+    /// Load a numbered PNG/JPEG sequence (Async)
+    ///
+    /// Only the first frame is decoded here; the player decodes the rest
+    /// ahead of playback on a worker thread.
+    async fn load_sequence_async(path: PathBuf) -> Result<(ProceduralContent, u64, u64, video::VideoPlayer)> {
+        tracing::info!("Loading image sequence (Async): {:?}", path);
+
+        tokio::task::spawn_blocking(move || -> Result<_> {
+            let paths = video::find_sequence(&path)?;
+            let compressed_size = paths.iter().filter_map(|p| std::fs::metadata(p).ok()).map(|m| m.len()).sum();
+            let clip = video::VideoClip::sequence(paths, video::DEFAULT_SEQUENCE_FPS)?;
+            let player = video::VideoPlayer::new(clip)?;
+            let content = player.content().clone();
+            let ProceduralContent::Raster { width, height, .. } = &content else {
+                anyhow::bail!("Sequence frame is not an image");
+            };
+
+            tracing::info!("Image sequence: {} frames of {}x{}", player.clip().frame_count(), width, height);
+            let original_size = *width as u64 * *height as u64 * 4 * player.clip().frame_count() as u64;
+            Ok((content, original_size, compressed_size, player))
         })
         .await
-        .context("Spawn blocking task failed")??;
+        .context("Spawn blocking task failed")?
+    }
 
-        Ok((result.0, ContentType::Image, result.1, result.2))
+    /// Load a GIF (Async): animated GIFs play as video, single frames are images
+    async fn load_gif_async(
+        path: PathBuf,
+    ) -> Result<(ProceduralContent, ContentType, u64, u64, Option<video::VideoPlayer>)> {
+        tracing::info!("Loading GIF (Async): {:?}", path);
+
+        let data = fs::read(&path).await.context("Failed to read file")?;
+        let compressed_size = data.len() as u64;
+        let clip = tokio::task::spawn_blocking(move || video::VideoClip::gif(&data))
+            .await
+            .context("Spawn blocking task failed")??;
+        let frame_count = clip.frame_count();
+        let player = video::VideoPlayer::new(clip)?;
+        let content = player.content().clone();
+        let ProceduralContent::Raster { width, height, .. } = &content else {
+            anyhow::bail!("GIF frame is not an image");
+        };
+        tracing::info!("GIF decoded: {} frames of {}x{}, {:.2}s", frame_count, width, height, player.clip().duration());
+
+        let original_size = *width as u64 * *height as u64 * 4 * frame_count as u64;
+        if frame_count == 1 {
+            return Ok((content, ContentType::Image, original_size, compressed_size, None));
+        }
+        Ok((content, ContentType::Video, original_size, compressed_size, Some(player)))
     }
 
     /// Get content type
@@ -425,7 +776,10 @@ impl Decoder {
                 | Some(ProceduralContent::Polynomial { .. })
                 | Some(ProceduralContent::SineWave { .. })
                 | Some(ProceduralContent::Fourier { .. })
+                | Some(ProceduralContent::Segmented { .. })
+                | Some(ProceduralContent::Composite { .. })
                 | Some(ProceduralContent::Fractal { .. })
+                | Some(ProceduralContent::Voronoi { .. })
         )
     }
 }
@@ -435,3 +789,158 @@ impl Default for Decoder {
         Self::new()
     }
 }
+
+/// Decode a standard image file to RGBA8 raster content
+///
+/// Returns the content with its raw and on-disk sizes.
+fn decode_image(path: &Path) -> Result<(ProceduralContent, u64, u64)> {
+    let img = image::open(path).context("Failed to open image")?;
+    let rgba = img.to_rgba8(); // Convert to RGBA for GPU upload
+    let (width, height) = rgba.dimensions();
+    let raw_data = rgba.into_raw();
+    let original_size = (width * height * 4) as u64;
+    let compressed_size = std::fs::metadata(path)?.len();
+
+    tracing::info!("Image decoded: {}x{}, {} bytes", width, height, original_size);
+
+    Ok((
+        ProceduralContent::Raster {
+            width,
+            height,
+            data: Arc::new(raw_data), // Zero-copy sharing
+        },
+        original_size,
+        compressed_size,
+    ))
+}
+
+/// Map a decoded .alice payload to renderable procedural content
+fn content_from_payload(payload: &alice::AlicePayload) -> ProceduralContent {
+    match payload {
+        // A line is a degree-1 polynomial: plot it as one
+        alice::AlicePayload::Linear(p) => ProceduralContent::Polynomial {
+            coefficients: vec![p.intercept_q16 as f64 / 65536.0, p.slope_q16 as f64 / 65536.0],
+            domain_end: p.sample_count,
+            value_range: sampled_range(payload, p.sample_count),
+        },
+        alice::AlicePayload::Polynomial(p) => ProceduralContent::Polynomial {
+            coefficients: p.coefficients_f64(),
+            domain_end: p.sample_count,
+            value_range: sampled_range(payload, p.sample_count),
+        },
+        alice::AlicePayload::Fourier(p) => ProceduralContent::Fourier {
+            coefficients: p
+                .terms
+                .iter()
+                .map(|t| (t.frequency as usize, t.amplitude, t.phase))
+                .collect(),
+            period: p.period as f32,
+        },
+        alice::AlicePayload::SineWave(p) => ProceduralContent::SineWave {
+            components: p
+                .components
+                .iter()
+                .map(|c| (c.frequency, c.amplitude, c.phase))
+                .collect(),
+            offset: p.offset,
+        },
+        alice::AlicePayload::Segmented(p) => ProceduralContent::Segmented {
+            segments: p
+                .segments
+                .iter()
+                .map(|s| (s.start_index, s.model.coefficients_f64()))
+                .collect(),
+            domain_end: p.domain_end(),
+            value_range: p.value_range(512),
+        },
+        alice::AlicePayload::Composite(p) => ProceduralContent::Composite {
+            layers: p
+                .layers
+                .iter()
+                .map(|l| (l.blend, l.weight, content_from_payload(&l.payload)))
+                .collect(),
+        },
+        alice::AlicePayload::Voronoi(p) => ProceduralContent::Voronoi {
+            seed: p.seed,
+            cell_density: p.cell_density,
+            metric: p.metric,
+            feature: p.feature,
+            jitter: p.jitter,
+        },
+        alice::AlicePayload::Perlin(p) => ProceduralContent::Perlin {
+            seed: p.seed,
+            scale: p.scale,
+            octaves: p.octaves,
+            persistence: p.persistence,
+            lacunarity: p.lacunarity,
+        },
+        alice::AlicePayload::Fractal(p) => ProceduralContent::Fractal {
+            fractal_type: match p.fractal_type {
+                0 => FractalType::Mandelbrot,
+                1 => FractalType::Julia,
+                2 => FractalType::BurningShip,
+                3 => FractalType::Tricorn,
+                _ => FractalType::Mandelbrot,
+            },
+            max_iterations: p.max_iterations,
+            escape_radius: p.escape_radius,
+            center: DVec2::new(p.center_x, p.center_y),
+            julia_c: if p.fractal_type == 1 {
+                Some(DVec2::new(p.julia_cx, p.julia_cy))
+            } else {
+                None
+            },
+        },
+    }
+}
+
+/// Min / max of a 1D payload sampled over [0, end]
+fn sampled_range(payload: &alice::AlicePayload, end: u32) -> (f64, f64) {
+    const STEPS: u32 = 512;
+    let mut range = (f64::INFINITY, f64::NEG_INFINITY);
+    for i in 0..=STEPS {
+        let x = end as f64 * i as f64 / STEPS as f64;
+        if let Some(y) = payload.evaluate(x).filter(|y| y.is_finite()) {
+            range = (range.0.min(y), range.1.max(y));
+        }
+    }
+    if range.0 > range.1 {
+        (0.0, 0.0)
+    } else {
+        range
+    }
+}
+
+/// Side limit of the grayscale byte map shown for non-image ALZ data
+const MAX_BYTE_MAP_SIDE: usize = 4096;
+
+/// Show decompressed ALZ data: as an image when it decodes as one,
+/// otherwise as a near-square grayscale byte map of its first
+/// `MAX_BYTE_MAP_SIDE`² bytes
+fn content_from_raw(raw: &[u8]) -> ProceduralContent {
+    if let Ok(img) = image::load_from_memory(raw) {
+        let rgba = img.to_rgba8();
+        let (width, height) = rgba.dimensions();
+        return ProceduralContent::Raster {
+            width,
+            height,
+            data: Arc::new(rgba.into_raw()),
+        };
+    }
+
+    let shown = &raw[..raw.len().min(MAX_BYTE_MAP_SIDE * MAX_BYTE_MAP_SIDE)];
+    if shown.len() < raw.len() {
+        tracing::warn!("ALZ byte map shows the first {} of {} bytes", shown.len(), raw.len());
+    }
+    let width = ((shown.len() as f64).sqrt().ceil() as usize).clamp(1, MAX_BYTE_MAP_SIDE);
+    let height = shown.len().div_ceil(width).max(1);
+    let mut data = vec![0u8; width * height * 4];
+    for (i, &b) in shown.iter().enumerate() {
+        data[i * 4..i * 4 + 4].copy_from_slice(&[b, b, b, 255]);
+    }
+    ProceduralContent::Raster {
+        width: width as u32,
+        height: height as u32,
+        data: Arc::new(data),
+    }
+}
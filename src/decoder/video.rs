@@ -0,0 +1,764 @@
+//! Image-sequence and animated GIF playback
+//!
+//! Both play as `ContentType::Video`, one `ProceduralContent::Raster` per
+//! frame, so they are drawn by the raster pipeline like still images.
+//!
+//! - Sequences: a directory of numbered PNG/JPEG files, or a file name with a
+//!   printf-style frame number (`frames/shot_%04d.png`). Frames are decoded
+//!   a few ahead of playback on a worker thread and play at
+//!   [`DEFAULT_SEQUENCE_FPS`] until changed.
+//! - Animated GIFs: all frames are decoded (composited) up front, up to
+//!   [`MAX_GIF_BYTES`] in total, and keep their own delays.
+
+use super::ProceduralContent;
+use anyhow::{bail, Context, Result};
+use image::{AnimationDecoder, ImageDecoder};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+
+/// Frame rate of image sequences until changed
+pub const DEFAULT_SEQUENCE_FPS: f64 = 24.0;
+
+/// Playback rates offered when stepping the fps up or down
+pub const FPS_STEPS: [f64; 12] = [1.0, 2.0, 5.0, 10.0, 12.0, 15.0, 24.0, 25.0, 30.0, 50.0, 60.0, 120.0];
+
+/// Largest total size of decoded (RGBA8) GIF frames
+pub const MAX_GIF_BYTES: usize = 1 << 30;
+
+/// GIF delays below this are shown at [`GIF_FALLBACK_DELAY`], as browsers do
+const GIF_MIN_DELAY: f64 = 0.02;
+const GIF_FALLBACK_DELAY: f64 = 0.1;
+
+/// Sequence frames decoded ahead of the one wanted
+const PREFETCH_DEPTH: usize = 4;
+
+/// Image file extensions accepted in sequences
+const SEQUENCE_EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+/// Where a frame's pixels come from
+enum FrameSource {
+    /// Decoded RGBA8 (GIF frames)
+    Decoded { width: u32, height: u32, data: Arc<Vec<u8>> },
+    /// Decoded ahead of playback (sequence frames)
+    File(PathBuf),
+}
+
+/// Frames of a video and their timing
+pub struct VideoClip {
+    frames: Vec<FrameSource>,
+    /// Start time of each frame at the native rate (seconds)
+    times: Vec<f64>,
+    /// Total length at the native rate (seconds)
+    duration: f64,
+}
+
+impl VideoClip {
+    /// Numbered image files shown for `1 / fps` seconds each
+    pub fn sequence(paths: Vec<PathBuf>, fps: f64) -> Result<Self> {
+        if paths.is_empty() {
+            bail!("Image sequence has no frames");
+        }
+        let delay = 1.0 / fps;
+        let frames = paths.into_iter().map(FrameSource::File).collect();
+        Ok(Self::with_delays(frames, |_| delay))
+    }
+
+    /// Decode every frame of a GIF
+    pub fn gif(data: &[u8]) -> Result<Self> {
+        Self::gif_with_limit(data, MAX_GIF_BYTES)
+    }
+
+    /// Decode every frame of a GIF, failing once the frames exceed `max_bytes`
+    fn gif_with_limit(data: &[u8], max_bytes: usize) -> Result<Self> {
+        let decoder = image::codecs::gif::GifDecoder::new(std::io::Cursor::new(data)).context("Invalid GIF")?;
+        // Frames are composited onto the full canvas
+        let (width, height) = decoder.dimensions();
+        let frame_bytes = (width as usize).checked_mul(height as usize).and_then(|n| n.checked_mul(4));
+
+        let mut frames = Vec::new();
+        let mut delays = Vec::new();
+        for frame in decoder.into_frames() {
+            let total = frame_bytes.and_then(|n| n.checked_mul(frames.len() + 1));
+            if total.map_or(true, |total| total > max_bytes) {
+                bail!(
+                    "GIF too large: {} or more frames of {}x{} exceed {} MiB decoded",
+                    frames.len() + 1,
+                    width,
+                    height,
+                    max_bytes >> 20
+                );
+            }
+            let frame = frame.context("Failed to decode GIF frames")?;
+
+            let (numer, denom) = frame.delay().numer_denom_ms();
+            let delay = numer as f64 / denom.max(1) as f64 / 1000.0;
+            delays.push(if delay < GIF_MIN_DELAY { GIF_FALLBACK_DELAY } else { delay });
+
+            let buffer = frame.into_buffer();
+            let (width, height) = buffer.dimensions();
+            frames.push(FrameSource::Decoded { width, height, data: Arc::new(buffer.into_raw()) });
+        }
+        if frames.is_empty() {
+            bail!("GIF has no frames");
+        }
+        Ok(Self::with_delays(frames, |i| delays[i]))
+    }
+
+    fn with_delays(frames: Vec<FrameSource>, delay: impl Fn(usize) -> f64) -> Self {
+        let mut times = Vec::with_capacity(frames.len());
+        let mut duration = 0.0;
+        for i in 0..frames.len() {
+            times.push(duration);
+            duration += delay(i);
+        }
+        Self { frames, times, duration }
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Length in seconds at the native rate
+    pub fn duration(&self) -> f64 {
+        self.duration
+    }
+
+    /// Start of `frame` in seconds at the native rate
+    pub fn frame_time(&self, frame: usize) -> f64 {
+        self.times[frame.min(self.times.len() - 1)]
+    }
+
+    /// Average frame rate the clip was made for
+    pub fn native_fps(&self) -> f64 {
+        self.frames.len() as f64 / self.duration
+    }
+
+    /// Frame shown at `time` seconds (native rate, clamped)
+    pub fn frame_at(&self, time: f64) -> usize {
+        self.times.partition_point(|&t| t <= time).saturating_sub(1)
+    }
+
+    /// Pixels of `frame`
+    pub fn decode(&self, frame: usize) -> Result<ProceduralContent> {
+        match &self.frames[frame] {
+            FrameSource::Decoded { width, height, data } => {
+                Ok(ProceduralContent::Raster { width: *width, height: *height, data: data.clone() })
+            }
+            FrameSource::File(path) => decode_file(path),
+        }
+    }
+}
+
+fn decode_file(path: &Path) -> Result<ProceduralContent> {
+    let rgba = image::open(path)
+        .with_context(|| format!("Failed to open frame {}", path.display()))?
+        .to_rgba8();
+    let (width, height) = rgba.dimensions();
+    Ok(ProceduralContent::Raster { width, height, data: Arc::new(rgba.into_raw()) })
+}
+
+/// Sequence frames waiting for the worker
+#[derive(Default)]
+struct PrefetchQueue {
+    frames: VecDeque<(usize, PathBuf)>,
+    /// Frame the worker is decoding
+    decoding: Option<usize>,
+    closed: bool,
+}
+
+type DecodedFrame = (usize, Result<ProceduralContent>);
+
+/// Decodes sequence frames on a worker thread so playback never waits on
+/// file I/O
+///
+/// Each request replaces the queue, so frames skipped over by a seek are
+/// not decoded.
+struct FramePrefetcher {
+    queue: Arc<(Mutex<PrefetchQueue>, Condvar)>,
+    decoded: mpsc::Receiver<DecodedFrame>,
+    /// Decoded frames not taken yet
+    ready: HashMap<usize, Result<ProceduralContent>>,
+}
+
+impl FramePrefetcher {
+    fn new() -> Result<Self> {
+        let queue = Arc::new((Mutex::new(PrefetchQueue::default()), Condvar::new()));
+        let (sender, decoded) = mpsc::channel();
+        let worker_queue = queue.clone();
+        thread::Builder::new()
+            .name("video-prefetch".into())
+            .spawn(move || Self::run(&worker_queue, &sender))
+            .context("Failed to start the frame decoder")?;
+        Ok(Self { queue, decoded, ready: HashMap::new() })
+    }
+
+    fn run(queue: &(Mutex<PrefetchQueue>, Condvar), decoded: &mpsc::Sender<DecodedFrame>) {
+        let (mutex, wake) = queue;
+        loop {
+            let (frame, path) = {
+                let mut queue = lock(mutex);
+                loop {
+                    if queue.closed {
+                        return;
+                    }
+                    if let Some(job) = queue.frames.pop_front() {
+                        queue.decoding = Some(job.0);
+                        break job;
+                    }
+                    queue = wake.wait(queue).unwrap_or_else(|e| e.into_inner());
+                }
+            };
+            let result = decode_file(&path);
+            // Hand over under the lock so `request` never misses a frame
+            let mut queue = lock(mutex);
+            queue.decoding = None;
+            if decoded.send((frame, result)).is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Take `frame` if it has been decoded
+    fn take(&mut self, frame: usize) -> Option<Result<ProceduralContent>> {
+        self.ready.extend(self.decoded.try_iter());
+        self.ready.remove(&frame)
+    }
+
+    /// Decode `frame` and the [`PREFETCH_DEPTH`] frames after it (looping),
+    /// dropping frames and requests outside that window
+    fn request(&mut self, clip: &VideoClip, frame: usize) {
+        let count = clip.frame_count();
+        let window: Vec<usize> = (0..(PREFETCH_DEPTH + 1).min(count)).map(|i| (frame + i) % count).collect();
+
+        let (mutex, wake) = &*self.queue;
+        let mut queue = lock(mutex);
+        self.ready.extend(self.decoded.try_iter());
+        self.ready.retain(|frame, _| window.contains(frame));
+        let decoding = queue.decoding;
+        queue.frames = window
+            .iter()
+            .filter(|&&frame| !self.ready.contains_key(&frame) && decoding != Some(frame))
+            .filter_map(|&frame| match &clip.frames[frame] {
+                FrameSource::File(path) => Some((frame, path.clone())),
+                FrameSource::Decoded { .. } => None,
+            })
+            .collect();
+        wake.notify_one();
+    }
+}
+
+impl Drop for FramePrefetcher {
+    fn drop(&mut self) {
+        let (mutex, wake) = &*self.queue;
+        lock(mutex).closed = true;
+        wake.notify_one();
+    }
+}
+
+fn lock(queue: &Mutex<PrefetchQueue>) -> MutexGuard<'_, PrefetchQueue> {
+    // A panicking decode leaves the queue usable
+    queue.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Plays a [`VideoClip`] at an adjustable frame rate
+pub struct VideoPlayer {
+    clip: VideoClip,
+    /// Frame being shown
+    frame: usize,
+    /// Frame to show once decoded
+    wanted: usize,
+    /// Pixels of the current frame
+    content: ProceduralContent,
+    /// Playback position in seconds at the native rate
+    clock: f64,
+    /// Playback rate; frame delays are scaled by `native_fps / fps`
+    fps: f64,
+    /// Decodes sequence frames (None when all frames are decoded)
+    prefetcher: Option<FramePrefetcher>,
+    /// Frames that failed to decode; they are skipped, not retried
+    failed: HashSet<usize>,
+}
+
+impl VideoPlayer {
+    /// Start at the first frame, at the clip's native rate
+    pub fn new(clip: VideoClip) -> Result<Self> {
+        let content = clip.decode(0)?;
+        let fps = clip.native_fps();
+        let has_files = clip.frames.iter().any(|frame| matches!(frame, FrameSource::File(_)));
+        let prefetcher = if has_files && clip.frame_count() > 1 {
+            let mut prefetcher = FramePrefetcher::new()?;
+            prefetcher.request(&clip, 1);
+            Some(prefetcher)
+        } else {
+            None
+        };
+        Ok(Self { clip, frame: 0, wanted: 0, content, clock: 0.0, fps, prefetcher, failed: HashSet::new() })
+    }
+
+    pub fn clip(&self) -> &VideoClip {
+        &self.clip
+    }
+
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Pixels of the current frame (`ProceduralContent::Raster`)
+    pub fn content(&self) -> &ProceduralContent {
+        &self.content
+    }
+
+    /// Playback position in seconds at the native rate
+    pub fn time(&self) -> f64 {
+        self.clock
+    }
+
+    pub fn fps(&self) -> f64 {
+        self.fps
+    }
+
+    // The viewer steps through FPS_STEPS; embedders may set any rate.
+    #[allow(dead_code)]
+    pub fn set_fps(&mut self, fps: f64) {
+        if fps.is_finite() && fps > 0.0 {
+            self.fps = fps;
+        }
+    }
+
+    /// Move to the next faster (or slower) rate of [`FPS_STEPS`]
+    pub fn step_fps(&mut self, faster: bool) {
+        let next = if faster {
+            FPS_STEPS.iter().find(|&&fps| fps > self.fps + 1e-6)
+        } else {
+            FPS_STEPS.iter().rev().find(|&&fps| fps < self.fps - 1e-6)
+        };
+        if let Some(&fps) = next {
+            self.fps = fps;
+        }
+    }
+
+    /// Jump to `frame` (clamped)
+    ///
+    /// A sequence frame still being decoded is shown by a later
+    /// [`poll`](Self::poll). Returns whether the frame shown changed.
+    pub fn seek(&mut self, frame: usize) -> Result<bool> {
+        let frame = frame.min(self.clip.frame_count() - 1);
+        self.wanted = frame;
+        self.clock = self.clip.frame_time(frame);
+        self.poll()
+    }
+
+    /// Move `delta` frames (clamped)
+    pub fn step(&mut self, delta: isize) -> Result<bool> {
+        self.seek(self.wanted.saturating_add_signed(delta))
+    }
+
+    /// Advance playback by `dt` seconds of wall time, looping at the end
+    ///
+    /// Returns whether the frame shown changed.
+    pub fn advance(&mut self, dt: f64) -> Result<bool> {
+        let duration = self.clip.duration();
+        self.clock = (self.clock + dt.max(0.0) * self.fps / self.clip.native_fps()) % duration;
+        self.wanted = self.clip.frame_at(self.clock);
+        self.poll()
+    }
+
+    /// Show the wanted frame if it is decoded; the current one stays up until
+    /// then
+    ///
+    /// Returns whether the frame shown changed. A frame that fails to decode
+    /// is reported once and then skipped: the previous frame stays up.
+    pub fn poll(&mut self) -> Result<bool> {
+        if self.wanted == self.frame || self.failed.contains(&self.wanted) {
+            return Ok(false);
+        }
+        let result = match &mut self.prefetcher {
+            Some(prefetcher) if matches!(self.clip.frames[self.wanted], FrameSource::File(_)) => {
+                match prefetcher.take(self.wanted) {
+                    Some(result) => result,
+                    None => {
+                        prefetcher.request(&self.clip, self.wanted);
+                        return Ok(false);
+                    }
+                }
+            }
+            _ => self.clip.decode(self.wanted),
+        };
+        let content = match result {
+            Ok(content) => content,
+            Err(e) => {
+                self.failed.insert(self.wanted);
+                return Err(e);
+            }
+        };
+
+        self.content = content;
+        self.frame = self.wanted;
+        if let Some(prefetcher) = &mut self.prefetcher {
+            prefetcher.request(&self.clip, (self.frame + 1) % self.clip.frame_count());
+        }
+        Ok(true)
+    }
+}
+
+/// Whether `path` names an image sequence rather than a single file
+pub fn is_sequence(path: &Path) -> bool {
+    path.is_dir() || path.file_name().and_then(|name| name.to_str()).and_then(parse_pattern).is_some()
+}
+
+/// Frames of a numbered image sequence, in frame-number order
+///
+/// `path` is a directory (all its PNG/JPEG files) or a file name with a
+/// printf-style frame number such as `shot_%04d.png`.
+pub fn find_sequence(path: &Path) -> Result<Vec<PathBuf>> {
+    let (dir, pattern) = if path.is_dir() {
+        (path, None)
+    } else {
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        let pattern = parse_pattern(name).with_context(|| format!("Not a sequence pattern: {}", name))?;
+        let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        (dir, Some(pattern))
+    };
+
+    let entries = std::fs::read_dir(dir).with_context(|| format!("Failed to list {}", dir.display()))?;
+    let mut frames: Vec<(u64, PathBuf)> = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let number = match &pattern {
+            Some(pattern) => pattern.frame_number(name),
+            None if is_sequence_image(name) => Some(trailing_number(name).unwrap_or(0)),
+            None => None,
+        };
+        if let Some(number) = number {
+            frames.push((number, path));
+        }
+    }
+    if frames.is_empty() {
+        bail!("No frames found for {}", path.display());
+    }
+    frames.sort();
+    Ok(frames.into_iter().map(|(_, path)| path).collect())
+}
+
+/// `prefix%0Nd suffix` file name pattern
+#[derive(Debug, PartialEq, Eq)]
+struct SequencePattern {
+    prefix: String,
+    /// Minimum digit count (`%04d` = 4)
+    digits: usize,
+    suffix: String,
+}
+
+impl SequencePattern {
+    /// Frame number of `name` if it matches the pattern
+    fn frame_number(&self, name: &str) -> Option<u64> {
+        let number = name.strip_prefix(&self.prefix)?.strip_suffix(&self.suffix)?;
+        if number.is_empty() || number.len() < self.digits || !number.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        number.parse().ok()
+    }
+}
+
+/// Parse `shot_%04d.png` / `shot%d.jpg`; the suffix must be a sequence image type
+fn parse_pattern(name: &str) -> Option<SequencePattern> {
+    let (prefix, rest) = name.split_once('%')?;
+    let (width, suffix) = rest.split_once('d')?;
+    let digits = if width.is_empty() {
+        0
+    } else if width.bytes().all(|b| b.is_ascii_digit()) {
+        width.parse().ok()?
+    } else {
+        return None;
+    };
+    if !is_sequence_image(suffix) {
+        return None;
+    }
+    Some(SequencePattern { prefix: prefix.to_string(), digits, suffix: suffix.to_string() })
+}
+
+/// Whether a file name (or name suffix) ends in a sequence image extension
+fn is_sequence_image(name: &str) -> bool {
+    name.rsplit_once('.')
+        .is_some_and(|(_, ext)| SEQUENCE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Last run of digits in the file stem (`shot_0012.png` = 12)
+fn trailing_number(name: &str) -> Option<u64> {
+    let stem = Path::new(name).file_stem()?.to_str()?;
+    let end = stem.rfind(|c: char| c.is_ascii_digit())? + 1;
+    let start = stem[..end].rfind(|c: char| !c.is_ascii_digit()).map_or(0, |i| i + 1);
+    stem[start..end].parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::gif::GifEncoder;
+    use image::{Delay, Frame, Rgba, RgbaImage};
+    use std::time::{Duration, Instant};
+
+    /// Solid `width` x `height` image of one color
+    fn solid(width: u32, height: u32, color: [u8; 4]) -> RgbaImage {
+        RgbaImage::from_pixel(width, height, Rgba(color))
+    }
+
+    fn red(content: &ProceduralContent) -> u8 {
+        match content {
+            ProceduralContent::Raster { data, .. } => data[0],
+            _ => panic!("not a raster frame"),
+        }
+    }
+
+    /// Poll like the render loop until the sequence frame `frame` is shown
+    fn wait_for_frame(player: &mut VideoPlayer, frame: usize) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while player.frame() != frame {
+            assert!(Instant::now() < deadline, "frame {} was not decoded", frame);
+            player.poll().unwrap();
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    /// Temporary directory removed on drop
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("alice-view-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_parse_pattern() {
+        let pattern = parse_pattern("shot_%04d.png").unwrap();
+        assert_eq!(
+            pattern,
+            SequencePattern { prefix: "shot_".into(), digits: 4, suffix: ".png".into() }
+        );
+        assert_eq!(pattern.frame_number("shot_0012.png"), Some(12));
+        assert_eq!(pattern.frame_number("shot_12345.png"), Some(12345));
+        assert_eq!(pattern.frame_number("shot_012.png"), None);
+        assert_eq!(pattern.frame_number("shot_0012.jpg"), None);
+        assert_eq!(pattern.frame_number("shot_00a2.png"), None);
+
+        assert_eq!(parse_pattern("f%d.JPG").unwrap().frame_number("f7.JPG"), Some(7));
+        assert_eq!(parse_pattern("photo.png"), None);
+        assert_eq!(parse_pattern("50%_off.png"), None);
+        assert_eq!(parse_pattern("clip_%04d.gif"), None);
+        assert_eq!(trailing_number("take2_frame_0031.png"), Some(31));
+        assert_eq!(trailing_number("cover.png"), None);
+    }
+
+    #[test]
+    fn test_find_sequence() {
+        let dir = TempDir::new("sequence");
+        for (name, value) in [("f_0010.png", 10u8), ("f_0002.png", 2), ("f_0001.png", 1), ("notes.txt", 0)] {
+            let path = dir.0.join(name);
+            if name.ends_with(".png") {
+                solid(2, 2, [value, 0, 0, 255]).save(&path).unwrap();
+            } else {
+                std::fs::write(&path, b"not a frame").unwrap();
+            }
+        }
+        let names = |paths: Vec<PathBuf>| -> Vec<String> {
+            paths.iter().map(|p| p.file_name().unwrap().to_string_lossy().to_string()).collect()
+        };
+
+        assert!(is_sequence(&dir.0));
+        assert_eq!(names(find_sequence(&dir.0).unwrap()), ["f_0001.png", "f_0002.png", "f_0010.png"]);
+
+        let pattern = dir.0.join("f_%04d.png");
+        assert!(is_sequence(&pattern));
+        assert!(!is_sequence(&dir.0.join("f_0001.png")));
+        assert_eq!(names(find_sequence(&pattern).unwrap()), ["f_0001.png", "f_0002.png", "f_0010.png"]);
+        assert!(find_sequence(&dir.0.join("g_%04d.png")).is_err());
+
+        let clip = VideoClip::sequence(find_sequence(&pattern).unwrap(), 10.0).unwrap();
+        assert_eq!(clip.frame_count(), 3);
+        assert!((clip.duration() - 0.3).abs() < 1e-9);
+        let mut player = VideoPlayer::new(clip).unwrap();
+        assert_eq!(red(player.content()), 1);
+        player.seek(2).unwrap();
+        wait_for_frame(&mut player, 2);
+        assert_eq!(red(player.content()), 10);
+    }
+
+    #[test]
+    fn test_sequence_prefetch() {
+        let dir = TempDir::new("prefetch");
+        let mut paths: Vec<PathBuf> = (0..8u8)
+            .map(|i| {
+                let path = dir.0.join(format!("f_{}.png", i));
+                solid(2, 2, [i * 10, 0, 0, 255]).save(&path).unwrap();
+                path
+            })
+            .collect();
+        paths.push(dir.0.join("missing.png"));
+        let mut player = VideoPlayer::new(VideoClip::sequence(paths, 10.0).unwrap()).unwrap();
+
+        // Frame 6 is beyond the frames decoded ahead: the first frame stays
+        // up until it is ready
+        assert!(!player.seek(6).unwrap());
+        assert_eq!((player.frame(), red(player.content())), (0, 0));
+        wait_for_frame(&mut player, 6);
+        assert_eq!(red(player.content()), 60);
+
+        // The frames after it are decoded ahead of playback
+        let prefetcher = player.prefetcher.as_mut().unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !prefetcher.ready.contains_key(&7) {
+            assert!(Instant::now() < deadline, "frame 7 was not decoded ahead");
+            thread::sleep(Duration::from_millis(5));
+            prefetcher.ready.extend(prefetcher.decoded.try_iter());
+        }
+        assert!(player.advance(0.15).unwrap());
+        assert_eq!(red(player.content()), 70);
+
+        // A frame that fails to decode is given up on
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut result = player.seek(8);
+        while result.is_ok() {
+            assert!(Instant::now() < deadline, "missing frame was not reported");
+            thread::sleep(Duration::from_millis(5));
+            result = player.poll();
+        }
+        let err = result.unwrap_err();
+        assert!(format!("{:#}", err).contains("missing.png"), "{:#}", err);
+        assert_eq!(player.frame(), 7);
+        assert!(!player.poll().unwrap());
+    }
+
+    #[test]
+    fn test_corrupt_frame_reported_once() {
+        let dir = TempDir::new("corrupt");
+        let paths: Vec<PathBuf> = (0..4u8)
+            .map(|i| {
+                let path = dir.0.join(format!("f_{}.png", i));
+                if i == 2 {
+                    std::fs::write(&path, b"not a png").unwrap();
+                } else {
+                    solid(2, 2, [i * 10, 0, 0, 255]).save(&path).unwrap();
+                }
+                path
+            })
+            .collect();
+        let mut player = VideoPlayer::new(VideoClip::sequence(paths, 10.0).unwrap()).unwrap();
+        player.seek(1).unwrap();
+        wait_for_frame(&mut player, 1);
+
+        // Playback reaches the corrupt frame and keeps ticking over it: the
+        // error is reported once and frame 1 stays up
+        let mut results = vec![player.seek(2)];
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while Instant::now() < deadline {
+            results.push(player.advance(0.0));
+            thread::sleep(Duration::from_millis(5));
+        }
+        let errors: Vec<_> = results.iter().filter_map(|result| result.as_ref().err()).collect();
+        assert_eq!(errors.len(), 1);
+        assert!(format!("{:#}", errors[0]).contains("f_2.png"), "{:#}", errors[0]);
+        assert!(results.iter().all(|result| !matches!(result, Ok(true))));
+        assert_eq!(player.frame(), 1);
+        assert!(!player.seek(2).unwrap());
+
+        // Playback continues past it
+        player.advance(0.15).unwrap();
+        wait_for_frame(&mut player, 3);
+        assert_eq!(red(player.content()), 30);
+    }
+
+    #[test]
+    fn test_gif_frames_and_delays() {
+        let mut data = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut data);
+            for (value, ms) in [(10u8, 100u32), (20, 300), (30, 0)] {
+                let frame = Frame::from_parts(solid(3, 2, [value, 0, 0, 255]), 0, 0, Delay::from_numer_denom_ms(ms, 1));
+                encoder.encode_frame(frame).unwrap();
+            }
+        }
+
+        let clip = VideoClip::gif(&data).unwrap();
+        assert_eq!(clip.frame_count(), 3);
+        // The zero delay falls back to 100 ms
+        assert!((clip.duration() - 0.5).abs() < 1e-9);
+        assert_eq!(clip.frame_at(0.05), 0);
+        assert_eq!(clip.frame_at(0.1), 1);
+        assert_eq!(clip.frame_at(0.45), 2);
+        assert!((clip.native_fps() - 6.0).abs() < 1e-9);
+
+        let player = VideoPlayer::new(clip).unwrap();
+        match player.content() {
+            ProceduralContent::Raster { width, height, data } => {
+                assert_eq!((*width, *height), (3, 2));
+                assert!((data[0] as i32 - 10).abs() <= 2, "red {}", data[0]);
+            }
+            _ => panic!("not a raster frame"),
+        }
+
+        assert!(VideoClip::gif(b"GIF89a").is_err());
+
+        // Three 3x2 RGBA frames take 72 bytes
+        assert_eq!(VideoClip::gif_with_limit(&data, 72).unwrap().frame_count(), 3);
+        let err = VideoClip::gif_with_limit(&data, 71).err().unwrap();
+        assert!(err.to_string().contains("GIF too large"), "{}", err);
+    }
+
+    #[test]
+    fn test_player_advance_fps_and_seek() {
+        let paths = (0..4).map(|i| PathBuf::from(format!("frame_{}.png", i))).collect();
+        let clip = VideoClip::sequence(paths, 10.0).unwrap();
+        // Sequence frames decode in the background, so use a clip of decoded frames
+        let frames = (0..4)
+            .map(|i| FrameSource::Decoded { width: 1, height: 1, data: Arc::new(vec![i as u8 * 10, 0, 0, 255]) })
+            .collect();
+        let clip = VideoClip { frames, ..clip };
+        let mut player = VideoPlayer::new(clip).unwrap();
+        assert_eq!(player.fps(), 10.0);
+
+        assert!(!player.advance(0.05).unwrap());
+        assert!(player.advance(0.06).unwrap());
+        assert_eq!((player.frame(), red(player.content())), (1, 10));
+
+        // Twice the rate: 0.1 s of wall time covers two frames
+        player.set_fps(20.0);
+        player.advance(0.1).unwrap();
+        assert_eq!(player.frame(), 3);
+
+        // Loops past the end
+        player.advance(0.05).unwrap();
+        assert_eq!(player.frame(), 0);
+
+        player.step(-1).unwrap();
+        assert_eq!(player.frame(), 0);
+        player.step(10).unwrap();
+        assert_eq!((player.frame(), red(player.content())), (3, 30));
+        assert!((player.time() - 0.3).abs() < 1e-9);
+
+        player.step_fps(true);
+        assert_eq!(player.fps(), 24.0);
+        player.set_fps(11.0);
+        player.step_fps(false);
+        assert_eq!(player.fps(), 10.0);
+        player.set_fps(1.0);
+        player.step_fps(false);
+        assert_eq!(player.fps(), 1.0);
+        player.set_fps(0.0);
+        assert_eq!(player.fps(), 1.0);
+    }
+}
@@ -0,0 +1,190 @@
+//! Expansion of .alice models back into sample data
+//!
+//! The inverse of [`crate::fit`]: evaluates a payload on a grid of sample
+//! positions and, when the metadata carries a sample interval, attaches a
+//! time to each row (absolute when the start timestamp is RFC 3339,
+//! otherwise seconds since the first sample).
+
+use crate::decoder::alice::{AliceFile, AlicePayload};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, FixedOffset};
+use std::fmt::Write as _;
+
+/// Sample positions `start, start + step, …` below `end`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleGrid {
+    pub start: f64,
+    pub end: f64,
+    pub step: f64,
+}
+
+impl SampleGrid {
+    /// Upper bound on rows produced by one expansion
+    pub const MAX_POINTS: usize = 10_000_000;
+
+    /// One row per stored sample, or `None` if the payload has no sample count
+    pub fn for_payload(payload: &AlicePayload) -> Option<Self> {
+        let count = match payload {
+            AlicePayload::Linear(p) => p.sample_count,
+            AlicePayload::Polynomial(p) => p.sample_count,
+            AlicePayload::Fourier(p) => p.sample_count,
+            AlicePayload::SineWave(p) => p.sample_count,
+            AlicePayload::Segmented(p) => p.domain_end(),
+            _ => 0,
+        };
+        (count > 0).then_some(Self { start: 0.0, end: count as f64, step: 1.0 })
+    }
+
+    /// Parse a range written `a..b`
+    pub fn parse_range(range: &str, step: f64) -> Result<Self> {
+        let (a, b) = range
+            .split_once("..")
+            .with_context(|| format!("range '{}' must look like a..b", range))?;
+        let grid = Self {
+            start: a.trim().parse().with_context(|| format!("invalid range start '{}'", a))?,
+            end: b.trim().parse().with_context(|| format!("invalid range end '{}'", b))?,
+            step,
+        };
+        grid.point_count()?;
+        Ok(grid)
+    }
+
+    /// Number of points, validating the grid
+    pub fn point_count(&self) -> Result<usize> {
+        if !(self.step > 0.0 && self.step.is_finite()) {
+            bail!("step must be positive, got {}", self.step);
+        }
+        if !(self.start.is_finite() && self.end.is_finite() && self.end > self.start) {
+            bail!("range {}..{} is empty", self.start, self.end);
+        }
+        let n = ((self.end - self.start) / self.step).ceil() as usize;
+        if n > Self::MAX_POINTS {
+            bail!("grid has {} points (max {})", n, Self::MAX_POINTS);
+        }
+        Ok(n)
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = f64> + '_ {
+        let n = self.point_count().unwrap_or(0);
+        (0..n).map(move |i| self.start + i as f64 * self.step)
+    }
+}
+
+/// Time attached to an expanded sample
+#[derive(Debug, Clone, PartialEq)]
+pub enum SampleTime {
+    Absolute(DateTime<FixedOffset>),
+    /// Seconds since the first sample
+    Elapsed(f64),
+}
+
+/// One reconstructed sample
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpandedSample {
+    /// Sample position
+    pub x: f64,
+    pub time: Option<SampleTime>,
+    pub value: f64,
+}
+
+/// Evaluate `file` on `grid`
+pub fn expand(file: &AliceFile, grid: &SampleGrid) -> Result<Vec<ExpandedSample>> {
+    grid.point_count()?;
+    if file.payload.evaluate(grid.start).is_none() {
+        bail!("{} is a 2D field, not a sampled series", file.content_type_name());
+    }
+
+    let start_time = file
+        .metadata
+        .timestamp
+        .as_deref()
+        .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok());
+    let interval = file.metadata.sample_interval;
+
+    Ok(grid
+        .points()
+        .map(|x| {
+            let time = interval.map(|dt| {
+                let seconds = x * dt;
+                match start_time {
+                    Some(t0) => SampleTime::Absolute(t0 + Duration::nanoseconds((seconds * 1e9).round() as i64)),
+                    None => SampleTime::Elapsed(seconds),
+                }
+            });
+            ExpandedSample { x, time, value: file.payload.evaluate(x).unwrap_or(f64::NAN) }
+        })
+        .collect())
+}
+
+/// Format samples as CSV (`sample,time,value`; time only when known)
+pub fn to_csv(samples: &[ExpandedSample]) -> String {
+    let has_time = samples.iter().any(|s| s.time.is_some());
+    let mut out = String::from(if has_time { "sample,time,value\n" } else { "sample,value\n" });
+    for s in samples {
+        let _ = write!(out, "{}", s.x);
+        if has_time {
+            match &s.time {
+                Some(SampleTime::Absolute(t)) => {
+                    let _ = write!(out, ",{}", t.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true));
+                }
+                Some(SampleTime::Elapsed(secs)) => {
+                    let _ = write!(out, ",{}", secs);
+                }
+                None => out.push(','),
+            }
+        }
+        let _ = writeln!(out, ",{}", s.value);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::alice::AliceFileBuilder;
+
+    #[test]
+    fn test_expand_default_grid_with_time() {
+        let file = AliceFileBuilder::polynomial(vec![1.0, 2.0], 3)
+            .timestamp("2025-01-30T12:00:00Z")
+            .sample_interval(30.0)
+            .build()
+            .unwrap();
+        let grid = SampleGrid::for_payload(&file.payload).unwrap();
+        let rows = expand(&file, &grid).unwrap();
+        assert_eq!(rows.iter().map(|r| r.value).collect::<Vec<_>>(), vec![1.0, 3.0, 5.0]);
+        assert_eq!(
+            to_csv(&rows),
+            "sample,time,value\n\
+             0,2025-01-30T12:00:00Z,1\n\
+             1,2025-01-30T12:00:30Z,3\n\
+             2,2025-01-30T12:01:00Z,5\n"
+        );
+    }
+
+    #[test]
+    fn test_expand_range_without_timestamp() {
+        let file = AliceFileBuilder::from_linear(1 << 16, 0, 100).sample_interval(0.5).build().unwrap();
+        let grid = SampleGrid::parse_range("10..11", 0.5).unwrap();
+        let rows = expand(&file, &grid).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].time, Some(SampleTime::Elapsed(5.25)));
+        assert_eq!(to_csv(&rows[..1]), "sample,time,value\n10,5,10\n");
+
+        let plain = AliceFileBuilder::from_linear(1 << 16, 0, 4).build().unwrap();
+        let grid = SampleGrid::for_payload(&plain.payload).unwrap();
+        assert!(to_csv(&expand(&plain, &grid).unwrap()).starts_with("sample,value\n0,0\n"));
+    }
+
+    #[test]
+    fn test_expand_rejects_bad_input() {
+        assert!(SampleGrid::parse_range("5..1", 1.0).is_err());
+        assert!(SampleGrid::parse_range("0..10", 0.0).is_err());
+        assert!(SampleGrid::parse_range("0-10", 1.0).is_err());
+
+        let perlin = AliceFileBuilder::perlin(1, 2.0, 3).build().unwrap();
+        assert!(SampleGrid::for_payload(&perlin.payload).is_none());
+        let grid = SampleGrid::parse_range("0..4", 1.0).unwrap();
+        assert!(expand(&perlin, &grid).is_err());
+    }
+}
@@ -0,0 +1,247 @@
+//! Image fidelity metrics for equation-replaced textures
+//!
+//! Measures how closely decoded content reproduces the image it replaces:
+//! PSNR over the RGB channels and SSIM (Wang et al. 2004, 11x11 Gaussian
+//! window) over the luma. Images are RGBA8 of equal size; alpha is ignored.
+
+/// Peak value of an 8-bit channel
+const PEAK: f64 = 255.0;
+/// SSIM stabilizers: (K1 L)^2 and (K2 L)^2 with K1 = 0.01, K2 = 0.03
+const C1: f64 = (0.01 * PEAK) * (0.01 * PEAK);
+const C2: f64 = (0.03 * PEAK) * (0.03 * PEAK);
+/// SSIM window: radius 5 (11x11), sigma 1.5
+const SSIM_RADIUS: usize = 5;
+const SSIM_SIGMA: f64 = 1.5;
+
+/// How faithfully a test image reproduces a reference
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FidelityReport {
+    /// Peak signal-to-noise ratio in dB (infinite for identical images)
+    pub psnr: f64,
+    /// Mean structural similarity of the luma (1 = identical)
+    pub ssim: f64,
+    /// Largest absolute difference of any RGB channel
+    pub max_error: u8,
+}
+
+/// Compare two `width` x `height` RGBA8 images
+pub fn measure(width: u32, height: u32, reference: &[u8], test: &[u8]) -> FidelityReport {
+    let len = width as usize * height as usize * 4;
+    let (reference, test) = (&reference[..len], &test[..len]);
+    let max_error = rgb_pairs(reference, test).map(|(a, b)| a.abs_diff(b)).max().unwrap_or(0);
+    FidelityReport {
+        psnr: psnr(reference, test),
+        ssim: ssim(width, height, reference, test),
+        max_error,
+    }
+}
+
+/// Mean squared error over the RGB channels
+pub fn mse(reference: &[u8], test: &[u8]) -> f64 {
+    let (sum, count) = rgb_pairs(reference, test).fold((0.0, 0usize), |(sum, count), (a, b)| {
+        let d = a as f64 - b as f64;
+        (sum + d * d, count + 1)
+    });
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f64
+    }
+}
+
+/// Peak signal-to-noise ratio in dB over the RGB channels
+pub fn psnr(reference: &[u8], test: &[u8]) -> f64 {
+    let mse = mse(reference, test);
+    if mse == 0.0 {
+        f64::INFINITY
+    } else {
+        10.0 * (PEAK * PEAK / mse).log10()
+    }
+}
+
+/// Mean SSIM of the luma of two `width` x `height` RGBA8 images
+///
+/// Images smaller than the 11x11 window use the largest window that fits.
+pub fn ssim(width: u32, height: u32, reference: &[u8], test: &[u8]) -> f64 {
+    let (width, height) = (width as usize, height as usize);
+    if width == 0 || height == 0 {
+        return 1.0;
+    }
+    let x = luma(reference, width * height);
+    let y = luma(test, width * height);
+    let product = |a: &[f64], b: &[f64]| a.iter().zip(b).map(|(a, b)| a * b).collect::<Vec<_>>();
+
+    let radius = SSIM_RADIUS.min((width.min(height) - 1) / 2);
+    let kernel = gaussian_kernel(radius, SSIM_SIGMA);
+    let blur = |plane: &[f64]| blur_valid(plane, width, height, &kernel);
+    let (mu_x, mu_y) = (blur(&x), blur(&y));
+    let (xx, yy, xy) = (blur(&product(&x, &x)), blur(&product(&y, &y)), blur(&product(&x, &y)));
+
+    let total: f64 = (0..mu_x.len())
+        .map(|i| {
+            let (mx, my) = (mu_x[i], mu_y[i]);
+            let var_x = xx[i] - mx * mx;
+            let var_y = yy[i] - my * my;
+            let cov = xy[i] - mx * my;
+            ((2.0 * mx * my + C1) * (2.0 * cov + C2)) / ((mx * mx + my * my + C1) * (var_x + var_y + C2))
+        })
+        .sum();
+    total / mu_x.len() as f64
+}
+
+/// Nearest-neighbour resample of an RGBA8 image to `out_width` x `out_height`
+pub fn resample(width: u32, height: u32, data: &[u8], out_width: u32, out_height: u32) -> Vec<u8> {
+    if (width, height) == (out_width, out_height) {
+        return data[..width as usize * height as usize * 4].to_vec();
+    }
+    let mut pixels = Vec::with_capacity(out_width as usize * out_height as usize * 4);
+    for y in 0..out_height as u64 {
+        let sy = (y * height as u64 / out_height as u64) as usize;
+        for x in 0..out_width as u64 {
+            let sx = (x * width as u64 / out_width as u64) as usize;
+            let i = (sy * width as usize + sx) * 4;
+            pixels.extend_from_slice(&data[i..i + 4]);
+        }
+    }
+    pixels
+}
+
+/// RGB channel pairs of two RGBA8 images
+fn rgb_pairs<'a>(reference: &'a [u8], test: &'a [u8]) -> impl Iterator<Item = (u8, u8)> + 'a {
+    reference
+        .chunks_exact(4)
+        .zip(test.chunks_exact(4))
+        .flat_map(|(a, b)| (0..3).map(move |c| (a[c], b[c])))
+}
+
+/// BT.601 luma of the first `count` pixels
+fn luma(data: &[u8], count: usize) -> Vec<f64> {
+    data.chunks_exact(4)
+        .take(count)
+        .map(|p| 0.299 * p[0] as f64 + 0.587 * p[1] as f64 + 0.114 * p[2] as f64)
+        .collect()
+}
+
+/// Normalized 1D Gaussian of `2 * radius + 1` taps
+fn gaussian_kernel(radius: usize, sigma: f64) -> Vec<f64> {
+    let taps: Vec<f64> = (0..=2 * radius)
+        .map(|i| {
+            let d = i as f64 - radius as f64;
+            (-d * d / (2.0 * sigma * sigma)).exp()
+        })
+        .collect();
+    let sum: f64 = taps.iter().sum();
+    taps.into_iter().map(|t| t / sum).collect()
+}
+
+/// Separable convolution keeping only positions where the window fits
+fn blur_valid(plane: &[f64], width: usize, height: usize, kernel: &[f64]) -> Vec<f64> {
+    let taps = kernel.len();
+    let (out_width, out_height) = (width + 1 - taps, height + 1 - taps);
+
+    let mut rows = Vec::with_capacity(out_width * height);
+    for row in plane.chunks_exact(width) {
+        rows.extend(row.windows(taps).map(|w| w.iter().zip(kernel).map(|(v, k)| v * k).sum::<f64>()));
+    }
+
+    let mut out = Vec::with_capacity(out_width * out_height);
+    for y in 0..out_height {
+        for x in 0..out_width {
+            out.push(kernel.iter().enumerate().map(|(k, weight)| rows[(y + k) * out_width + x] * weight).sum());
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Horizontal gradient with a bright square in the middle
+    fn test_image(width: u32, height: u32) -> Vec<u8> {
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .flat_map(|(x, y)| {
+                let v = (x * 200 / width) as u8;
+                let square = (width / 4..3 * width / 4).contains(&x) && (height / 4..3 * height / 4).contains(&y);
+                let v = if square { v / 4 + 180 } else { v };
+                [v, v / 2, 255 - v, 255]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_identical_images() {
+        let image = test_image(32, 24);
+        let report = measure(32, 24, &image, &image);
+        assert_eq!(report.psnr, f64::INFINITY);
+        assert!((report.ssim - 1.0).abs() < 1e-9);
+        assert_eq!(report.max_error, 0);
+    }
+
+    #[test]
+    fn test_psnr_of_known_error() {
+        let reference = test_image(16, 16);
+        // +10 on every RGB channel (alpha differences are ignored)
+        let test: Vec<u8> = reference.chunks(4).flat_map(|p| [p[0] + 10, p[1] + 10, p[2] - 10, 0]).collect();
+
+        assert!((mse(&reference, &test) - 100.0).abs() < 1e-9);
+        let expected = 10.0 * (255.0f64 * 255.0 / 100.0).log10();
+        assert!((psnr(&reference, &test) - expected).abs() < 1e-9);
+        assert_eq!(measure(16, 16, &reference, &test).max_error, 10);
+    }
+
+    #[test]
+    fn test_ssim_penalizes_structure_loss() {
+        let (width, height) = (40, 32);
+        let reference = test_image(width, height);
+
+        // Uniform shift: the structure survives
+        let brighter: Vec<u8> = reference.iter().map(|v| v.saturating_add(8)).collect();
+        // Checkerboard noise of similar energy: the structure is damaged
+        let noisy: Vec<u8> = reference
+            .chunks(4)
+            .enumerate()
+            .flat_map(|(i, p)| {
+                let (x, y) = (i % width as usize, i / width as usize);
+                let n = if (x + y) % 2 == 0 { 8 } else { -8i16 };
+                let c = |v: u8| (v as i16 + n).clamp(0, 255) as u8;
+                [c(p[0]), c(p[1]), c(p[2]), p[3]]
+            })
+            .collect();
+
+        let shifted = ssim(width, height, &reference, &brighter);
+        let damaged = ssim(width, height, &reference, &noisy);
+        assert!(shifted > 0.99, "shifted {}", shifted);
+        assert!(damaged < shifted, "damaged {} shifted {}", damaged, shifted);
+    }
+
+    #[test]
+    fn test_ssim_of_flat_images() {
+        // No variance: SSIM reduces to the luminance term
+        let gray = |v: u8| vec![v; 12 * 12 * 4];
+        let expected = (2.0 * 100.0 * 110.0 + C1) / (100.0f64 * 100.0 + 110.0 * 110.0 + C1);
+        let value = ssim(12, 12, &gray(100), &gray(110));
+        assert!((value - expected).abs() < 1e-6, "{} vs {}", value, expected);
+    }
+
+    #[test]
+    fn test_small_images() {
+        // Smaller than the 11x11 window
+        let image = test_image(4, 3);
+        assert!((ssim(4, 3, &image, &image) - 1.0).abs() < 1e-9);
+        assert!((ssim(1, 1, &[10, 20, 30, 255], &[10, 20, 30, 255]) - 1.0).abs() < 1e-9);
+        assert_eq!(ssim(0, 0, &[], &[]), 1.0);
+    }
+
+    #[test]
+    fn test_resample() {
+        let data: Vec<u8> = (0..4 * 2).flat_map(|i| [i as u8, 0, 0, 255]).collect();
+        assert_eq!(resample(4, 2, &data, 4, 2), data);
+
+        let reds: Vec<u8> = resample(4, 2, &data, 2, 1).chunks(4).map(|p| p[0]).collect();
+        assert_eq!(reds, vec![0, 2]);
+        let reds: Vec<u8> = resample(4, 2, &data, 8, 2).chunks(4).map(|p| p[0]).collect();
+        assert_eq!(reds, vec![0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7]);
+    }
+}
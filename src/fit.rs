@@ -0,0 +1,404 @@
+//! Least-squares fitting of .alice models to sample data
+//!
+//! "Store equations, not pixels": turns a column of samples into a Linear,
+//! Polynomial or Fourier payload plus the residual statistics that say how
+//! faithful the equation is. Samples are indexed 0, 1, 2, … so fitted
+//! models share the sample-position domain of hand-written .alice files.
+
+use crate::decoder::alice::{
+    AliceFileBuilder, AlicePayload, FourierPayload, FourierTerm, LinearPayload,
+    PolynomialCoefficients, PolynomialPayload,
+};
+pub use crate::decoder::alice::{FitStats, META_MAX_ABS_ERROR, META_MODEL, META_RMSE};
+use anyhow::{bail, Context, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// Highest polynomial degree accepted
+pub const MAX_DEGREE: usize = 10;
+/// Highest Fourier harmonic accepted (plus the DC term, filling the
+/// renderer's term slots)
+pub const MAX_HARMONICS: usize = FourierPayload::MAX_TERMS - 1;
+
+/// Model family to fit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FitModel {
+    Linear,
+    Polynomial(usize),
+    Fourier(usize),
+    /// Smallest model within the error budget
+    Auto,
+}
+
+impl FromStr for FitModel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, arg) = match s.split_once(':') {
+            Some((name, arg)) => (name, Some(arg)),
+            None => (s, None),
+        };
+        let order = |max: usize| -> Result<usize> {
+            let n: usize = arg
+                .with_context(|| format!("model '{}' needs an order, e.g. {}:3", name, name))?
+                .parse()
+                .with_context(|| format!("invalid order in '{}'", s))?;
+            if n == 0 || n > max {
+                bail!("order for '{}' must be 1..={}", name, max);
+            }
+            Ok(n)
+        };
+        match name {
+            "linear" => Ok(Self::Linear),
+            "polynomial" | "poly" => Ok(Self::Polynomial(order(MAX_DEGREE)?)),
+            "fourier" => Ok(Self::Fourier(order(MAX_HARMONICS)?)),
+            "auto" => Ok(Self::Auto),
+            _ => bail!("unknown model '{}' (expected linear, polynomial:N, fourier:K or auto)", s),
+        }
+    }
+}
+
+impl fmt::Display for FitModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Linear => write!(f, "linear"),
+            Self::Polynomial(n) => write!(f, "polynomial:{}", n),
+            Self::Fourier(k) => write!(f, "fourier:{}", k),
+            Self::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+impl FitStats {
+    /// Compare `payload` against `samples` at positions 0, 1, 2, …
+    pub fn measure(payload: &AlicePayload, samples: &[f64]) -> Self {
+        let mut sum_sq = 0.0;
+        let mut max_abs_error: f64 = 0.0;
+        for (i, &y) in samples.iter().enumerate() {
+            let r = payload.evaluate(i as f64).unwrap_or(f64::NAN) - y;
+            sum_sq += r * r;
+            max_abs_error = max_abs_error.max(r.abs());
+        }
+        Self {
+            rmse: (sum_sq / samples.len().max(1) as f64).sqrt(),
+            max_abs_error,
+        }
+    }
+}
+
+/// A fitted payload and how well it reproduces the samples
+#[derive(Debug, Clone)]
+pub struct Fit {
+    pub model: FitModel,
+    pub payload: AlicePayload,
+    pub stats: FitStats,
+}
+
+impl Fit {
+    fn new(model: FitModel, payload: AlicePayload, samples: &[f64]) -> Self {
+        let stats = FitStats::measure(&payload, samples);
+        Self { model, payload, stats }
+    }
+
+    /// Builder for the .alice file, with the fit recorded in metadata
+    pub fn builder(&self) -> AliceFileBuilder {
+        AliceFileBuilder::from_payload(self.payload.clone())
+            .original_size(self.sample_count() as u64 * 4) // 4 bytes per sample
+            .custom(META_MODEL, self.model.to_string().into())
+            .custom(META_RMSE, self.stats.rmse.into())
+            .custom(META_MAX_ABS_ERROR, self.stats.max_abs_error.into())
+    }
+
+    fn sample_count(&self) -> u32 {
+        match &self.payload {
+            AlicePayload::Linear(p) => p.sample_count,
+            AlicePayload::Polynomial(p) => p.sample_count,
+            AlicePayload::Fourier(p) => p.sample_count,
+            _ => 0,
+        }
+    }
+}
+
+/// Read samples from CSV text
+///
+/// Takes the last column of each row; blank lines, `#` comments and a
+/// non-numeric header row are skipped.
+pub fn read_csv(text: &str) -> Result<Vec<f64>> {
+    let mut samples = Vec::new();
+    for (line_no, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let field = line.rsplit(',').next().unwrap_or_default().trim();
+        match field.parse::<f64>() {
+            Ok(v) if v.is_finite() => samples.push(v),
+            _ if samples.is_empty() && line_no == 0 => {} // header row
+            _ => bail!("line {}: '{}' is not a number", line_no + 1, field),
+        }
+    }
+    Ok(samples)
+}
+
+/// Fit `model` to `samples`; `max_error` is the absolute error budget used
+/// by [`FitModel::Auto`]
+pub fn fit(samples: &[f64], model: FitModel, max_error: f64) -> Result<Fit> {
+    match model {
+        FitModel::Linear => fit_linear(samples),
+        FitModel::Polynomial(n) => fit_polynomial(samples, n),
+        FitModel::Fourier(k) => fit_fourier(samples, k),
+        FitModel::Auto => fit_auto(samples, max_error),
+    }
+}
+
+/// Round `value` to Q16.16; `None` when it does not fit an i32
+pub fn to_q16(value: f64) -> Option<i32> {
+    let q = (value * 65536.0).round();
+    if q.is_nan() || q < i32::MIN as f64 || q > i32::MAX as f64 {
+        return None;
+    }
+    Some(q as i32)
+}
+
+/// Straight line stored as Q16.16 (ALICE-Edge linear model)
+pub fn fit_linear(samples: &[f64]) -> Result<Fit> {
+    let coefs = polynomial_coefficients(samples, 1)?;
+    let q16 = |v: f64, name: &str| -> Result<i32> {
+        match to_q16(v) {
+            Some(q) => Ok(q),
+            None => bail!("{} {} exceeds the Q16.16 range; use --model polynomial:1", name, v),
+        }
+    };
+    let payload = AlicePayload::Linear(LinearPayload {
+        slope_q16: q16(coefs[1], "slope")?,
+        intercept_q16: q16(coefs[0], "intercept")?,
+        sample_count: samples.len() as u32,
+    });
+    Ok(Fit::new(FitModel::Linear, payload, samples))
+}
+
+/// Polynomial of the given degree with f64 coefficients
+pub fn fit_polynomial(samples: &[f64], degree: usize) -> Result<Fit> {
+    let coefs = polynomial_coefficients(samples, degree)?;
+    let payload = AlicePayload::Polynomial(PolynomialPayload {
+        coefficients: PolynomialCoefficients::F64(coefs),
+        sample_count: samples.len() as u32,
+    });
+    Ok(Fit::new(FitModel::Polynomial(degree), payload, samples))
+}
+
+/// Fourier series with harmonics 1..=k over one period of the whole series
+pub fn fit_fourier(samples: &[f64], harmonics: usize) -> Result<Fit> {
+    let n = samples.len();
+    check_samples(n, 2 * harmonics + 1)?;
+    let period = n as f64;
+    let w = std::f64::consts::TAU / period;
+
+    // Columns: 1, cos(kwx), sin(kwx) for k = 1..=harmonics
+    let rows: Vec<Vec<f64>> = (0..n)
+        .map(|x| {
+            let mut row = vec![1.0];
+            for k in 1..=harmonics {
+                let a = w * k as f64 * x as f64;
+                row.push(a.cos());
+                row.push(a.sin());
+            }
+            row
+        })
+        .collect();
+    let sol = least_squares(rows, samples.to_vec()).context("samples do not determine a Fourier fit")?;
+
+    // a·cos + b·sin = A·cos(θ + φ) with A = √(a² + b²), φ = atan2(-b, a)
+    let mut terms = vec![FourierTerm { frequency: 0, amplitude: sol[0] as f32, phase: 0.0 }];
+    for k in 1..=harmonics {
+        let (a, b) = (sol[2 * k - 1], sol[2 * k]);
+        terms.push(FourierTerm {
+            frequency: k as u32,
+            amplitude: a.hypot(b) as f32,
+            phase: (-b).atan2(a) as f32,
+        });
+    }
+    let payload = AlicePayload::Fourier(FourierPayload { period, sample_count: n as u32, terms });
+    Ok(Fit::new(FitModel::Fourier(harmonics), payload, samples))
+}
+
+/// Smallest encoded model whose max abs error is within `max_error`,
+/// falling back to the most accurate candidate when none is
+pub fn fit_auto(samples: &[f64], max_error: f64) -> Result<Fit> {
+    let candidates = std::iter::once(FitModel::Linear)
+        .chain((2..=6).map(FitModel::Polynomial))
+        .chain((1..=8).map(FitModel::Fourier));
+
+    let fits: Vec<Fit> = candidates.filter_map(|m| fit(samples, m, max_error).ok()).collect();
+    let size = |f: &Fit| f.payload.to_bytes().len();
+
+    fits.iter()
+        .filter(|f| f.stats.max_abs_error <= max_error)
+        .min_by(|a, b| size(a).cmp(&size(b)).then(a.stats.rmse.total_cmp(&b.stats.rmse)))
+        .or_else(|| fits.iter().min_by(|a, b| a.stats.max_abs_error.total_cmp(&b.stats.max_abs_error)))
+        .cloned()
+        .context("no model could be fitted to the samples")
+}
+
+fn check_samples(n: usize, unknowns: usize) -> Result<()> {
+    if n < unknowns {
+        bail!("need at least {} samples, got {}", unknowns, n);
+    }
+    Ok(())
+}
+
+/// Ascending coefficients c0..=c_degree for positions 0, 1, 2, …
+fn polynomial_coefficients(samples: &[f64], degree: usize) -> Result<Vec<f64>> {
+    let n = samples.len();
+    check_samples(n, degree + 1)?;
+
+    // Fit on t = x / scale ∈ [0, 1] for conditioning, then undo the scaling
+    let scale = (n - 1).max(1) as f64;
+    let rows: Vec<Vec<f64>> = (0..n)
+        .map(|x| {
+            let t = x as f64 / scale;
+            (0..=degree).map(|i| t.powi(i as i32)).collect()
+        })
+        .collect();
+    let sol = least_squares(rows, samples.to_vec()).context("samples do not determine a polynomial fit")?;
+    Ok(sol.iter().enumerate().map(|(i, a)| a / scale.powi(i as i32)).collect())
+}
+
+/// Solve min ‖Ax − b‖ by Householder QR; `None` if A is rank deficient
+fn least_squares(rows: Vec<Vec<f64>>, b: Vec<f64>) -> Option<Vec<f64>> {
+    let m = rows.len();
+    let n = rows.first()?.len();
+    if m < n {
+        return None;
+    }
+
+    // Augmented [A | b] so each reflection updates b alongside A
+    let mut a: Vec<Vec<f64>> = rows
+        .into_iter()
+        .zip(b)
+        .map(|(mut row, y)| {
+            row.push(y);
+            row
+        })
+        .collect();
+
+    for j in 0..n {
+        let norm = a[j..].iter().map(|row| row[j] * row[j]).sum::<f64>().sqrt();
+        if norm == 0.0 {
+            return None;
+        }
+        let alpha = if a[j][j] > 0.0 { -norm } else { norm };
+        let mut v: Vec<f64> = a[j..].iter().map(|row| row[j]).collect();
+        v[0] -= alpha;
+        let v_norm_sq: f64 = v.iter().map(|x| x * x).sum();
+        if v_norm_sq == 0.0 {
+            continue;
+        }
+
+        for k in j..=n {
+            let s: f64 = a[j..].iter().zip(&v).map(|(row, vi)| vi * row[k]).sum();
+            let f = 2.0 * s / v_norm_sq;
+            for (row, vi) in a[j..].iter_mut().zip(&v) {
+                row[k] -= f * vi;
+            }
+        }
+    }
+
+    // Back-substitute R·x = Qᵀb
+    let max_diag = (0..n).map(|j| a[j][j].abs()).fold(0.0, f64::max);
+    let mut x = vec![0.0; n];
+    for j in (0..n).rev() {
+        if a[j][j].abs() <= max_diag * 1e-12 {
+            return None;
+        }
+        let s: f64 = (j + 1..n).map(|k| a[j][k] * x[k]).sum();
+        x[j] = (a[j][n] - s) / a[j][j];
+    }
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_parse() {
+        assert_eq!("linear".parse::<FitModel>().unwrap(), FitModel::Linear);
+        assert_eq!("polynomial:3".parse::<FitModel>().unwrap(), FitModel::Polynomial(3));
+        assert_eq!("fourier:4".parse::<FitModel>().unwrap(), FitModel::Fourier(4));
+        assert_eq!("auto".parse::<FitModel>().unwrap(), FitModel::Auto);
+        assert!("polynomial".parse::<FitModel>().is_err());
+        assert!("polynomial:99".parse::<FitModel>().is_err());
+        assert!("spline:2".parse::<FitModel>().is_err());
+        assert_eq!(FitModel::Polynomial(3).to_string(), "polynomial:3");
+    }
+
+    #[test]
+    fn test_read_csv() {
+        let text = "time,temp\n0,20.5\n1,21.0\n\n# gap\n2,21.5\n";
+        assert_eq!(read_csv(text).unwrap(), vec![20.5, 21.0, 21.5]);
+        assert_eq!(read_csv("1\n2\n3").unwrap(), vec![1.0, 2.0, 3.0]);
+        assert!(read_csv("1\nx\n").is_err());
+    }
+
+    #[test]
+    fn test_fit_exact_models() {
+        let line: Vec<f64> = (0..100).map(|x| 25.0 + 0.5 * x as f64).collect();
+        let f = fit_linear(&line).unwrap();
+        assert!(f.stats.max_abs_error < 1e-3, "{:?}", f.stats);
+
+        let cubic: Vec<f64> = (0..200).map(|x| {
+            let x = x as f64;
+            1.0 - 0.2 * x + 0.003 * x * x - 1e-5 * x * x * x
+        }).collect();
+        let f = fit_polynomial(&cubic, 3).unwrap();
+        assert!(f.stats.max_abs_error < 1e-6, "{:?}", f.stats);
+
+        let n = 240;
+        let wave: Vec<f64> = (0..n).map(|x| {
+            let t = std::f64::consts::TAU * x as f64 / n as f64;
+            10.0 + 3.0 * (2.0 * t + 0.4).cos()
+        }).collect();
+        let f = fit_fourier(&wave, 2).unwrap();
+        assert!(f.stats.max_abs_error < 1e-4, "{:?}", f.stats);
+        let AlicePayload::Fourier(p) = &f.payload else { panic!("expected Fourier") };
+        assert!((p.terms[2].amplitude - 3.0).abs() < 1e-4);
+        assert!((p.terms[2].phase - 0.4).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_fit_auto_prefers_small_models() {
+        let line: Vec<f64> = (0..50).map(|x| 3.0 - 0.25 * x as f64).collect();
+        assert_eq!(fit_auto(&line, 0.01).unwrap().model, FitModel::Linear);
+
+        let quad: Vec<f64> = (0..50).map(|x| (x as f64 - 20.0).powi(2) * 0.1).collect();
+        assert_eq!(fit_auto(&quad, 0.01).unwrap().model, FitModel::Polynomial(2));
+    }
+
+    #[test]
+    fn test_stats_roundtrip_through_metadata() {
+        let samples = [1.0, 2.0, 2.5, 4.0, 5.5];
+        let f = fit_linear(&samples).unwrap();
+        let file = f.builder().build().unwrap();
+        assert_eq!(file.header.original_size, 20);
+        let stats = FitStats::from_metadata(&file.metadata).unwrap();
+        assert_eq!(stats, f.stats);
+        assert_eq!(file.metadata.custom[META_MODEL], "linear");
+    }
+
+    #[test]
+    fn test_to_q16() {
+        assert_eq!(to_q16(1.5), Some(98304));
+        assert_eq!(to_q16(-0.00001), Some(-1));
+        assert_eq!(to_q16(0.99999), Some(65535));
+        assert_eq!(to_q16(40000.0), None);
+        assert_eq!(to_q16(f64::NAN), None);
+    }
+
+    #[test]
+    fn test_too_few_samples() {
+        assert!(fit_polynomial(&[1.0, 2.0], 3).is_err());
+        assert!(fit_fourier(&[1.0; 4], 2).is_err());
+        assert!(fit_linear(&[5.0; 10]).is_ok());
+    }
+}
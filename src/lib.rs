@@ -22,7 +22,11 @@
 //! ```
 
 pub mod app;
+pub mod convert;
 pub mod decoder;
+pub mod expand;
+pub mod fidelity;
+pub mod fit;
 pub mod renderer;
 pub mod ui;
 #[cfg(feature = "analytics")]
@@ -36,7 +40,7 @@ use anyhow::Result;
 use winit::event_loop::{ControlFlow, EventLoop};
 
 // Re-export key types
-pub use app::{App, FrameStats, ViewerConfig, ViewerState, XRayType};
+pub use app::{App, CompareView, FrameStats, RasterFilter, ViewerConfig, ViewerState, XRayType};
 pub use decoder::Decoder;
 
 /// Launch the ALICE-View window with the given configuration
@@ -130,4 +134,13 @@ impl ViewerConfig {
             ..Default::default()
         }
     }
+
+    /// Create config for a live ASP stream received on `addr` (TCP and UDP)
+    pub fn for_asp_stream(addr: &str) -> Self {
+        Self {
+            title: format!("ALICE-View - Live {}", addr),
+            asp_listen: Some(addr.to_string()),
+            ..Default::default()
+        }
+    }
 }
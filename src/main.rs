@@ -5,6 +5,7 @@
 
 mod app;
 mod decoder;
+mod fidelity;
 mod renderer;
 mod ui;
 
@@ -47,12 +48,20 @@ fn print_usage() {
     eprintln!();
     eprintln!("Arguments:");
     eprintln!("  [FILE]    SDF file to open (.json, .asdf, .asdf.json, .alice, .alz)");
+    eprintln!("            or a video: animated .gif, a directory of numbered PNG/JPEG");
+    eprintln!("            frames, or a frame pattern such as frames/shot_%04d.png");
     eprintln!();
     eprintln!("Options:");
     eprintln!("  --last         Reopen last opened file");
     eprintln!("  --width <N>    Window width (default: 1280)");
     eprintln!("  --height <N>   Window height (default: 720)");
     eprintln!("  --stats        Show performance stats on startup");
+    eprintln!("  --asp-listen <ADDR>");
+    eprintln!("                 Show a live ASP stream sent to ADDR over TCP or UDP");
+    eprintln!("                 (e.g. 127.0.0.1:9000)");
+    eprintln!("  --compare <IMAGE>");
+    eprintln!("                 Compare the .alice/.alz FILE with the image it replaces");
+    eprintln!("                 (PSNR, SSIM, split and difference views)");
     eprintln!("  --help, -h     Show this help message");
     eprintln!("  --version, -V  Show version");
     eprintln!();
@@ -62,11 +71,18 @@ fn print_usage() {
     eprintln!("  Scroll       Dolly (zoom)");
     eprintln!("  R            Reset camera");
     eprintln!("  F2           Toggle stats");
+    eprintln!("  Space        Play / pause (.asp, video)");
+    eprintln!("  ←/→          Step one frame");
+    eprintln!("  - / =        Video frame rate down / up");
+    eprintln!("  T            Plot x axis: time / sample index");
+    eprintln!("  V            Compare view: split / difference / off");
+    eprintln!("  [ / ]        Move the comparison split");
     eprintln!("  F11          Fullscreen");
     eprintln!("  F12          Screenshot");
     eprintln!("  Ctrl+O       Open file");
     eprintln!();
     eprintln!("Drag & drop .json / .asdf files onto the window to view.");
+    eprintln!("Shift + drop an image to compare it with the loaded .alice / .alz file.");
 }
 
 fn main() -> Result<()> {
@@ -84,6 +100,8 @@ fn main() -> Result<()> {
     let mut height: u32 = 720;
     let mut show_stats = false;
     let mut use_last = false;
+    let mut asp_listen: Option<String> = None;
+    let mut compare_with: Option<String> = None;
 
     let mut i = 1;
     while i < args.len() {
@@ -104,6 +122,26 @@ fn main() -> Result<()> {
                     width = val.parse().unwrap_or(1280);
                 }
             }
+            "--asp-listen" => {
+                i += 1;
+                match args.get(i) {
+                    Some(addr) => asp_listen = Some(addr.clone()),
+                    None => {
+                        eprintln!("--asp-listen requires an address");
+                        std::process::exit(1);
+                    }
+                }
+            }
+            "--compare" => {
+                i += 1;
+                match args.get(i) {
+                    Some(path) => compare_with = Some(path.clone()),
+                    None => {
+                        eprintln!("--compare requires an image path");
+                        std::process::exit(1);
+                    }
+                }
+            }
             "--height" => {
                 i += 1;
                 if let Some(val) = args.get(i) {
@@ -148,6 +186,8 @@ fn main() -> Result<()> {
         height,
         show_stats,
         initial_file: file_path,
+        asp_listen,
+        compare_with,
         ..Default::default()
     };
     let mut app = app::App::with_config(config);
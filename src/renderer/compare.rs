@@ -0,0 +1,106 @@
+//! Fidelity comparison against a paired reference image
+//!
+//! When a reference is paired, the decoded content is rendered once at the
+//! reference resolution (the same shader and view as on screen, minus the
+//! vignette) and read back. The raster pipeline shows that image next to the
+//! reference, and PSNR / SSIM are measured from it on a background thread.
+
+use super::ProceduralPipeline;
+use crate::decoder::{Decoder, ProceduralContent};
+use crate::fidelity::{self, FidelityReport};
+use anyhow::Result;
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Arc;
+use wgpu::*;
+
+/// Decoded content at reference resolution and its fidelity metrics
+#[derive(Default)]
+pub struct Comparison {
+    /// Reference the test image was made for
+    reference: Option<Arc<Vec<u8>>>,
+    /// Decoded content at reference resolution (RGBA8)
+    test: Option<Arc<Vec<u8>>>,
+    /// Metrics being measured in the background
+    pending: Option<Receiver<FidelityReport>>,
+    report: Option<FidelityReport>,
+}
+
+impl Comparison {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Re-render the decoded content when the paired reference changes
+    ///
+    /// Returns the test image to show against the reference, if any.
+    pub fn update(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        pipeline: &ProceduralPipeline,
+        decoder: &Decoder,
+    ) -> Option<&Arc<Vec<u8>>> {
+        let Some(ProceduralContent::Raster { width, height, data }) = decoder.reference() else {
+            *self = Self::default();
+            return None;
+        };
+
+        if !self.reference.as_ref().is_some_and(|reference| Arc::ptr_eq(reference, data)) {
+            *self = Self { reference: Some(data.clone()), ..Self::default() };
+            match Self::render_test(device, queue, pipeline, decoder, *width, *height) {
+                Ok(test) => {
+                    let test = Arc::new(test);
+                    self.pending = Some(Self::spawn_measure(*width, *height, data.clone(), test.clone()));
+                    self.test = Some(test);
+                }
+                Err(e) => tracing::error!("Fidelity comparison failed: {:#}", e),
+            }
+        }
+
+        if let Some(report) = self.pending.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            tracing::info!("Fidelity: PSNR {:.2} dB, SSIM {:.4}", report.psnr, report.ssim);
+            self.report = Some(report);
+            self.pending = None;
+        }
+        self.test.as_ref()
+    }
+
+    /// Metrics of the current pairing (None while measuring)
+    pub fn report(&self) -> Option<FidelityReport> {
+        self.report
+    }
+
+    /// The decoded content as an RGBA8 image of the reference's size
+    fn render_test(
+        device: &Device,
+        queue: &Queue,
+        pipeline: &ProceduralPipeline,
+        decoder: &Decoder,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<u8>> {
+        match decoder.content() {
+            // Decompressed ALZ images are compared pixel for pixel
+            Some(ProceduralContent::Raster { width: w, height: h, data }) => {
+                Ok(fidelity::resample(*w, *h, data, width, height))
+            }
+            _ => pipeline.render_image(device, queue, decoder, width, height),
+        }
+    }
+
+    fn spawn_measure(
+        width: u32,
+        height: u32,
+        reference: Arc<Vec<u8>>,
+        test: Arc<Vec<u8>>,
+    ) -> Receiver<FidelityReport> {
+        let (tx, rx) = channel();
+        let spawned = std::thread::Builder::new().name("fidelity".into()).spawn(move || {
+            let _ = tx.send(fidelity::measure(width, height, &reference, &test));
+        });
+        if let Err(e) = spawned {
+            tracing::error!("Failed to start fidelity measurement: {}", e);
+        }
+        rx
+    }
+}
@@ -2,8 +2,12 @@
 
 mod pipeline;
 mod infinite_zoom;
+mod raster;
+mod compare;
 
 pub use pipeline::*;
+pub use raster::*;
+pub use compare::*;
 // infinite_zoom exports are public API for external consumers.
 #[allow(unused_imports)]
 pub use infinite_zoom::*;
@@ -26,6 +30,10 @@ pub struct Renderer {
     size: PhysicalSize<u32>,
     // 2D procedural pipeline
     procedural_pipeline: ProceduralPipeline,
+    // 2D textured quad for raster images
+    raster_pipeline: RasterPipeline,
+    // Decoded content against a paired reference image
+    comparison: Comparison,
     // 3D SDF raymarching pipeline
     sdf_pipeline: SdfPipeline,
     egui_renderer: egui_wgpu::Renderer,
@@ -98,6 +106,7 @@ impl Renderer {
 
         // Create both pipelines
         let procedural_pipeline = ProceduralPipeline::new(&device, surface_format);
+        let raster_pipeline = RasterPipeline::new(&device, surface_format);
         let sdf_pipeline = SdfPipeline::new(&device, surface_format);
 
         let egui_ctx = egui::Context::default();
@@ -118,6 +127,8 @@ impl Renderer {
             config,
             size,
             procedural_pipeline,
+            raster_pipeline,
+            comparison: Comparison::new(),
             sdf_pipeline,
             egui_renderer,
             egui_state,
@@ -269,9 +280,19 @@ impl Renderer {
         let resolution = [self.size.width as f32, self.size.height as f32];
 
         // Update appropriate pipeline uniforms based on render mode
+        let mut show_raster = false;
         match state.render_mode {
             RenderMode::Procedural2D => {
-                self.procedural_pipeline.update_uniforms(&self.queue, state, time, resolution);
+                // Offscreen render of a new pairing goes first: it has its own uniforms and submit
+                let comparison =
+                    self.comparison.update(&self.device, &self.queue, &self.procedural_pipeline, decoder);
+                show_raster =
+                    self.raster_pipeline.update(&self.device, &self.queue, state, decoder, comparison, resolution);
+                state.stats.fidelity = self.comparison.report();
+                if !show_raster {
+                    self.procedural_pipeline.update_corrections(&self.device, &self.queue, decoder);
+                    self.procedural_pipeline.update_uniforms(&self.queue, state, decoder, time, resolution);
+                }
             }
             RenderMode::Sdf3D => {
                 let scene_id = ui.sdf_scene_id();
@@ -297,6 +318,9 @@ impl Renderer {
 
             // Render with appropriate pipeline
             match state.render_mode {
+                RenderMode::Procedural2D if show_raster => {
+                    self.raster_pipeline.render(&mut render_pass);
+                }
                 RenderMode::Procedural2D => {
                     self.procedural_pipeline.render(&mut render_pass, state, decoder);
                 }
@@ -311,7 +335,7 @@ impl Renderer {
             pixels_per_point: 1.0,
         };
 
-        let full_output = ui.render(&self.egui_ctx, state);
+        let full_output = ui.render(&self.egui_ctx, state, resolution);
 
         let clipped_primitives = self.egui_ctx.tessellate(
             full_output.shapes,
@@ -1,17 +1,63 @@
 //! Procedural rendering pipeline
 
 use crate::app::ViewerState;
-use crate::decoder::Decoder;
+use crate::decoder::{
+    AspRoi, Decoder, FractalType, PlotFrame, ProceduralContent, VoronoiFeature, VoronoiMetric,
+};
+use anyhow::Result;
 use wgpu::*;
 
 /// Procedural rendering pipeline
 pub struct ProceduralPipeline {
     render_pipeline: RenderPipeline,
-    // Stored to allow future dynamic bind group rebuilds (e.g. texture updates).
-    #[allow(dead_code)]
     bind_group_layout: BindGroupLayout,
     uniform_buffer: Buffer,
     bind_group: BindGroup,
+    /// ASP residual corrections at frame resolution (1x1 when unused)
+    correction_texture: Texture,
+    /// Frame size and ROIs currently in `correction_texture`
+    corrections: Option<((u32, u32), Vec<AspRoi>)>,
+    /// Rectangles of `correction_texture` holding residuals; everything
+    /// else is zero
+    correction_rects: Vec<(Origin3d, Extent3d)>,
+    /// Color target format (also used for offscreen renders)
+    format: TextureFormat,
+}
+
+/// Maximum number of vec4 term slots (polynomial coefficients, Fourier terms,
+/// packed segments)
+pub const MAX_TERMS: usize = 16;
+
+/// Content type IDs understood by procedural.wgsl
+const CONTENT_PERLIN: u32 = 0;
+const CONTENT_POLYNOMIAL: u32 = 1;
+const CONTENT_FRACTAL: u32 = 2;
+// 3 = Gradient (shader-only demo field)
+const CONTENT_VORONOI: u32 = 4;
+const CONTENT_SINE: u32 = 5;
+const CONTENT_FOURIER: u32 = 6;
+const CONTENT_RASTER: u32 = 7;
+const CONTENT_SEGMENTED: u32 = 8;
+const CONTENT_COMPOSITE: u32 = 9;
+
+/// Darkening of the view corners (0 = none)
+const VIGNETTE: f32 = 0.5;
+
+/// Maximum number of composite layers evaluated per pixel
+pub const MAX_LAYERS: usize = 8;
+
+/// One composite layer; terms live in the shared `terms` array
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct LayerUniform {
+    content_type: u32,
+    blend: u32,             // 0=add, 1=multiply, 2=max, 3=domain warp
+    term_offset: u32,       // first slot in Uniforms::terms
+    term_count: u32,
+    params: [f32; 4],
+    center: [f32; 4],       // xy = fractal center, zw = Julia constant
+    seed: [u32; 4],
+    weight: [f32; 4],       // x = weight
 }
 
 /// Uniforms for procedural shaders
@@ -27,13 +73,227 @@ struct Uniforms {
     zoom: f32,              // offset 12 (align 4)
     pan: [f32; 2],          // offset 16 (align 8)
     content_type: u32,      // offset 24 (align 4)
-    param1: f32,            // offset 28
-    param2: f32,            // offset 32
-    param3: f32,            // offset 36
-    param4: f32,            // offset 40
-    _pad1: u32,             // offset 44 (padding to 48 bytes, 16-byte boundary)
+    term_count: u32,        // offset 28
+    params: [f32; 4],       // offset 32 (content-specific scalars)
+    center: [f32; 4],       // offset 48 (xy = f64 high part, zw = low part)
+    julia_c: [f32; 4],      // offset 64 (xy = Julia constant)
+    seed: [u32; 4],         // offset 80 (xy = u64 seed lo/hi)
+    terms: [[f32; 4]; MAX_TERMS], // offset 96 (coefficients / (freq, amp, phase))
+    layer_count: u32,       // offset 352
+    _pad: [u32; 3],
+    layers: [LayerUniform; MAX_LAYERS], // offset 368
+    band: [f32; 4],         // offset 1008 (x = max abs error, y = RMSE; 0 = no band)
+    correction: [f32; 4],   // offset 1024 (x = residuals present, y = outline ROIs, zw = frame size)
+    display: [f32; 4],      // offset 1040 (x = vignette strength)
+}
+
+/// Content-specific part of the procedural uniforms
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct ContentUniforms {
+    content_type: u32,
+    term_count: u32,
+    params: [f32; 4],
+    center: [f32; 4],
+    julia_c: [f32; 4],
+    seed: [u32; 4],
+    terms: [[f32; 4]; MAX_TERMS],
+    layer_count: u32,
+    layers: [LayerUniform; MAX_LAYERS],
+}
+
+impl Default for ContentUniforms {
+    /// Fallback when nothing is loaded: default Perlin field
+    fn default() -> Self {
+        Self {
+            content_type: CONTENT_PERLIN,
+            term_count: 0,
+            params: [10.0, 6.0, 0.5, 2.0], // scale, octaves, persistence, lacunarity
+            center: [0.0; 4],
+            julia_c: [0.0; 4],
+            seed: [0; 4],
+            terms: [[0.0; 4]; MAX_TERMS],
+            layer_count: 0,
+            layers: [LayerUniform::default(); MAX_LAYERS],
+        }
+    }
+}
+
+/// Split an f64 into (high, low) f32 parts so the shader can recover extra precision
+#[inline(always)]
+fn split_f64(value: f64) -> (f32, f32) {
+    let hi = value as f32;
+    let lo = (value - hi as f64) as f32;
+    (hi, lo)
 }
 
+impl ContentUniforms {
+    /// Number of leading `terms` slots used by this content
+    fn term_slots(&self) -> usize {
+        let count = self.term_count as usize;
+        match self.content_type {
+            CONTENT_POLYNOMIAL => count.div_ceil(4),
+            CONTENT_SINE | CONTENT_FOURIER => count,
+            CONTENT_SEGMENTED => {
+                let mut slot = 0;
+                for _ in 0..count {
+                    slot += 1 + (self.terms[slot][1] as usize).div_ceil(4);
+                }
+                slot
+            }
+            _ => 0,
+        }
+    }
+
+    /// Map decoded content into the shader uniform layout
+    fn from_content(content: Option<&ProceduralContent>) -> Self {
+        let mut u = Self::default();
+        let Some(content) = content else {
+            return u;
+        };
+
+        match content {
+            ProceduralContent::Perlin { seed, scale, octaves, persistence, lacunarity } => {
+                u.content_type = CONTENT_PERLIN;
+                u.params = [*scale, *octaves as f32, *persistence, *lacunarity];
+                u.seed = [*seed as u32, (*seed >> 32) as u32, 0, 0];
+            }
+            ProceduralContent::Polynomial { coefficients, .. } => {
+                u.content_type = CONTENT_POLYNOMIAL;
+                let count = coefficients.len().min(MAX_TERMS * 4);
+                for (i, c) in coefficients.iter().take(count).enumerate() {
+                    u.terms[i / 4][i % 4] = *c as f32;
+                }
+                u.term_count = count as u32;
+                // Sampled series are framed like segmented ones; params.x = 0
+                // keeps plain view units
+                u.params = content.plot_frame().map_or([0.0; 4], |frame| {
+                    [frame.domain_end as f32, frame.y_center as f32, frame.y_scale as f32, 0.0]
+                });
+            }
+            ProceduralContent::SineWave { components, offset } => {
+                u.content_type = CONTENT_SINE;
+                let count = components.len().min(MAX_TERMS);
+                for (slot, (freq, amp, phase)) in u.terms.iter_mut().zip(components.iter()) {
+                    *slot = [*freq, *amp, *phase, 0.0];
+                }
+                u.term_count = count as u32;
+                let swing: f32 = offset.abs() + components.iter().take(count).map(|(_, a, _)| a.abs()).sum::<f32>();
+                u.params = [*offset, 1.5 / swing.max(1e-6), 0.0, 0.0];
+            }
+            ProceduralContent::Fourier { coefficients, period } => {
+                u.content_type = CONTENT_FOURIER;
+                let count = coefficients.len().min(MAX_TERMS);
+                for (slot, (freq, amp, phase)) in u.terms.iter_mut().zip(coefficients.iter()) {
+                    *slot = [*freq as f32, *amp, *phase, 0.0];
+                }
+                u.term_count = count as u32;
+                // Vertical scale so the full swing fits the view
+                let swing: f32 = coefficients.iter().take(count).map(|(_, a, _)| a.abs()).sum();
+                u.params = [*period, 1.5 / swing.max(1e-6), 0.0, 0.0];
+            }
+            ProceduralContent::Segmented { segments, domain_end, value_range } => {
+                u.content_type = CONTENT_SEGMENTED;
+                // Each segment: header slot (start, coefficient count) followed
+                // by its coefficients packed 4 per slot. Segments that do not
+                // fit are dropped and params.w marks where the drawn ones end.
+                let mut slot = 0;
+                let mut uncovered = 0.0;
+                for (start, coefficients) in segments {
+                    let coef_slots = coefficients.len().div_ceil(4);
+                    if slot + 1 + coef_slots > MAX_TERMS {
+                        uncovered = (*start).max(1) as f32;
+                        break;
+                    }
+                    u.terms[slot] = [*start as f32, coefficients.len() as f32, 0.0, 0.0];
+                    for (i, c) in coefficients.iter().enumerate() {
+                        u.terms[slot + 1 + i / 4][i % 4] = *c as f32;
+                    }
+                    slot += 1 + coef_slots;
+                    u.term_count += 1;
+                }
+                let frame = PlotFrame::new(*domain_end, *value_range);
+                u.params = [frame.domain_end as f32, frame.y_center as f32, frame.y_scale as f32, uncovered];
+            }
+            ProceduralContent::Fractal { fractal_type, max_iterations, escape_radius, center, julia_c } => {
+                u.content_type = CONTENT_FRACTAL;
+                let type_id = match fractal_type {
+                    FractalType::Mandelbrot => 0.0,
+                    FractalType::Julia => 1.0,
+                    FractalType::BurningShip => 2.0,
+                    FractalType::Tricorn => 3.0,
+                };
+                u.params = [
+                    (*max_iterations).clamp(1, MAX_FRACTAL_ITERATIONS) as f32,
+                    *escape_radius,
+                    type_id,
+                    0.0,
+                ];
+                let (cx_hi, cx_lo) = split_f64(center.x);
+                let (cy_hi, cy_lo) = split_f64(center.y);
+                u.center = [cx_hi, cy_hi, cx_lo, cy_lo];
+                if let Some(c) = julia_c {
+                    u.julia_c = [c.x as f32, c.y as f32, 0.0, 0.0];
+                }
+            }
+            ProceduralContent::Voronoi { seed, cell_density, metric, feature, jitter } => {
+                u.content_type = CONTENT_VORONOI;
+                let metric_id = match metric {
+                    VoronoiMetric::Euclidean => 0.0,
+                    VoronoiMetric::Manhattan => 1.0,
+                    VoronoiMetric::Chebyshev => 2.0,
+                };
+                let feature_id = match feature {
+                    VoronoiFeature::F1 => 0.0,
+                    VoronoiFeature::F2 => 1.0,
+                    VoronoiFeature::F2MinusF1 => 2.0,
+                };
+                u.params = [*cell_density, jitter.clamp(0.0, 1.0), metric_id, feature_id];
+                u.seed = [*seed as u32, (*seed >> 32) as u32, 0, 0];
+            }
+            ProceduralContent::Composite { layers } => {
+                u.content_type = CONTENT_COMPOSITE;
+                // Layers share the term slots; ones that do not fit are dropped
+                // (reported by CompositePayload::layers_rendered)
+                let mut next_slot = 0;
+                for (blend, weight, child) in layers {
+                    if u.layer_count as usize == MAX_LAYERS {
+                        break;
+                    }
+                    if matches!(child, ProceduralContent::Composite { .. } | ProceduralContent::Raster { .. }) {
+                        continue;
+                    }
+                    let c = Self::from_content(Some(child));
+                    let slots = c.term_slots();
+                    if next_slot + slots > MAX_TERMS {
+                        continue;
+                    }
+                    u.terms[next_slot..next_slot + slots].copy_from_slice(&c.terms[..slots]);
+                    u.layers[u.layer_count as usize] = LayerUniform {
+                        content_type: c.content_type,
+                        blend: *blend as u32,
+                        term_offset: next_slot as u32,
+                        term_count: c.term_count,
+                        params: c.params,
+                        center: [c.center[0] + c.center[2], c.center[1] + c.center[3], c.julia_c[0], c.julia_c[1]],
+                        seed: c.seed,
+                        weight: [*weight, 0.0, 0.0, 0.0],
+                    };
+                    next_slot += slots;
+                    u.layer_count += 1;
+                }
+            }
+            ProceduralContent::Raster { .. } => {
+                u.content_type = CONTENT_RASTER;
+            }
+        }
+
+        u
+    }
+}
+
+/// Upper bound for fractal iterations evaluated per pixel
+const MAX_FRACTAL_ITERATIONS: u32 = 4096;
+
 impl ProceduralPipeline {
     pub fn new(device: &Device, format: TextureFormat) -> Self {
         // Shader module
@@ -45,16 +305,28 @@ impl ProceduralPipeline {
         // Bind group layout
         let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
             label: Some("Procedural Bind Group Layout"),
-            entries: &[BindGroupLayoutEntry {
-                binding: 0,
-                visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
-                ty: BindingType::Buffer {
-                    ty: BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
                 },
-                count: None,
-            }],
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+            ],
         });
 
         // Uniform buffer
@@ -66,14 +338,8 @@ impl ProceduralPipeline {
         });
 
         // Bind group
-        let bind_group = device.create_bind_group(&BindGroupDescriptor {
-            label: Some("Procedural Bind Group"),
-            layout: &bind_group_layout,
-            entries: &[BindGroupEntry {
-                binding: 0,
-                resource: uniform_buffer.as_entire_binding(),
-            }],
-        });
+        let correction_texture = Self::create_correction_texture(device, 1, 1);
+        let bind_group = Self::create_bind_group(device, &bind_group_layout, &uniform_buffer, &correction_texture);
 
         // Pipeline layout
         let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
@@ -119,33 +385,281 @@ impl ProceduralPipeline {
             bind_group_layout,
             uniform_buffer,
             bind_group,
+            correction_texture,
+            corrections: None,
+            correction_rects: Vec::new(),
+            format,
         }
     }
 
-    /// Update uniform buffer with current state
+    fn create_correction_texture(device: &Device, width: u32, height: u32) -> Texture {
+        device.create_texture(&TextureDescriptor {
+            label: Some("ASP Correction Texture"),
+            size: Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8Snorm,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+            view_formats: &[],
+        })
+    }
+
+    fn create_bind_group(
+        device: &Device,
+        layout: &BindGroupLayout,
+        uniform_buffer: &Buffer,
+        correction_texture: &Texture,
+    ) -> BindGroup {
+        let correction_view = correction_texture.create_view(&TextureViewDescriptor::default());
+        device.create_bind_group(&BindGroupDescriptor {
+            label: Some("Procedural Bind Group"),
+            layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::TextureView(&correction_view),
+                },
+            ],
+        })
+    }
+
+    /// Upload the C-packet corrections of the shown .asp frame
+    ///
+    /// Only uploads when the ROIs change, and then only the ROI rectangles:
+    /// the previous frame's are cleared and the new ones written. The texture
+    /// is recreated when the frame size changes.
+    pub fn update_corrections(&mut self, device: &Device, queue: &Queue, decoder: &Decoder) {
+        let max_size = device.limits().max_texture_dimension_2d;
+        let state = decoder.asp_state().filter(|state| {
+            state.keyframe.as_ref().is_some_and(|k| k.width.max(k.height) <= max_size) && !state.corrections.is_empty()
+        });
+        let current = state.and_then(|state| {
+            let keyframe = state.keyframe.as_ref()?;
+            Some(((keyframe.width, keyframe.height), state.corrections.as_slice()))
+        });
+        let unchanged = match (&self.corrections, current) {
+            (Some((size, rois)), Some((new_size, new_rois))) => *size == new_size && rois.as_slice() == new_rois,
+            (None, None) => true,
+            _ => false,
+        };
+        if unchanged {
+            return;
+        }
+
+        let images = state.map_or_else(Vec::new, |state| state.correction_images());
+        let Some(((width, height), rois)) = current.filter(|_| !images.is_empty()) else {
+            self.corrections = None;
+            return;
+        };
+        if self.correction_texture.size() != (Extent3d { width, height, depth_or_array_layers: 1 }) {
+            // New textures start out cleared
+            self.correction_texture = Self::create_correction_texture(device, width, height);
+            self.bind_group =
+                Self::create_bind_group(device, &self.bind_group_layout, &self.uniform_buffer, &self.correction_texture);
+            self.correction_rects.clear();
+        }
+        for (origin, extent) in std::mem::take(&mut self.correction_rects) {
+            let zeros = vec![0u8; extent.width as usize * extent.height as usize * 4];
+            self.write_correction_rect(queue, origin, extent, &zeros);
+        }
+        for image in &images {
+            let origin = Origin3d { x: image.x, y: image.y, z: 0 };
+            let extent = Extent3d { width: image.width, height: image.height, depth_or_array_layers: 1 };
+            self.write_correction_rect(queue, origin, extent, bytemuck::cast_slice(&image.texels));
+            self.correction_rects.push((origin, extent));
+        }
+        self.corrections = Some(((width, height), rois.to_vec()));
+    }
+
+    fn write_correction_rect(&self, queue: &Queue, origin: Origin3d, extent: Extent3d, texels: &[u8]) {
+        queue.write_texture(
+            ImageCopyTexture {
+                texture: &self.correction_texture,
+                mip_level: 0,
+                origin,
+                aspect: TextureAspect::All,
+            },
+            texels,
+            ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(extent.width * 4),
+                rows_per_image: Some(extent.height),
+            },
+            extent,
+        );
+    }
+
+    /// Update uniform buffer with current state and decoded content
     pub fn update_uniforms(
         &self,
         queue: &Queue,
         state: &ViewerState,
+        decoder: &Decoder,
         time: f32,
         resolution: [f32; 2],
     ) {
         let uniforms = Uniforms {
-            resolution,
             time,
             zoom: state.zoom,
             pan: state.pan,
-            content_type: 0,  // Default to Perlin
-            param1: 10.0,     // scale
-            param2: 6.0,      // octaves
-            param3: 0.0,
-            param4: 0.0,
-            _pad1: 0,
+            correction: self.corrections.as_ref().map_or([0.0; 4], |((width, height), _)| {
+                [1.0, state.show_roi_outlines as u32 as f32, *width as f32, *height as f32]
+            }),
+            display: [VIGNETTE, 0.0, 0.0, 0.0],
+            ..Self::content_uniforms(decoder, resolution)
         };
 
         queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
     }
 
+    /// Uniforms for the decoded content in the default view (zoom 1, no pan,
+    /// time 0, no corrections or vignette)
+    fn content_uniforms(decoder: &Decoder, resolution: [f32; 2]) -> Uniforms {
+        let content = ContentUniforms::from_content(decoder.content());
+        // Error band around plotted curves, in data units
+        let band = decoder
+            .fit_stats()
+            .map_or([0.0; 4], |s| [s.max_abs_error as f32, s.rmse as f32, 0.0, 0.0]);
+
+        Uniforms {
+            resolution,
+            time: 0.0,
+            zoom: 1.0,
+            pan: [0.0, 0.0],
+            content_type: content.content_type,
+            term_count: content.term_count,
+            params: content.params,
+            center: content.center,
+            julia_c: content.julia_c,
+            seed: content.seed,
+            terms: content.terms,
+            layer_count: content.layer_count,
+            _pad: [0; 3],
+            layers: content.layers,
+            band,
+            correction: [0.0; 4],
+            display: [0.0; 4],
+        }
+    }
+
+    /// Render the decoded content offscreen at `width` x `height` and read it back
+    ///
+    /// Uses the default view without vignette, so pixel (x, y) shows what
+    /// the screen shows at that position of a `width` x `height` window.
+    /// Returns RGBA8 pixels. Submits its own commands: call it before
+    /// [`update_uniforms`](Self::update_uniforms) for the frame.
+    pub fn render_image(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        decoder: &Decoder,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<u8>> {
+        let bgra = match self.format {
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => false,
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => true,
+            format => anyhow::bail!("Cannot read back {:?} render targets", format),
+        };
+        let max_size = device.limits().max_texture_dimension_2d;
+        if width == 0 || height == 0 || width.max(height) > max_size {
+            anyhow::bail!("Cannot render {}x{} offscreen (GPU limit {})", width, height, max_size);
+        }
+
+        let uniforms = Self::content_uniforms(decoder, [width as f32, height as f32]);
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+
+        let size = Extent3d { width, height, depth_or_array_layers: 1 };
+        let target = device.create_texture(&TextureDescriptor {
+            label: Some("Procedural Offscreen Target"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: self.format,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = target.create_view(&TextureViewDescriptor::default());
+
+        let unpadded_bytes_per_row = width * 4;
+        let align = COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(align) * align;
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("Procedural Readback Buffer"),
+            size: padded_bytes_per_row as u64 * height as u64,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("Procedural Offscreen Encoder"),
+        });
+        {
+            let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+                label: Some("Procedural Offscreen Pass"),
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(Color::BLACK),
+                        store: StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_pipeline(&self.render_pipeline);
+            render_pass.set_bind_group(0, &self.bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+        encoder.copy_texture_to_buffer(
+            ImageCopyTexture {
+                texture: &target,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            ImageCopyBuffer {
+                buffer: &buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            size,
+        );
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        slice.map_async(MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        device.poll(Maintain::Wait);
+        rx.recv()??;
+
+        let data = slice.get_mapped_range();
+        let mut pixels = Vec::with_capacity(unpadded_bytes_per_row as usize * height as usize);
+        for row in data.chunks(padded_bytes_per_row as usize).take(height as usize) {
+            pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+        }
+        drop(data);
+        buffer.unmap();
+
+        if bgra {
+            pixels.chunks_exact_mut(4).for_each(|p| p.swap(0, 2));
+        }
+        Ok(pixels)
+    }
+
     pub fn render<'a>(
         &'a self,
         render_pass: &mut RenderPass<'a>,
@@ -432,3 +946,162 @@ impl SdfPipeline {
         render_pass.draw(0..3, 0..1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::BlendOp;
+    use glam::DVec2;
+
+    #[test]
+    fn test_uniforms_layout() {
+        assert_eq!(std::mem::size_of::<LayerUniform>(), 80);
+        assert_eq!(std::mem::size_of::<Uniforms>(), 96 + MAX_TERMS * 16 + 16 + MAX_LAYERS * 80 + 16 + 16 + 16);
+        assert_eq!(std::mem::size_of::<Uniforms>() % 16, 0);
+    }
+
+    #[test]
+    fn test_julia_content_mapping() {
+        let content = ProceduralContent::Fractal {
+            fractal_type: FractalType::Julia,
+            max_iterations: 300,
+            escape_radius: 4.0,
+            center: DVec2::new(-0.743643887037151, 0.131825904205330),
+            julia_c: Some(DVec2::new(-0.7, 0.27)),
+        };
+        let u = ContentUniforms::from_content(Some(&content));
+
+        assert_eq!(u.content_type, CONTENT_FRACTAL);
+        assert_eq!(u.params, [300.0, 4.0, 1.0, 0.0]);
+        assert_eq!(u.julia_c[0], -0.7);
+        assert_eq!(u.julia_c[1], 0.27);
+
+        // High + low parts recover the f64 center better than a plain f32
+        let recovered = u.center[0] as f64 + u.center[2] as f64;
+        assert!((recovered - -0.743643887037151).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_segmented_packing() {
+        let content = ProceduralContent::Segmented {
+            segments: vec![(0, vec![1.0, 2.0]), (50, vec![1.0, 2.0, 3.0, 4.0, 5.0])],
+            domain_end: 100,
+            value_range: (0.0, 10.0),
+        };
+        let u = ContentUniforms::from_content(Some(&content));
+
+        assert_eq!(u.content_type, CONTENT_SEGMENTED);
+        assert_eq!(u.term_count, 2);
+        assert_eq!(u.terms[0], [0.0, 2.0, 0.0, 0.0]);
+        assert_eq!(u.terms[1], [1.0, 2.0, 0.0, 0.0]);
+        assert_eq!(u.terms[2], [50.0, 5.0, 0.0, 0.0]);
+        assert_eq!(u.terms[4], [5.0, 0.0, 0.0, 0.0]);
+        assert_eq!(u.params, [100.0, 5.0, 0.3, 0.0]);
+    }
+
+    #[test]
+    fn test_segmented_overflow_marks_uncovered_domain() {
+        // A linear segment takes 2 slots, so only the first 8 of 10 fit
+        let content = ProceduralContent::Segmented {
+            segments: (0..10).map(|i| (i * 100, vec![i as f64, 0.5])).collect(),
+            domain_end: 1000,
+            value_range: (0.0, 60.0),
+        };
+        let u = ContentUniforms::from_content(Some(&content));
+
+        assert_eq!(u.term_count, 8);
+        assert_eq!(u.term_slots(), MAX_TERMS);
+        assert_eq!(u.terms[14], [700.0, 2.0, 0.0, 0.0]);
+        assert_eq!(u.params[3], 800.0);
+
+        let fitting = ProceduralContent::Segmented {
+            segments: (0..8).map(|i| (i * 100, vec![i as f64, 0.5])).collect(),
+            domain_end: 800,
+            value_range: (0.0, 60.0),
+        };
+        assert_eq!(ContentUniforms::from_content(Some(&fitting)).params[3], 0.0);
+    }
+
+    #[test]
+    fn test_polynomial_plot_frame() {
+        let series = ProceduralContent::Polynomial {
+            coefficients: vec![2.0, 0.5],
+            domain_end: 8,
+            value_range: (2.0, 6.0),
+        };
+        let u = ContentUniforms::from_content(Some(&series));
+        assert_eq!(u.content_type, CONTENT_POLYNOMIAL);
+        assert_eq!(u.terms[0], [2.0, 0.5, 0.0, 0.0]);
+        assert_eq!(u.params, [8.0, 4.0, 0.75, 0.0]);
+
+        // Sample 0 at the left edge, the domain end at the right
+        let frame = series.plot_frame().unwrap();
+        assert_eq!((frame.view_x(0.0), frame.view_x(8.0)), (-2.0, 2.0));
+        assert_eq!(frame.sample_x(frame.view_x(3.5)), 3.5);
+        assert_eq!(frame.value(frame.view_y(5.0)), 5.0);
+
+        let unbounded = ProceduralContent::Polynomial { coefficients: vec![1.0], domain_end: 0, value_range: (0.0, 0.0) };
+        assert!(unbounded.plot_frame().is_none());
+        assert_eq!(ContentUniforms::from_content(Some(&unbounded)).params[0], 0.0);
+    }
+
+    #[test]
+    fn test_composite_term_offsets() {
+        let content = ProceduralContent::Composite {
+            layers: vec![
+                (BlendOp::Add, 1.0, ProceduralContent::Polynomial { coefficients: vec![1.0; 6], domain_end: 0, value_range: (0.0, 0.0) }),
+                (BlendOp::Max, 0.5, ProceduralContent::SineWave { components: vec![(1.0, 2.0, 0.0)], offset: 0.0 }),
+                (
+                    BlendOp::DomainWarp,
+                    0.2,
+                    ProceduralContent::Perlin { seed: 3, scale: 2.0, octaves: 4, persistence: 0.5, lacunarity: 2.0 },
+                ),
+            ],
+        };
+        let u = ContentUniforms::from_content(Some(&content));
+
+        assert_eq!(u.content_type, CONTENT_COMPOSITE);
+        assert_eq!(u.layer_count, 3);
+        assert_eq!((u.layers[0].term_offset, u.layers[0].term_count), (0, 6));
+        assert_eq!((u.layers[1].term_offset, u.layers[1].term_count), (2, 1));
+        assert_eq!(u.terms[2], [1.0, 2.0, 0.0, 0.0]);
+        assert_eq!(u.layers[1].blend, 2);
+        assert_eq!(u.layers[2].blend, 3);
+        assert_eq!(u.layers[2].weight[0], 0.2);
+        assert_eq!(u.layers[2].params, [2.0, 4.0, 0.5, 2.0]);
+    }
+
+    #[test]
+    fn test_composite_skips_layers_beyond_term_slots() {
+        let fourier = |k: usize| ProceduralContent::Fourier { coefficients: vec![(k, 1.0, 0.0); MAX_TERMS], period: 10.0 };
+        let content = ProceduralContent::Composite {
+            layers: vec![
+                (BlendOp::Add, 1.0, fourier(1)),
+                (BlendOp::Add, 1.0, fourier(2)),
+                (BlendOp::Max, 0.5, ProceduralContent::Perlin { seed: 1, scale: 2.0, octaves: 4, persistence: 0.5, lacunarity: 2.0 }),
+            ],
+        };
+        let u = ContentUniforms::from_content(Some(&content));
+
+        // The second Fourier layer has no slots left; the Perlin layer needs none
+        assert_eq!(u.layer_count, 2);
+        assert_eq!((u.layers[0].term_offset, u.layers[0].term_count), (0, MAX_TERMS as u32));
+        assert_eq!(u.layers[1].content_type, CONTENT_PERLIN);
+    }
+
+    #[test]
+    fn test_perlin_and_empty_mapping() {
+        let content = ProceduralContent::Perlin {
+            seed: 0x1_0000_0002,
+            scale: 5.0,
+            octaves: 4,
+            persistence: 0.6,
+            lacunarity: 2.5,
+        };
+        let u = ContentUniforms::from_content(Some(&content));
+        assert_eq!(u.params, [5.0, 4.0, 0.6, 2.5]);
+        assert_eq!(u.seed, [2, 1, 0, 0]);
+
+        assert_eq!(ContentUniforms::from_content(None), ContentUniforms::default());
+    }
+}
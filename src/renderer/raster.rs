@@ -0,0 +1,386 @@
+//! Textured-quad pipeline for raster images
+//!
+//! `ProceduralContent::Raster` is uploaded to a texture once per image (the
+//! shared `Arc` identifies it) and drawn as a quad that follows the 2D
+//! zoom/pan. When a reference image is paired with .alice/.alz content, the
+//! quad shows the reference next to the decoded content (split view) or their
+//! difference as a heatmap.
+
+use crate::app::{CompareView, RasterFilter, ViewerState};
+use crate::decoder::{Decoder, ProceduralContent};
+use std::borrow::Cow;
+use std::sync::Arc;
+use wgpu::*;
+
+/// Uniforms for raster.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct RasterUniforms {
+    resolution: [f32; 2], // offset 0
+    zoom: f32,            // offset 8
+    mode: u32,            // offset 12 (see MODE_*)
+    pan: [f32; 2],        // offset 16
+    image_size: [f32; 2], // offset 24 (true pixel dimensions)
+    split: f32,           // offset 32 (fraction of the image width)
+    _pad: [f32; 3],
+}
+
+/// Display modes understood by raster.wgsl
+const MODE_IMAGE: u32 = 0;
+const MODE_SPLIT: u32 = 1;
+const MODE_DIFFERENCE: u32 = 2;
+
+/// Texture uploaded from shared pixels
+struct RasterImage {
+    /// Pixels the texture was uploaded from
+    data: Arc<Vec<u8>>,
+    /// Possibly decimated to fit the GPU limit
+    texture: Texture,
+}
+
+/// Raster image pipeline
+pub struct RasterPipeline {
+    render_pipeline: RenderPipeline,
+    bind_group_layout: BindGroupLayout,
+    uniform_buffer: Buffer,
+    linear_sampler: Sampler,
+    nearest_sampler: Sampler,
+    /// Image being shown (the reference when comparing)
+    image: Option<RasterImage>,
+    /// Decoded content compared against `image`
+    overlay: Option<RasterImage>,
+    /// Bind group for the current textures and its filter
+    bind_group: Option<(BindGroup, RasterFilter)>,
+}
+
+impl RasterPipeline {
+    pub fn new(device: &Device, format: TextureFormat) -> Self {
+        let shader = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("Raster Shader"),
+            source: ShaderSource::Wgsl(include_str!("../shaders/raster.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Raster Bind Group Layout"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let uniform_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("Raster Uniform Buffer"),
+            size: std::mem::size_of::<RasterUniforms>() as u64,
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let sampler = |label, filter| {
+            device.create_sampler(&SamplerDescriptor {
+                label: Some(label),
+                address_mode_u: AddressMode::ClampToEdge,
+                address_mode_v: AddressMode::ClampToEdge,
+                mag_filter: filter,
+                min_filter: filter,
+                ..Default::default()
+            })
+        };
+        let linear_sampler = sampler("Raster Linear Sampler", FilterMode::Linear);
+        let nearest_sampler = sampler("Raster Nearest Sampler", FilterMode::Nearest);
+
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("Raster Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let render_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("Raster Render Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(ColorTargetState {
+                    format,
+                    blend: Some(BlendState::ALPHA_BLENDING),
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            multiview: None,
+        });
+
+        Self {
+            render_pipeline,
+            bind_group_layout,
+            uniform_buffer,
+            linear_sampler,
+            nearest_sampler,
+            image: None,
+            overlay: None,
+            bind_group: None,
+        }
+    }
+
+    /// Upload the image to show if it changed and update uniforms
+    ///
+    /// Shows the decoder's raster image, or the paired reference against
+    /// `comparison` (the decoded content at reference resolution) unless the
+    /// compare view is off. Returns whether there is an image to draw.
+    pub fn update(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        state: &ViewerState,
+        decoder: &Decoder,
+        comparison: Option<&Arc<Vec<u8>>>,
+        resolution: [f32; 2],
+    ) -> bool {
+        let comparison = comparison.filter(|_| state.compare_view != CompareView::Off);
+        let source = if comparison.is_some() { decoder.reference() } else { decoder.content() };
+        let Some(ProceduralContent::Raster { width, height, data }) = source else {
+            self.clear();
+            return false;
+        };
+        let len = *width as usize * *height as usize * 4;
+        if *width == 0 || *height == 0 || data.len() < len || comparison.is_some_and(|test| test.len() < len) {
+            self.clear();
+            return false;
+        }
+
+        // Re-upload only for new pixels; a filter change just swaps the sampler
+        let mut changed = Self::cache(&mut self.image, device, queue, *width, *height, data);
+        changed |= match comparison {
+            Some(test) => Self::cache(&mut self.overlay, device, queue, *width, *height, test),
+            None => self.overlay.take().is_some(),
+        };
+        let bind_group = match self.bind_group.take() {
+            Some((bind_group, filter)) if !changed && filter == state.raster_filter => bind_group,
+            _ => self.create_bind_group(device, state.raster_filter),
+        };
+        self.bind_group = Some((bind_group, state.raster_filter));
+
+        let mode = match (comparison, state.compare_view) {
+            (None, _) => MODE_IMAGE,
+            (Some(_), CompareView::Difference) => MODE_DIFFERENCE,
+            (Some(_), _) => MODE_SPLIT,
+        };
+        let uniforms = RasterUniforms {
+            resolution,
+            zoom: state.zoom,
+            mode,
+            pan: state.pan,
+            image_size: [*width as f32, *height as f32],
+            split: state.compare_split,
+            _pad: [0.0; 3],
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+        true
+    }
+
+    fn clear(&mut self) {
+        self.image = None;
+        self.overlay = None;
+        self.bind_group = None;
+    }
+
+    /// Keep `slot` uploaded from `data`; returns whether it was (re)uploaded
+    fn cache(
+        slot: &mut Option<RasterImage>,
+        device: &Device,
+        queue: &Queue,
+        width: u32,
+        height: u32,
+        data: &Arc<Vec<u8>>,
+    ) -> bool {
+        if slot.as_ref().is_some_and(|image| Arc::ptr_eq(&image.data, data)) {
+            return false;
+        }
+        *slot = Some(RasterImage {
+            data: data.clone(),
+            texture: Self::upload(device, queue, width, height, data),
+        });
+        true
+    }
+
+    fn upload(device: &Device, queue: &Queue, width: u32, height: u32, data: &[u8]) -> Texture {
+        let max_size = device.limits().max_texture_dimension_2d;
+        let (tex_width, tex_height, pixels) = fit_texture(width, height, data, max_size);
+        if (tex_width, tex_height) != (width, height) {
+            tracing::warn!(
+                "Image {}x{} exceeds the GPU texture limit; showing it at {}x{}",
+                width,
+                height,
+                tex_width,
+                tex_height
+            );
+        }
+
+        let size = Extent3d { width: tex_width, height: tex_height, depth_or_array_layers: 1 };
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some("Raster Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8UnormSrgb,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            &pixels,
+            ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(tex_width * 4),
+                rows_per_image: Some(tex_height),
+            },
+            size,
+        );
+        texture
+    }
+
+    /// Bind group for the current image (and overlay, else the image again)
+    fn create_bind_group(&self, device: &Device, filter: RasterFilter) -> BindGroup {
+        let sampler = match filter {
+            RasterFilter::Linear => &self.linear_sampler,
+            RasterFilter::Nearest => &self.nearest_sampler,
+        };
+        let view = |image: &Option<RasterImage>| {
+            image.as_ref().map(|image| image.texture.create_view(&TextureViewDescriptor::default()))
+        };
+        let image_view = view(&self.image).expect("raster image uploaded before its bind group");
+        let overlay_view = view(&self.overlay);
+        device.create_bind_group(&BindGroupDescriptor {
+            label: Some("Raster Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: self.uniform_buffer.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::TextureView(&image_view),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: BindingResource::Sampler(sampler),
+                },
+                BindGroupEntry {
+                    binding: 3,
+                    resource: BindingResource::TextureView(overlay_view.as_ref().unwrap_or(&image_view)),
+                },
+            ],
+        })
+    }
+
+    /// Draw the image uploaded by the last [`update`](Self::update)
+    pub fn render<'a>(&'a self, render_pass: &mut RenderPass<'a>) {
+        let Some((bind_group, _)) = &self.bind_group else {
+            return;
+        };
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, bind_group, &[]);
+        render_pass.draw(0..6, 0..1);
+    }
+}
+
+/// Pixels to upload for a `width` x `height` RGBA8 image
+///
+/// Images larger than `max_size` on either side are decimated by the
+/// smallest integer step that fits; others are passed through untouched.
+fn fit_texture(width: u32, height: u32, data: &[u8], max_size: u32) -> (u32, u32, Cow<'_, [u8]>) {
+    let step = width.max(height).div_ceil(max_size.max(1)).max(1);
+    if step == 1 {
+        return (width, height, Cow::Borrowed(&data[..width as usize * height as usize * 4]));
+    }
+
+    let (out_width, out_height) = (width.div_ceil(step), height.div_ceil(step));
+    let mut pixels = Vec::with_capacity(out_width as usize * out_height as usize * 4);
+    for y in (0..height).step_by(step as usize) {
+        for x in (0..width).step_by(step as usize) {
+            let i = (y as usize * width as usize + x as usize) * 4;
+            pixels.extend_from_slice(&data[i..i + 4]);
+        }
+    }
+    (out_width, out_height, Cow::Owned(pixels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raster_uniforms_layout() {
+        assert_eq!(std::mem::size_of::<RasterUniforms>(), 48);
+    }
+
+    #[test]
+    fn test_fit_texture() {
+        let data: Vec<u8> = (0..5 * 3).flat_map(|i| [i as u8, 0, 0, 255]).collect();
+        let (w, h, pixels) = fit_texture(5, 3, &data, 8);
+        assert_eq!((w, h), (5, 3));
+        assert!(matches!(pixels, Cow::Borrowed(_)));
+
+        // Every second pixel of every second row
+        let (w, h, pixels) = fit_texture(5, 3, &data, 4);
+        assert_eq!((w, h), (3, 2));
+        let reds: Vec<u8> = pixels.chunks(4).map(|p| p[0]).collect();
+        assert_eq!(reds, vec![0, 2, 4, 10, 12, 14]);
+    }
+}
@@ -1,19 +1,43 @@
 // ALICE-View Procedural Generation Shader
 // "Store equations, not pixels" - GPU-computed infinite resolution
 
+const MAX_TERMS: u32 = 16u;
+const MAX_LAYERS: u32 = 8u;
+
+// Composite layer; its terms live at terms[term_offset..]
+struct Layer {
+    content_type: u32,
+    blend: u32,         // 0=add, 1=multiply, 2=max, 3=domain warp
+    term_offset: u32,
+    term_count: u32,
+    params: vec4<f32>,
+    center: vec4<f32>,  // xy = fractal center, zw = Julia constant
+    seed: vec4<u32>,
+    weight: vec4<f32>,  // x = weight
+}
+
 struct Uniforms {
     resolution: vec2<f32>,
     time: f32,
     zoom: f32,
     pan: vec2<f32>,
-    content_type: u32,  // 0=Perlin, 1=Polynomial, 2=Fractal, 3=Gradient, 4=Voronoi
-    param1: f32,
-    param2: f32,
-    param3: f32,
-    param4: f32,
+    content_type: u32,  // 0=Perlin, 1=Polynomial, 2=Fractal, 3=Gradient, 4=Voronoi, 5=Sine, 6=Fourier, 7=Raster, 8=Segmented, 9=Composite
+    term_count: u32,
+    params: vec4<f32>,  // content-specific scalars
+    center: vec4<f32>,  // xy = f64 high part, zw = low part
+    julia_c: vec4<f32>, // xy = Julia constant
+    seed: vec4<u32>,    // xy = u64 seed lo/hi
+    terms: array<vec4<f32>, MAX_TERMS>,
+    layer_count: u32,
+    layers: array<Layer, MAX_LAYERS>,
+    band: vec4<f32>,    // x = max abs error, y = RMSE (data units; 0 = none)
+    correction: vec4<f32>, // x = residuals present, y = outline ROIs, zw = frame size (pixels)
+    display: vec4<f32>,    // x = vignette strength
 }
 
 @group(0) @binding(0) var<uniform> uniforms: Uniforms;
+// ASP C-packet residuals (rgb) and ROI coverage (a), one texel per frame pixel
+@group(0) @binding(1) var correction_tex: texture_2d<f32>;
 
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
@@ -77,35 +101,48 @@ fn grad_noise(p: vec2<f32>) -> f32 {
     return mix(mix(a, b, u.x), mix(c, d, u.x), u.y);
 }
 
-// Fractal Brownian Motion
-fn fbm(p: vec2<f32>, octaves: i32) -> f32 {
+// Fractal Brownian Motion (normalized to [0, 1])
+fn fbm(p: vec2<f32>, octaves: i32, persistence: f32, lacunarity: f32) -> f32 {
     var value = 0.0;
     var amplitude = 0.5;
     var frequency = 1.0;
-    var pos = p;
+    var total = 0.0;
 
     for (var i = 0; i < octaves; i++) {
-        value += amplitude * grad_noise(pos * frequency);
-        amplitude *= 0.5;
-        frequency *= 2.0;
+        value += amplitude * grad_noise(p * frequency);
+        total += amplitude;
+        amplitude *= persistence;
+        frequency *= lacunarity;
     }
 
-    return value;
+    return value / max(total, 1e-6);
+}
+
+// Map a u64 seed (lo/hi words) to a stable domain offset
+fn seed_offset(seed: vec2<u32>) -> vec2<f32> {
+    let a = (seed.x ^ (seed.y * 0x9E3779B9u)) & 0xFFFFu;
+    let b = ((seed.x >> 16u) ^ seed.y) & 0xFFFFu;
+    return vec2<f32>(f32(a), f32(b)) * 0.7548776;
 }
 
 // ============================================
 // Content Type Generators
 // ============================================
 
+// Perlin FBM value: params = (scale, octaves, persistence, lacunarity)
+fn perlin_value(p: vec2<f32>, params: vec4<f32>, seed: vec2<u32>) -> f32 {
+    let octaves = clamp(i32(params.y), 1, 16);
+    return fbm(p * params.x + seed_offset(seed), octaves, params.z, params.w);
+}
+
 // Perlin noise with octaves
 fn generate_perlin(uv: vec2<f32>) -> vec3<f32> {
-    let scale = uniforms.param1;
-    let octaves = i32(uniforms.param2);
-
     let world_pos = (uv - 0.5) / uniforms.zoom + uniforms.pan;
-    let n = fbm(world_pos * scale, octaves);
+    return terrain_color(perlin_value(world_pos, uniforms.params, uniforms.seed.xy));
+}
 
-    // Color gradient based on noise value
+// Height palette for scalar fields in [0, 1]
+fn terrain_color(n: f32) -> vec3<f32> {
     let low = vec3<f32>(0.1, 0.2, 0.4);
     let mid = vec3<f32>(0.3, 0.6, 0.3);
     let high = vec3<f32>(0.9, 0.8, 0.6);
@@ -117,57 +154,234 @@ fn generate_perlin(uv: vec2<f32>) -> vec3<f32> {
     }
 }
 
-// Polynomial surface visualization
-fn generate_polynomial(uv: vec2<f32>) -> vec3<f32> {
-    let world_pos = (uv - 0.5) / uniforms.zoom + uniforms.pan;
-    let x = world_pos.x;
-    let y = world_pos.y;
-
-    // Coefficients from params
-    let a = uniforms.param1;
-    let b = uniforms.param2;
-    let c = uniforms.param3;
-    let d = uniforms.param4;
-
-    // z = ax³ + by² + cxy + d
-    let z = a * x * x * x + b * y * y + c * x * y + d;
+// Polynomial coefficient i (packed 4 per term slot from slot `base`, ascending powers)
+fn poly_coef(base: u32, i: u32) -> f32 {
+    let idx = base * 4u + i;
+    return uniforms.terms[min(idx / 4u, MAX_TERMS - 1u)][idx % 4u];
+}
 
-    // Height-based coloring
-    let normalized = clamp(z * 0.5 + 0.5, 0.0, 1.0);
+// Evaluate polynomial and its derivative with Horner's method
+fn poly_eval(x: f32, base: u32, count: u32) -> vec2<f32> {
+    var value = 0.0;
+    var deriv = 0.0;
+    var i = i32(count) - 1;
+    loop {
+        if (i < 0) { break; }
+        deriv = deriv * x + value;
+        value = value * x + poly_coef(base, u32(i));
+        i--;
+    }
+    return vec2<f32>(value, deriv);
+}
 
+// Shared 2D function plot: axes, filled regions, error band and an
+// anti-aliased curve. f = (value, derivative) at x, px = world units per
+// pixel, band_scale = plotted units per data unit
+fn plot_function(x: f32, y: f32, f: vec2<f32>, px: f32, band_scale: f32) -> vec3<f32> {
+    // Fill above/below curve with height-based coloring
+    let normalized = clamp(0.5 + 0.5 * tanh(f.x - y), 0.0, 1.0);
     let color1 = vec3<f32>(0.2, 0.1, 0.5);
     let color2 = vec3<f32>(0.1, 0.8, 0.6);
     let color3 = vec3<f32>(1.0, 0.9, 0.3);
-
+    var color: vec3<f32>;
     if (normalized < 0.5) {
-        return mix(color1, color2, normalized * 2.0);
+        color = mix(color1, color2, normalized * 2.0) * 0.35;
     } else {
-        return mix(color2, color3, (normalized - 0.5) * 2.0);
+        color = mix(color2, color3, (normalized - 0.5) * 2.0) * 0.35;
     }
+
+    // Axes
+    let axis = 1.0 - smoothstep(0.0, px * 1.5, min(abs(x), abs(y)));
+    color = mix(color, vec3<f32>(0.6, 0.6, 0.6), axis * 0.6);
+
+    // Residual band: ±RMSE shaded, ±max error lighter with edge lines
+    let band = uniforms.band.xy * band_scale;
+    if (band.x > 0.0) {
+        let off = abs(f.x - y);
+        let band_color = vec3<f32>(0.3, 0.6, 1.0);
+        color = mix(color, band_color, (1.0 - smoothstep(band.x - px, band.x, off)) * 0.2);
+        color = mix(color, band_color, (1.0 - smoothstep(band.y - px, band.y, off)) * 0.25);
+        let edge = 1.0 - smoothstep(0.0, px * 1.5, abs(off - band.x));
+        color = mix(color, band_color, edge * 0.7);
+    }
+
+    // Curve (distance approximated by |f(x) - y| / |∇|)
+    let dist = abs(f.x - y) / sqrt(1.0 + f.y * f.y);
+    let line = 1.0 - smoothstep(px, px * 2.5, dist);
+    return mix(color, vec3<f32>(1.0, 0.9, 0.3), line);
 }
 
-// Mandelbrot fractal
-fn generate_fractal(uv: vec2<f32>) -> vec3<f32> {
-    let max_iter = i32(uniforms.param1);
-    let escape_radius = uniforms.param2;
+// Polynomial curve plot: y = Σ c[i]·x^i. A sampled series
+// (params = domain samples, y center, y scale) is framed like a segmented
+// one; params.x = 0 plots x and y in view units.
+fn generate_polynomial(uv: vec2<f32>) -> vec3<f32> {
+    let world_pos = ((uv - 0.5) / uniforms.zoom + uniforms.pan) * 4.0;
+    let px = 4.0 / (uniforms.zoom * uniforms.resolution.y);
+    if (uniforms.params.x > 0.0) {
+        let x = world_pos.x + 2.0;
+        let samples_per_unit = uniforms.params.x / 4.0;
+        let f = poly_eval(x * samples_per_unit, 0u, uniforms.term_count);
+        let y_scale = uniforms.params.z;
+        let plotted = vec2<f32>((f.x - uniforms.params.y) * y_scale, f.y * samples_per_unit * y_scale);
+        return plot_function(x, -world_pos.y, plotted, px, y_scale);
+    }
+    return plot_function(world_pos.x, -world_pos.y, poly_eval(world_pos.x, 0u, uniforms.term_count), px, 1.0);
+}
 
-    let world_pos = (uv - 0.5) / uniforms.zoom + uniforms.pan;
-    let c = vec2<f32>(world_pos.x * 3.0 - 0.5, world_pos.y * 3.0);
+// Piecewise model: terms hold per segment a header (start, coefficient count)
+// followed by its coefficients, 4 per slot. term_count = number of segments.
+// Returns (value, derivative) with x relative to the covering segment start.
+fn segmented_eval(x: f32, base: u32, count: u32) -> vec2<f32> {
+    var slot = base;
+    var chosen = base;
+    for (var i = 0u; i < count && slot < MAX_TERMS; i++) {
+        let header = uniforms.terms[slot];
+        if (header.x <= x) { chosen = slot; }
+        slot += 1u + (u32(header.y) + 3u) / 4u;
+    }
 
+    let header = uniforms.terms[min(chosen, MAX_TERMS - 1u)];
+    let local = x - header.x;
+    let coef_base = (chosen + 1u) * 4u;
+    var value = 0.0;
+    var deriv = 0.0;
+    var i = i32(header.y) - 1;
+    loop {
+        if (i < 0) { break; }
+        let idx = coef_base + u32(i);
+        deriv = deriv * local + value;
+        value = value * local + uniforms.terms[min(idx / 4u, MAX_TERMS - 1u)][idx % 4u];
+        i--;
+    }
+    return vec2<f32>(value, deriv);
+}
+
+// Segmented time series: params = (domain samples, y center, y scale,
+// first sample not covered by the uploaded segments; 0 = all uploaded)
+fn generate_segmented(uv: vec2<f32>) -> vec3<f32> {
+    let world_pos = ((uv - 0.5) / uniforms.zoom + uniforms.pan) * 4.0;
+    let px = 4.0 / (uniforms.zoom * uniforms.resolution.y);
+
+    // The full domain spans the view width at zoom 1, sample 0 at the left edge
+    let x = world_pos.x + 2.0;
+    let samples_per_unit = uniforms.params.x / 4.0;
+
+    // Segments that did not fit the term slots: hatch their domain rather
+    // than extending the last uploaded segment across it
+    let uncovered = uniforms.params.w;
+    if (uncovered > 0.0 && x * samples_per_unit >= uncovered) {
+        let stripe = step(0.5, fract((x - world_pos.y) / (px * 16.0)));
+        return mix(vec3<f32>(0.12, 0.05, 0.05), vec3<f32>(0.35, 0.1, 0.1), stripe);
+    }
+    let f = segmented_eval(x * samples_per_unit, 0u, uniforms.term_count);
+    let y_scale = uniforms.params.z;
+    let plotted = vec2<f32>((f.x - uniforms.params.y) * y_scale, f.y * samples_per_unit * y_scale);
+    var color = plot_function(x, -world_pos.y, plotted, px, y_scale);
+
+    // Segment boundaries
+    var slot = 0u;
+    for (var i = 0u; i < uniforms.term_count && slot < MAX_TERMS; i++) {
+        let header = uniforms.terms[slot];
+        let d = abs(x - header.x / samples_per_unit);
+        let boundary = 1.0 - smoothstep(0.0, px * 1.5, d);
+        color = mix(color, vec3<f32>(0.9, 0.4, 0.3), boundary * 0.5);
+        slot += 1u + (u32(header.y) + 3u) / 4u;
+    }
+    return color;
+}
+
+// Evaluate Fourier series and its derivative: Σ a·cos(2π·k·t/T + φ)
+fn fourier_eval(t: f32, base: u32, count: u32, period: f32) -> vec2<f32> {
+    let w0 = 6.2831853 / max(period, 1e-6);
+    var value = 0.0;
+    var deriv = 0.0;
+    for (var i = base; i < min(base + count, MAX_TERMS); i++) {
+        let term = uniforms.terms[i];
+        let w = w0 * term.x;
+        let arg = w * t + term.z;
+        value += term.y * cos(arg);
+        deriv -= term.y * w * sin(arg);
+    }
+    return vec2<f32>(value, deriv);
+}
+
+// Fourier series plot: one base period spans the view at zoom 1
+fn generate_fourier(uv: vec2<f32>) -> vec3<f32> {
+    let world_pos = ((uv - 0.5) / uniforms.zoom + uniforms.pan) * 4.0;
+    let px = 4.0 / (uniforms.zoom * uniforms.resolution.y);
+    let period = uniforms.params.x;
+    let y_scale = uniforms.params.y;
+
+    // x ∈ [-2, 2] → t ∈ [-T/2, T/2]
+    let t_per_x = period * 0.25;
+    let f = fourier_eval(world_pos.x * t_per_x, 0u, uniforms.term_count, period);
+    let scaled = vec2<f32>(f.x * y_scale, f.y * t_per_x * y_scale);
+    return plot_function(world_pos.x, -world_pos.y, scaled, px, y_scale);
+}
+
+// Evaluate sum of sines and its derivative: offset + Σ a·sin(2π·f·x + φ)
+fn sine_eval(x: f32, base: u32, count: u32, offset: f32) -> vec2<f32> {
+    var value = offset;
+    var deriv = 0.0;
+    for (var i = base; i < min(base + count, MAX_TERMS); i++) {
+        let term = uniforms.terms[i];
+        let w = 6.2831853 * term.x;
+        let arg = w * x + term.z;
+        value += term.y * sin(arg);
+        deriv += term.y * w * cos(arg);
+    }
+    return vec2<f32>(value, deriv);
+}
+
+// Scrolling waveform plot: the signal advances one x unit per second
+fn generate_sine(uv: vec2<f32>) -> vec3<f32> {
+    let world_pos = ((uv - 0.5) / uniforms.zoom + uniforms.pan) * 4.0;
+    let px = 4.0 / (uniforms.zoom * uniforms.resolution.y);
+    let y_scale = uniforms.params.y;
+
+    let f = sine_eval(world_pos.x + uniforms.time, 0u, uniforms.term_count, uniforms.params.x);
+    return plot_function(world_pos.x, -world_pos.y, f * y_scale, px, y_scale);
+}
+
+// Escape-time fractals: iterations before |z| exceeds the escape radius
+// (0=Mandelbrot, 1=Julia, 2=Burning Ship, 3=Tricorn)
+fn fractal_iterations(p: vec2<f32>, julia_c: vec2<f32>, max_iter: i32, escape_radius: f32, fractal_type: u32) -> i32 {
     var z = vec2<f32>(0.0, 0.0);
-    var i = 0;
+    var c = p;
+    if (fractal_type == 1u) {
+        z = p;
+        c = julia_c;
+    }
 
-    for (var iter = 0; iter < 256; iter++) {
-        if (iter >= max_iter) { break; }
+    var i = 0;
+    for (var iter = 0; iter < max_iter; iter++) {
         if (dot(z, z) > escape_radius * escape_radius) { break; }
 
-        z = vec2<f32>(
-            z.x * z.x - z.y * z.y + c.x,
-            2.0 * z.x * z.y + c.y
-        );
+        switch (fractal_type) {
+            case 2u: {
+                let a = abs(z);
+                z = vec2<f32>(a.x * a.x - a.y * a.y, 2.0 * a.x * a.y) + c;
+            }
+            case 3u: {
+                z = vec2<f32>(z.x * z.x - z.y * z.y, -2.0 * z.x * z.y) + c;
+            }
+            default: {
+                z = vec2<f32>(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y) + c;
+            }
+        }
         i++;
     }
+    return i;
+}
+
+fn generate_fractal(uv: vec2<f32>) -> vec3<f32> {
+    let max_iter = i32(uniforms.params.x);
 
+    // Offset from center is small, so add it to the low part first (f64 split)
+    let view = ((uv - 0.5) / uniforms.zoom + uniforms.pan) * 3.0;
+    let p = uniforms.center.xy + (uniforms.center.zw + view);
+
+    let i = fractal_iterations(p, uniforms.julia_c.xy, max_iter, uniforms.params.y, u32(uniforms.params.z));
     if (i >= max_iter) {
         return vec3<f32>(0.0, 0.0, 0.0);
     }
@@ -191,54 +405,202 @@ fn generate_gradient(uv: vec2<f32>) -> vec3<f32> {
     let radial = length(world_pos);
     let angle = atan2(world_pos.y, world_pos.x);
 
-    let r = 0.5 + 0.5 * sin(radial * uniforms.param1 + uniforms.time);
-    let g = 0.5 + 0.5 * cos(angle * uniforms.param2);
-    let b = 0.5 + 0.5 * sin(radial * uniforms.param3 + angle * uniforms.param4);
+    let r = 0.5 + 0.5 * sin(radial * uniforms.params.x + uniforms.time);
+    let g = 0.5 + 0.5 * cos(angle * uniforms.params.y);
+    let b = 0.5 + 0.5 * sin(radial * uniforms.params.z + angle * uniforms.params.w);
 
     return vec3<f32>(r, g, b);
 }
 
-// Voronoi cells
-fn generate_voronoi(uv: vec2<f32>) -> vec3<f32> {
-    let scale = uniforms.param1;
-    let world_pos = (uv - 0.5) / uniforms.zoom + uniforms.pan;
-    let p = world_pos * scale;
+// Voronoi distance under the selected metric (0=Euclidean, 1=Manhattan, 2=Chebyshev)
+fn voronoi_distance(d: vec2<f32>, metric: u32) -> f32 {
+    switch (metric) {
+        case 1u: { return abs(d.x) + abs(d.y); }
+        case 2u: { return max(abs(d.x), abs(d.y)); }
+        default: { return length(d); }
+    }
+}
+
+struct VoronoiSample {
+    f1: f32,
+    f2: f32,
+    cell_id: vec2<f32>,
+}
 
+// Nearest / second-nearest feature distances at p (in cell units)
+fn voronoi_sample(p: vec2<f32>, jitter: f32, metric: u32, offset: vec2<f32>) -> VoronoiSample {
     let n = floor(p);
     let f = fract(p);
 
-    var min_dist = 10.0;
+    var f1 = 10.0;
+    var f2 = 10.0;
     var min_point = vec2<f32>(0.0);
 
-    for (var j = -1; j <= 1; j++) {
-        for (var i = -1; i <= 1; i++) {
+    // 5x5 neighborhood keeps F2 exact for non-Euclidean metrics
+    for (var j = -2; j <= 2; j++) {
+        for (var i = -2; i <= 2; i++) {
             let neighbor = vec2<f32>(f32(i), f32(j));
-            let point = vec2<f32>(
-                hash2(n + neighbor),
-                hash2(n + neighbor + vec2<f32>(57.0, 113.0))
+            let cell = n + neighbor + offset;
+            let rnd = vec2<f32>(
+                hash2(cell),
+                hash2(cell + vec2<f32>(57.0, 113.0))
             );
+            let point = 0.5 + (rnd - 0.5) * jitter;
 
-            let diff = neighbor + point - f;
-            let dist = length(diff);
+            let dist = voronoi_distance(neighbor + point - f, metric);
 
-            if (dist < min_dist) {
-                min_dist = dist;
+            if (dist < f1) {
+                f2 = f1;
+                f1 = dist;
                 min_point = point;
+            } else if (dist < f2) {
+                f2 = dist;
             }
         }
     }
 
+    return VoronoiSample(f1, f2, n + offset + min_point * 100.0);
+}
+
+// Scalar feature value (0=F1, 1=F2, 2=F2-F1)
+fn voronoi_feature(s: VoronoiSample, feature: u32) -> f32 {
+    switch (feature) {
+        case 1u: { return s.f2; }
+        case 2u: { return s.f2 - s.f1; }
+        default: { return s.f1; }
+    }
+}
+
+// Voronoi cells with metric, feature (0=F1, 1=F2, 2=F2-F1) and jitter
+fn generate_voronoi(uv: vec2<f32>) -> vec3<f32> {
+    let world_pos = (uv - 0.5) / uniforms.zoom + uniforms.pan;
+    let s = voronoi_sample(
+        world_pos * uniforms.params.x,
+        uniforms.params.y,
+        u32(uniforms.params.z),
+        seed_offset(uniforms.seed.xy)
+    );
+    let f1 = s.f1;
+    let f2 = s.f2;
+
     // Color based on cell
+    let cell_id = s.cell_id;
     let cell_color = vec3<f32>(
-        hash2(n + min_point * 100.0),
-        hash2(n + min_point * 100.0 + vec2<f32>(17.0, 31.0)),
-        hash2(n + min_point * 100.0 + vec2<f32>(73.0, 89.0))
+        hash2(cell_id),
+        hash2(cell_id + vec2<f32>(17.0, 31.0)),
+        hash2(cell_id + vec2<f32>(73.0, 89.0))
     );
 
-    // Add edge highlight
-    let edge = smoothstep(0.0, 0.1, min_dist);
+    switch (u32(uniforms.params.w)) {
+        case 1u: { return cell_color * clamp(f2, 0.0, 1.0); }
+        case 2u: { return cell_color * smoothstep(0.0, 0.1, f2 - f1); }
+        default: { return cell_color * smoothstep(0.0, 0.1, f1); }
+    }
+}
 
-    return cell_color * edge;
+// Scalar value of one composite layer at p.
+// 1D models (polynomial, sine, Fourier, segmented) are profiles along x.
+fn layer_value(layer: Layer, p: vec2<f32>) -> f32 {
+    let base = layer.term_offset;
+    let count = layer.term_count;
+    switch (layer.content_type) {
+        case 0u: { return perlin_value(p, layer.params, layer.seed.xy); }
+        case 1u: { return poly_eval(p.x, base, count).x; }
+        case 2u: {
+            let max_iter = max(i32(layer.params.x), 1);
+            let i = fractal_iterations(layer.center.xy + p * 3.0, layer.center.zw, max_iter, layer.params.y, u32(layer.params.z));
+            return f32(i) / f32(max_iter);
+        }
+        case 4u: {
+            let s = voronoi_sample(p * layer.params.x, layer.params.y, u32(layer.params.z), seed_offset(layer.seed.xy));
+            return voronoi_feature(s, u32(layer.params.w));
+        }
+        case 5u: { return sine_eval(p.x, base, count, layer.params.x).x; }
+        case 6u: { return fourier_eval(p.x, base, count, layer.params.x).x; }
+        case 8u: { return segmented_eval(p.x, base, count).x; }
+        default: { return 0.0; }
+    }
+}
+
+// Layered composite: layers combine bottom to top; the first contributing
+// layer initializes the result, domain-warp layers displace later layers.
+fn generate_composite(uv: vec2<f32>) -> vec3<f32> {
+    var p = (uv - 0.5) / uniforms.zoom + uniforms.pan;
+    var acc = 0.0;
+    var started = false;
+
+    for (var i = 0u; i < min(uniforms.layer_count, MAX_LAYERS); i++) {
+        let layer = uniforms.layers[i];
+        let w = layer.weight.x;
+
+        if (layer.blend == 3u) {
+            let warp = vec2<f32>(layer_value(layer, p), layer_value(layer, p + vec2<f32>(5.2, 1.3)));
+            p += w * warp;
+            continue;
+        }
+
+        let v = w * layer_value(layer, p);
+        if (!started) {
+            acc = v;
+            started = true;
+        } else {
+            switch (layer.blend) {
+                case 1u: { acc *= v; }
+                case 2u: { acc = max(acc, v); }
+                default: { acc += v; }
+            }
+        }
+    }
+
+    return terrain_color(clamp(acc, 0.0, 1.0));
+}
+
+// ============================================
+// ASP Corrections
+// ============================================
+
+const ROI_OUTLINE_COLOR: vec3<f32> = vec3<f32>(1.0, 0.2, 0.8);
+
+// Frame pixel position under the view; the frame spans the view at zoom 1
+// with row 0 at the top
+fn frame_position(uv: vec2<f32>) -> vec2<f32> {
+    let frame_uv = (uv - 0.5) / uniforms.zoom + uniforms.pan + 0.5;
+    return frame_uv * uniforms.correction.zw;
+}
+
+fn roi_coverage(texel: vec2<i32>) -> f32 {
+    let size = vec2<i32>(textureDimensions(correction_tex));
+    if (any(texel < vec2<i32>(0)) || any(texel >= size)) {
+        return 0.0;
+    }
+    return textureLoad(correction_tex, texel, 0).a;
+}
+
+// Add the residuals covering this pixel
+fn apply_corrections(color: vec3<f32>, uv: vec2<f32>) -> vec3<f32> {
+    let texel = vec2<i32>(floor(frame_position(uv)));
+    if (roi_coverage(texel) < 0.5) {
+        return color;
+    }
+    // snorm texels are value / 127; residuals are value / 255
+    return color + textureLoad(correction_tex, texel, 0).rgb * (127.0 / 255.0);
+}
+
+// 1 on the border of ROI-covered pixels (about 1.5 screen pixels wide)
+fn roi_outline(uv: vec2<f32>) -> f32 {
+    let p = frame_position(uv);
+    let width = fwidth(p) * 1.5;
+    let texel = vec2<i32>(floor(p));
+    if (roi_coverage(texel) < 0.5) {
+        return 0.0;
+    }
+
+    let f = p - floor(p);
+    let left = f.x < width.x && roi_coverage(texel - vec2<i32>(1, 0)) < 0.5;
+    let right = f.x > 1.0 - width.x && roi_coverage(texel + vec2<i32>(1, 0)) < 0.5;
+    let top = f.y < width.y && roi_coverage(texel - vec2<i32>(0, 1)) < 0.5;
+    let bottom = f.y > 1.0 - width.y && roi_coverage(texel + vec2<i32>(0, 1)) < 0.5;
+    return select(0.0, 1.0, left || right || top || bottom);
 }
 
 // ============================================
@@ -255,12 +617,25 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
         case 2u: { color = generate_fractal(in.uv); }
         case 3u: { color = generate_gradient(in.uv); }
         case 4u: { color = generate_voronoi(in.uv); }
+        case 5u: { color = generate_sine(in.uv); }
+        case 6u: { color = generate_fourier(in.uv); }
+        case 8u: { color = generate_segmented(in.uv); }
+        case 9u: { color = generate_composite(in.uv); }
         default: { color = vec3<f32>(0.5, 0.5, 0.5); }
     }
 
-    // Subtle vignette
-    let vignette = 1.0 - length(in.uv - 0.5) * 0.5;
+    // Hybrid procedural + residual streams
+    if (uniforms.correction.x > 0.5) {
+        color = apply_corrections(color, in.uv);
+    }
+
+    // Subtle vignette (off for fidelity measurements)
+    let vignette = 1.0 - length(in.uv - 0.5) * uniforms.display.x;
     color *= vignette;
 
+    if (uniforms.correction.y > 0.5) {
+        color = mix(color, ROI_OUTLINE_COLOR, roi_outline(in.uv));
+    }
+
     return vec4<f32>(color, 1.0);
 }
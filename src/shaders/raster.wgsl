@@ -0,0 +1,103 @@
+// ALICE-View Raster Shader
+// Decoded images as a textured quad, placed by the same zoom/pan as the
+// procedural view; also compares decoded content with a reference image
+
+struct RasterUniforms {
+    resolution: vec2<f32>,
+    zoom: f32,
+    mode: u32,              // 0 = image, 1 = split, 2 = difference
+    pan: vec2<f32>,
+    image_size: vec2<f32>,  // true pixel dimensions
+    split: f32,             // split position (fraction of the image width)
+    _pad0: f32,
+    _pad1: f32,
+    _pad2: f32,
+}
+
+@group(0) @binding(0) var<uniform> uniforms: RasterUniforms;
+// The image, or the reference image when comparing
+@group(0) @binding(1) var image_tex: texture_2d<f32>;
+@group(0) @binding(2) var image_sampler: sampler;
+// Decoded content at the reference resolution (the image again when not comparing)
+@group(0) @binding(3) var overlay_tex: texture_2d<f32>;
+
+const MODE_SPLIT: u32 = 1u;
+const MODE_DIFFERENCE: u32 = 2u;
+// Channel error (0-1) that saturates the heatmap
+const DIFF_FULL_SCALE: f32 = 0.25;
+const SPLIT_LINE_COLOR: vec3<f32> = vec3<f32>(1.0, 1.0, 1.0);
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+// Two triangles covering the image; uv = (0, 0) is its top-left pixel
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0), vec2<f32>(1.0, 0.0), vec2<f32>(0.0, 1.0),
+        vec2<f32>(0.0, 1.0), vec2<f32>(1.0, 0.0), vec2<f32>(1.0, 1.0),
+    );
+    let corner = corners[vertex_index];
+
+    // At zoom 1 the image fits the view with its aspect ratio kept
+    let aspect = (uniforms.image_size.x / uniforms.image_size.y) / (uniforms.resolution.x / uniforms.resolution.y);
+    let fit = select(vec2<f32>(1.0, 1.0 / aspect), vec2<f32>(aspect, 1.0), aspect < 1.0);
+    let frame_uv = 0.5 + (corner - 0.5) * fit;
+
+    // Inverse of the procedural view mapping: frame_uv = (uv - 0.5) / zoom + pan + 0.5
+    let view_uv = (frame_uv - 0.5 - uniforms.pan) * uniforms.zoom + 0.5;
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(view_uv.x * 2.0 - 1.0, 1.0 - view_uv.y * 2.0, 0.0, 1.0);
+    out.uv = corner;
+    return out;
+}
+
+// Linear to sRGB transfer (texture samples are linear)
+fn to_srgb(c: vec3<f32>) -> vec3<f32> {
+    let low = c * 12.92;
+    let high = 1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, c <= vec3<f32>(0.0031308));
+}
+
+// Black -> blue -> red -> yellow -> white
+fn heatmap(t: f32) -> vec3<f32> {
+    let x = clamp(t, 0.0, 1.0) * 4.0;
+    if (x < 1.0) {
+        return mix(vec3<f32>(0.0), vec3<f32>(0.0, 0.0, 1.0), x);
+    } else if (x < 2.0) {
+        return mix(vec3<f32>(0.0, 0.0, 1.0), vec3<f32>(1.0, 0.0, 0.0), x - 1.0);
+    } else if (x < 3.0) {
+        return mix(vec3<f32>(1.0, 0.0, 0.0), vec3<f32>(1.0, 1.0, 0.0), x - 2.0);
+    }
+    return mix(vec3<f32>(1.0, 1.0, 0.0), vec3<f32>(1.0), x - 3.0);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let image = textureSample(image_tex, image_sampler, in.uv);
+    let overlay = textureSample(overlay_tex, image_sampler, in.uv);
+    let line_width = fwidth(in.uv.x) * 1.5;
+
+    switch (uniforms.mode) {
+        case MODE_SPLIT: {
+            // Decoded content left of the split, reference right of it
+            var color = select(image, overlay, in.uv.x < uniforms.split);
+            if (abs(in.uv.x - uniforms.split) < line_width) {
+                color = vec4<f32>(SPLIT_LINE_COLOR, 1.0);
+            }
+            return color;
+        }
+        case MODE_DIFFERENCE: {
+            // Largest channel error as the display shows it (sRGB)
+            let diff = abs(to_srgb(image.rgb) - to_srgb(overlay.rgb));
+            let error = max(diff.r, max(diff.g, diff.b));
+            return vec4<f32>(heatmap(error / DIFF_FULL_SCALE), 1.0);
+        }
+        default: {
+            return image;
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Fidelity panel for .alice/.alz content paired with a reference image
+//!
+//! Shows how well the equations reproduce the image they replace (PSNR,
+//! SSIM) next to what replacing it saved (compression ratio). The split and
+//! difference views are drawn by the raster pipeline and driven from the
+//! keyboard (V, [ and ]).
+
+use crate::app::{CompareView, ViewerState};
+use crate::decoder::{Decoder, ProceduralContent};
+use egui::{Color32, Stroke};
+
+/// Snapshot of the pairing, taken once per frame
+pub struct FidelityInfo {
+    /// File name of the reference image
+    name: String,
+    size: (u32, u32),
+    compression_ratio: f32,
+}
+
+impl FidelityInfo {
+    pub fn from_decoder(decoder: &Decoder) -> Option<Self> {
+        let Some(ProceduralContent::Raster { width, height, .. }) = decoder.reference() else {
+            return None;
+        };
+        let path = std::path::Path::new(decoder.reference_path()?);
+        Some(Self {
+            name: path.file_name().unwrap_or(path.as_os_str()).to_string_lossy().to_string(),
+            size: (*width, *height),
+            compression_ratio: decoder.compression_ratio(),
+        })
+    }
+
+    pub fn render(&self, ctx: &egui::Context, state: &ViewerState) {
+        egui::Area::new(egui::Id::new("fidelity_panel"))
+            .anchor(egui::Align2::LEFT_BOTTOM, [10.0, -10.0])
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style())
+                    .fill(Color32::from_rgba_premultiplied(10, 10, 10, 230))
+                    .stroke(Stroke::new(1.0, Color32::from_gray(60)))
+                    .inner_margin(8.0)
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new("FIDELITY").strong().color(Color32::WHITE));
+                        ui.add_space(4.0);
+
+                        egui::Grid::new("fidelity_grid").num_columns(2).spacing([20.0, 4.0]).show(ui, |ui| {
+                            ui.label("Reference:");
+                            ui.label(format!("{} ({}×{})", self.name, self.size.0, self.size.1));
+                            ui.end_row();
+
+                            match &state.stats.fidelity {
+                                Some(report) => {
+                                    ui.label("PSNR:");
+                                    ui.colored_label(psnr_color(report.psnr), format_psnr(report.psnr));
+                                    ui.end_row();
+
+                                    ui.label("SSIM:");
+                                    ui.colored_label(ssim_color(report.ssim), format!("{:.4}", report.ssim));
+                                    ui.end_row();
+
+                                    ui.label("Max error:");
+                                    ui.label(format!("{} / 255", report.max_error));
+                                    ui.end_row();
+                                }
+                                None => {
+                                    ui.label("PSNR / SSIM:");
+                                    ui.label(egui::RichText::new("measuring…").weak());
+                                    ui.end_row();
+                                }
+                            }
+
+                            ui.label("Ratio:");
+                            ui.label(format!("{:.1}x", self.compression_ratio));
+                            ui.end_row();
+
+                            ui.label("View:");
+                            ui.label(match state.compare_view {
+                                CompareView::Split => {
+                                    format!("◀ Decoded | Reference ▶ ({:.0}%)", state.compare_split * 100.0)
+                                }
+                                CompareView::Difference => "|Δ| heatmap (25% = full scale)".to_string(),
+                                CompareView::Off => "Decoded only".to_string(),
+                            });
+                            ui.end_row();
+                        });
+
+                        ui.label(
+                            egui::RichText::new("V: Split / Difference / Off  [ ]: Move split").small().weak(),
+                        );
+                    });
+            });
+    }
+}
+
+/// `xx.xx dB`, or ∞ for a lossless match
+fn format_psnr(psnr: f64) -> String {
+    if psnr.is_infinite() {
+        "∞ dB (lossless)".to_string()
+    } else {
+        format!("{:.2} dB", psnr)
+    }
+}
+
+/// Green above 40 dB (visually lossless), yellow above 30 dB
+fn psnr_color(psnr: f64) -> Color32 {
+    if psnr > 40.0 {
+        Color32::GREEN
+    } else if psnr > 30.0 {
+        Color32::YELLOW
+    } else {
+        Color32::RED
+    }
+}
+
+fn ssim_color(ssim: f64) -> Color32 {
+    if ssim > 0.95 {
+        Color32::GREEN
+    } else if ssim > 0.85 {
+        Color32::YELLOW
+    } else {
+        Color32::RED
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! Displays .alice file details: equation, compression, metadata
 
-use crate::decoder::alice::{AliceFile, AlicePayload};
+use crate::decoder::alice::{self, AliceFile, AlicePayload, ChecksumStatus, FitStats, META_MODEL};
 use egui::{Color32, RichText, Ui};
 
 /// File information for display
@@ -12,6 +12,10 @@ pub struct FileInfo {
     pub path: Option<String>,
     /// Content type name
     pub content_type: String,
+    /// Format version
+    pub version: u8,
+    /// Payload checksum verification result
+    pub checksum: ChecksumStatus,
     /// Human-readable equation
     pub equation: String,
     /// Compression ratio
@@ -30,6 +34,8 @@ pub struct FileInfo {
     pub unit: Option<String>,
     /// Description
     pub description: Option<String>,
+    /// Seconds between samples
+    pub sample_interval: Option<f64>,
     /// Sample count (for linear)
     pub sample_count: Option<u32>,
     /// Extra details (content-specific)
@@ -42,6 +48,8 @@ impl FileInfo {
         let mut info = Self {
             path: path.map(|s| s.to_string()),
             content_type: file.content_type_name().to_string(),
+            version: file.header.version,
+            checksum: file.header.checksum_status,
             equation: file.equation_string(),
             compression_ratio: file.compression_ratio(),
             original_size: file.header.original_size,
@@ -51,6 +59,7 @@ impl FileInfo {
             location: file.metadata.location.clone(),
             unit: file.metadata.unit.clone(),
             description: file.metadata.description.clone(),
+            sample_interval: file.metadata.sample_interval,
             sample_count: None,
             details: Vec::new(),
         };
@@ -64,6 +73,75 @@ impl FileInfo {
                 info.details.push(("Slope (float)".to_string(), format!("{:.6}", p.slope_f32())));
                 info.details.push(("Intercept (float)".to_string(), format!("{:.4}", p.intercept_f32())));
             }
+            AlicePayload::Polynomial(p) => {
+                info.sample_count = Some(p.sample_count);
+                info.details.push(("Degree".to_string(), format!("{}", p.degree())));
+                info.details.push(("Encoding".to_string(), p.encoding_name().to_string()));
+                for (i, c) in p.coefficients_f64().iter().enumerate() {
+                    info.details.push((format!("c{}", i), format!("{:.6}", c)));
+                }
+            }
+            AlicePayload::Fourier(p) => {
+                info.sample_count = Some(p.sample_count);
+                info.details.push(("Period".to_string(), format!("{}", p.period)));
+                info.details.push(("Terms".to_string(), format!("{}", p.terms.len())));
+                for t in &p.terms {
+                    info.details.push((
+                        format!("k={}", t.frequency),
+                        format!("A={:.4}, φ={:.4}", t.amplitude, t.phase),
+                    ));
+                }
+            }
+            AlicePayload::SineWave(p) => {
+                info.sample_count = Some(p.sample_count);
+                info.details.push(("Offset".to_string(), format!("{:.4}", p.offset)));
+                for (i, c) in p.components.iter().enumerate() {
+                    info.details.push((
+                        format!("Wave {}", i + 1),
+                        format!("f={:.4}, A={:.4}, φ={:.4}", c.frequency, c.amplitude, c.phase),
+                    ));
+                }
+            }
+            AlicePayload::Segmented(p) => {
+                info.sample_count = Some(p.sample_count);
+                info.details.push(("Segments".to_string(), format!("{}", p.segments.len())));
+                for (i, seg) in p.segments.iter().enumerate() {
+                    let end = p.segments.get(i + 1).map_or(p.domain_end(), |next| next.start_index);
+                    info.details.push((
+                        format!("Segment {} [{}..{})", i + 1, seg.start_index, end),
+                        seg.model.equation_string(),
+                    ));
+                }
+            }
+            AlicePayload::Composite(p) => {
+                info.details.push(("Layers".to_string(), format!("{}", p.layers.len())));
+                let rendered = p.layers_rendered();
+                for (i, (layer, shown)) in p.layers.iter().zip(&rendered).enumerate() {
+                    let note = if *shown { "" } else { " ⚠ not rendered" };
+                    info.details.push((
+                        format!("Layer {} ({}, ×{:.2}){}", i + 1, layer.blend.name(), layer.weight, note),
+                        format!("{}: {}", layer.payload.content_type().name(), layer.payload.equation_string()),
+                    ));
+                }
+                let dropped = rendered.iter().filter(|shown| !**shown).count();
+                if dropped > 0 {
+                    info.details.push((
+                        "⚠ Warning".to_string(),
+                        format!(
+                            "{} layer(s) exceed the {} shared term slots and are not rendered",
+                            dropped,
+                            alice::CompositePayload::MAX_TERM_SLOTS
+                        ),
+                    ));
+                }
+            }
+            AlicePayload::Voronoi(p) => {
+                info.details.push(("Seed".to_string(), format!("{}", p.seed)));
+                info.details.push(("Cell Density".to_string(), format!("{:.2}", p.cell_density)));
+                info.details.push(("Metric".to_string(), p.metric.name().to_string()));
+                info.details.push(("Feature".to_string(), p.feature.name().to_string()));
+                info.details.push(("Jitter".to_string(), format!("{:.2}", p.jitter)));
+            }
             AlicePayload::Perlin(p) => {
                 info.details.push(("Seed".to_string(), format!("{}", p.seed)));
                 info.details.push(("Scale".to_string(), format!("{:.2}", p.scale)));
@@ -82,6 +160,16 @@ impl FileInfo {
             }
         }
 
+        // Residual statistics from `alice-create fit`
+        if let Some(stats) = FitStats::from_metadata(&file.metadata) {
+            if let Some(model) = file.metadata.custom.get(META_MODEL).and_then(|v| v.as_str()) {
+                info.details.push(("Fit Model".to_string(), model.to_string()));
+            }
+            let unit = info.unit.as_deref().map(|u| format!(" {}", u)).unwrap_or_default();
+            info.details.push(("RMSE (band)".to_string(), format!("±{:.6}{}", stats.rmse, unit)));
+            info.details.push(("Max Abs Error (band)".to_string(), format!("±{:.6}{}", stats.max_abs_error, unit)));
+        }
+
         info
     }
 
@@ -159,6 +247,23 @@ impl FileInfo {
                     ui.label(format!("{}", count));
                 });
             }
+
+            ui.horizontal(|ui| {
+                ui.label("Checksum:");
+                match self.checksum {
+                    ChecksumStatus::Valid(crc) => {
+                        ui.label(RichText::new("✔ OK").color(Color32::from_rgb(100, 255, 100)));
+                        ui.monospace(format!("{:08x}", crc));
+                    }
+                    ChecksumStatus::Mismatch { stored, computed } => {
+                        ui.label(RichText::new("✘ Mismatch").color(Color32::from_rgb(255, 100, 100)).strong())
+                            .on_hover_text(format!("stored {:08x}, computed {:08x}", stored, computed));
+                    }
+                    ChecksumStatus::Absent => {
+                        ui.label(RichText::new(format!("none (v{})", self.version)).color(Color32::GRAY));
+                    }
+                }
+            });
         });
 
         ui.add_space(8.0);
@@ -168,7 +273,8 @@ impl FileInfo {
             || self.timestamp.is_some()
             || self.location.is_some()
             || self.unit.is_some()
-            || self.description.is_some();
+            || self.description.is_some()
+            || self.sample_interval.is_some();
 
         if has_metadata {
             ui.group(|ui| {
@@ -199,6 +305,12 @@ impl FileInfo {
                         ui.label(unit);
                     });
                 }
+                if let Some(interval) = self.sample_interval {
+                    ui.horizontal(|ui| {
+                        ui.label("Sample Interval:");
+                        ui.label(format!("{} s", interval));
+                    });
+                }
                 if let Some(ref desc) = self.description {
                     ui.horizontal(|ui| {
                         ui.label("Description:");
@@ -7,6 +7,9 @@
 mod viewport;
 mod xray;
 mod stats;
+mod plot;
+mod timeline;
+mod fidelity;
 pub mod file_info;
 pub mod sdf_panel;
 pub mod export;
@@ -17,16 +20,19 @@ pub mod export;
 pub use viewport::*;
 pub use xray::*;
 pub use stats::*;
+pub use plot::*;
+pub use timeline::*;
+pub use fidelity::*;
 pub use file_info::*;
 pub use sdf_panel::*;
 pub use export::*;
 
-use crate::app::{RenderMode, ViewerState, XRayType};
-use crate::decoder::Decoder;
+use crate::app::{CompareView, RasterFilter, RenderMode, ViewerState, XRayType};
+use crate::decoder::{AspSeek, Decoder, ProceduralContent};
 use egui::FullOutput;
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::thread;
-use winit::event::WindowEvent;
+use winit::event::{ElementState, MouseButton, WindowEvent};
 
 /// UI state and rendering
 pub struct Ui {
@@ -42,6 +48,24 @@ pub struct Ui {
     file_loader_tx: Sender<String>,
     /// Current file info
     current_file_info: Option<FileInfo>,
+    /// Axes and hover readout for sampled series
+    plot_overlay: Option<PlotOverlay>,
+    /// Decoder revision `plot_overlay` was built for
+    plot_revision: Option<u64>,
+    /// Playback position of a loaded .asp recording
+    timeline: Option<TimelineInfo>,
+    /// Frame picked on the timeline, applied on the next update
+    seek_request: Option<usize>,
+    /// Pointer events for the next egui frame
+    input_events: Vec<egui::Event>,
+    /// Last cursor position (pixels)
+    pointer_pos: Option<egui::Pos2>,
+    /// Pixel size of the raster image shown in the stats
+    raster_size: Option<(u32, u32)>,
+    /// Reference image paired with the loaded .alice/.alz file
+    fidelity: Option<FidelityInfo>,
+    /// Reference image to pair once pending loads are done (Shift+drop)
+    pending_reference: Option<String>,
     /// SDF control panel
     sdf_panel: SdfPanel,
     /// Pending WGSL shader for pipeline rebuild (set when .asdf is loaded)
@@ -64,6 +88,15 @@ impl Ui {
             file_loader_rx: rx,
             file_loader_tx: tx,
             current_file_info: None,
+            plot_overlay: None,
+            plot_revision: None,
+            timeline: None,
+            seek_request: None,
+            input_events: Vec::new(),
+            pointer_pos: None,
+            raster_size: None,
+            fidelity: None,
+            pending_reference: None,
             sdf_panel: SdfPanel::new(),
             pending_wgsl: None,
             export_status_rx: erx,
@@ -85,9 +118,44 @@ impl Ui {
     }
 
     /// Handle window events
-    pub fn handle_event(&mut self, _event: &WindowEvent, _ctx: &egui::Context) -> egui_winit::EventResponse {
+    ///
+    /// Passes the pointer on to egui (one point per pixel, as the UI is
+    /// drawn). Presses over a panel are consumed so they do not also move the
+    /// camera.
+    pub fn handle_event(&mut self, event: &WindowEvent, ctx: &egui::Context) -> egui_winit::EventResponse {
+        let mut consumed = false;
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                let pos = egui::pos2(position.x as f32, position.y as f32);
+                self.pointer_pos = Some(pos);
+                self.input_events.push(egui::Event::PointerMoved(pos));
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.pointer_pos = None;
+                self.input_events.push(egui::Event::PointerGone);
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                let button = match button {
+                    MouseButton::Left => Some(egui::PointerButton::Primary),
+                    MouseButton::Right => Some(egui::PointerButton::Secondary),
+                    MouseButton::Middle => Some(egui::PointerButton::Middle),
+                    _ => None,
+                };
+                if let (Some(button), Some(pos)) = (button, self.pointer_pos) {
+                    let pressed = *state == ElementState::Pressed;
+                    consumed = pressed && ctx.is_pointer_over_area();
+                    self.input_events.push(egui::Event::PointerButton {
+                        pos,
+                        button,
+                        pressed,
+                        modifiers: egui::Modifiers::default(),
+                    });
+                }
+            }
+            _ => {}
+        }
         egui_winit::EventResponse {
-            consumed: false,
+            consumed,
             repaint: false,
         }
     }
@@ -105,6 +173,37 @@ impl Ui {
         self.stats_collector.record_frame();
         state.stats.fps = self.stats_collector.fps();
 
+        // Stream playback follows the frame clock; long stalls (file loads)
+        // are capped so playback does not jump ahead
+        if !state.paused {
+            let dt = (self.stats_collector.last_frame_ms() / 1000.0).min(0.25);
+            decoder.advance_playback(dt as f64);
+            decoder.poll_stream();
+        } else {
+            decoder.poll_playback();
+        }
+        state.stats.stream = decoder.asp_listener().map(|listener| listener.stats());
+        if let Some(frame) = self.seek_request.take() {
+            decoder.seek_playback(AspSeek::Frame(frame));
+        }
+
+        // True pixel dimensions for images, unbounded for procedural content
+        let raster_size = match decoder.content() {
+            Some(ProceduralContent::Raster { width, height, .. }) => Some((*width, *height)),
+            _ => None,
+        };
+        if raster_size != self.raster_size {
+            self.raster_size = raster_size;
+            state.stats.resolution = match raster_size {
+                Some((width, height)) => format!("{}×{} px", width, height),
+                None => "∞ (Procedural)".to_string(),
+            };
+        }
+        self.timeline = decoder
+            .asp_player()
+            .map(TimelineInfo::from_player)
+            .or_else(|| decoder.video_player().map(TimelineInfo::from_video));
+
         // Check export status
         while let Ok(status) = self.export_status_rx.try_recv() {
             match &status {
@@ -170,6 +269,24 @@ impl Ui {
                 }
             }
         }
+
+        // Axes follow the shown content however it was loaded (command
+        // line, dialog, drop) or advanced
+        if self.plot_revision != Some(decoder.revision()) {
+            self.plot_revision = Some(decoder.revision());
+            self.plot_overlay = decoder
+                .alice_file()
+                .zip(decoder.content())
+                .and_then(|(file, content)| PlotOverlay::new(file, content));
+        }
+
+        if let Some(path) = self.pending_reference.take() {
+            match decoder.pair_reference(&path) {
+                Ok(()) => tracing::info!("Comparing with reference image: {}", path),
+                Err(e) => tracing::error!("Failed to pair reference image: {:#}", e),
+            }
+        }
+        self.fidelity = FidelityInfo::from_decoder(decoder);
     }
 
     /// Take pending WGSL shader (for pipeline rebuild)
@@ -185,11 +302,24 @@ impl Ui {
         self.file_info_open = !self.file_info_open;
     }
 
+    /// Switch the plot x axis between sample index and time
+    pub fn toggle_plot_x_axis(&mut self) {
+        if let Some(plot) = &mut self.plot_overlay {
+            plot.toggle_x_axis();
+            tracing::info!("Plot x axis: {:?}", plot.x_axis);
+        }
+    }
+
     /// Queue a file path for loading (used by drag-and-drop)
     pub fn queue_file(&self, path: String) {
         let _ = self.file_loader_tx.send(path);
     }
 
+    /// Queue a reference image to compare the loaded file with (Shift+drop)
+    pub fn queue_reference(&mut self, path: String) {
+        self.pending_reference = Some(path);
+    }
+
     /// Open file dialog asynchronously (non-blocking)
     fn open_file_dialog(&self) {
         let tx = self.file_loader_tx.clone();
@@ -198,7 +328,7 @@ impl Ui {
             if let Some(path) = rfd::FileDialog::new()
                 .add_filter("ALICE SDF", &["asdf", "json"])
                 .add_filter("ALICE Files", &["alz", "alice", "asp"])
-                .add_filter("Images", &["png", "jpg", "jpeg", "bmp"])
+                .add_filter("Images", &["png", "jpg", "jpeg", "bmp", "gif"])
                 .add_filter("All Files", &["*"])
                 .pick_file()
             {
@@ -208,9 +338,13 @@ impl Ui {
     }
 
     /// Render UI
-    pub fn render(&mut self, ctx: &egui::Context, state: &mut ViewerState) -> FullOutput {
+    pub fn render(&mut self, ctx: &egui::Context, state: &mut ViewerState, screen_size: [f32; 2]) -> FullOutput {
         // Begin egui frame
-        ctx.begin_frame(egui::RawInput::default());
+        ctx.begin_frame(egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, screen_size.into())),
+            events: std::mem::take(&mut self.input_events),
+            ..Default::default()
+        });
 
         // 1. Top Menu Bar
         egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
@@ -265,6 +399,34 @@ impl Ui {
                         ui.close_menu();
                     }
 
+                    // ASP correction ROIs (C)
+                    if ui.checkbox(&mut state.show_roi_outlines, "▢ ROI Outlines (C)").clicked() {
+                        ui.close_menu();
+                    }
+
+                    ui.separator();
+                    ui.label(egui::RichText::new("Image Filter (F)").strong());
+                    ui.radio_value(&mut state.raster_filter, RasterFilter::Linear, "Linear");
+                    ui.radio_value(&mut state.raster_filter, RasterFilter::Nearest, "Nearest");
+
+                    if self.fidelity.is_some() {
+                        ui.separator();
+                        ui.label(egui::RichText::new("Compare (V)").strong());
+                        ui.radio_value(&mut state.compare_view, CompareView::Split, "◧ Split");
+                        ui.radio_value(&mut state.compare_view, CompareView::Difference, "🔥 Difference");
+                        ui.radio_value(&mut state.compare_view, CompareView::Off, "Off");
+                    }
+
+                    if let Some(plot) = &mut self.plot_overlay {
+                        ui.separator();
+                        ui.label(egui::RichText::new("Plot X Axis (T)").strong());
+                        ui.radio_value(&mut plot.x_axis, PlotXAxis::Samples, "# Samples");
+                        let has_time = plot.has_time();
+                        ui.add_enabled_ui(has_time, |ui| {
+                            ui.radio_value(&mut plot.x_axis, PlotXAxis::Time, "🕒 Time");
+                        });
+                    }
+
                     ui.separator();
                     ui.label(egui::RichText::new("X-Ray Type (Tab)").strong());
 
@@ -352,6 +514,27 @@ impl Ui {
             }
         }
 
+        // Stream timeline (bottom)
+        if let Some(ref timeline) = self.timeline {
+            if let Some(frame) = timeline.render(ctx, state.paused) {
+                self.seek_request = Some(frame);
+            }
+        }
+
+        // Fidelity against the paired reference image
+        if state.render_mode == RenderMode::Procedural2D {
+            if let Some(fidelity) = &self.fidelity {
+                fidelity.render(ctx, state);
+            }
+        }
+
+        // Plot axes and hover readout (in the space left by the panels)
+        if state.render_mode == RenderMode::Procedural2D {
+            if let Some(plot) = &self.plot_overlay {
+                plot.render(ctx, state);
+            }
+        }
+
         // 5. About Dialog
         if self.about_open {
             egui::Window::new("About ALICE-View")
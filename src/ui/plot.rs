@@ -0,0 +1,423 @@
+//! Plot overlay for sampled series (Linear / Polynomial models)
+//!
+//! The curve itself is drawn by the procedural shader; this overlay adds
+//! axes with tick labels and a hover readout of the model value, using the
+//! same view mapping ([`PlotFrame`] + `ViewerState.zoom/pan`).
+
+use crate::app::ViewerState;
+use crate::decoder::alice::{AliceFile, AlicePayload};
+use crate::decoder::{PlotFrame, ProceduralContent};
+use chrono::{DateTime, Duration, FixedOffset};
+use egui::{Align2, Color32, FontId, Pos2, Rect, Stroke};
+
+const GRID_COLOR: Color32 = Color32::from_rgba_premultiplied(40, 40, 48, 40);
+const AXIS_COLOR: Color32 = Color32::from_rgb(150, 150, 160);
+const HOVER_COLOR: Color32 = Color32::from_rgb(255, 200, 100);
+
+/// Tick steps (seconds) used for wall-clock axes
+const TIME_STEPS: [f64; 18] = [
+    1.0, 2.0, 5.0, 10.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0, 900.0, 1800.0, 3600.0, 7200.0, 10800.0,
+    21600.0, 43200.0, 86400.0,
+];
+
+/// What the x axis is labelled in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlotXAxis {
+    /// Sample index
+    Samples,
+    /// Time derived from the metadata timestamp and sample interval
+    Time,
+}
+
+/// Axes and hover readout for the loaded series
+pub struct PlotOverlay {
+    frame: PlotFrame,
+    payload: AlicePayload,
+    unit: Option<String>,
+    /// Seconds per sample
+    sample_interval: Option<f64>,
+    /// Time of sample 0 (absolute axis when set, elapsed seconds otherwise)
+    start: Option<DateTime<FixedOffset>>,
+    pub x_axis: PlotXAxis,
+}
+
+/// Screen ↔ view mapping used by procedural.wgsl (uv.y = 0 at the top)
+struct ViewTransform {
+    screen: Rect,
+    zoom: f64,
+    pan: [f64; 2],
+}
+
+impl ViewTransform {
+    fn to_screen(&self, view_x: f64, view_y: f64) -> Pos2 {
+        let u = (view_x / 4.0 - self.pan[0]) * self.zoom + 0.5;
+        let v = (-view_y / 4.0 - self.pan[1]) * self.zoom + 0.5;
+        Pos2::new(
+            self.screen.left() + (u * self.screen.width() as f64) as f32,
+            self.screen.top() + (v * self.screen.height() as f64) as f32,
+        )
+    }
+
+    fn to_view(&self, p: Pos2) -> (f64, f64) {
+        let u = ((p.x - self.screen.left()) / self.screen.width()) as f64;
+        let v = ((p.y - self.screen.top()) / self.screen.height()) as f64;
+        (((u - 0.5) / self.zoom + self.pan[0]) * 4.0, -((v - 0.5) / self.zoom + self.pan[1]) * 4.0)
+    }
+}
+
+impl PlotOverlay {
+    /// Overlay for `file` when its content is plotted against sample position
+    pub fn new(file: &AliceFile, content: &ProceduralContent) -> Option<Self> {
+        let frame = content.plot_frame()?;
+        file.payload.evaluate(0.0)?;
+
+        let sample_interval = file.metadata.sample_interval.filter(|dt| *dt > 0.0 && dt.is_finite());
+        let start = file
+            .metadata
+            .timestamp
+            .as_deref()
+            .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok());
+
+        Some(Self {
+            frame,
+            payload: file.payload.clone(),
+            unit: file.metadata.unit.clone(),
+            sample_interval,
+            start,
+            x_axis: if sample_interval.is_some() { PlotXAxis::Time } else { PlotXAxis::Samples },
+        })
+    }
+
+    /// Whether samples can be labelled with a time
+    pub fn has_time(&self) -> bool {
+        self.sample_interval.is_some()
+    }
+
+    /// Switch between sample and time labels (stays on samples without a
+    /// sample interval)
+    pub fn toggle_x_axis(&mut self) {
+        self.x_axis = match self.x_axis {
+            PlotXAxis::Time => PlotXAxis::Samples,
+            PlotXAxis::Samples if self.has_time() => PlotXAxis::Time,
+            PlotXAxis::Samples => PlotXAxis::Samples,
+        };
+    }
+
+    /// Draw gridlines, tick labels and the hover readout
+    pub fn render(&self, ctx: &egui::Context, state: &ViewerState) {
+        let view = ViewTransform {
+            screen: ctx.screen_rect(),
+            zoom: state.zoom as f64,
+            pan: [state.pan[0] as f64, state.pan[1] as f64],
+        };
+        // Stay clear of the menu bar and side panels
+        let area = ctx.available_rect();
+        if area.width() < 80.0 || area.height() < 60.0 {
+            return;
+        }
+        let painter = ctx
+            .layer_painter(egui::LayerId::new(egui::Order::Background, egui::Id::new("plot_overlay")))
+            .with_clip_rect(area);
+        let font = FontId::monospace(11.0);
+
+        let (left, top) = view.to_view(area.left_top());
+        let (right, bottom) = view.to_view(area.right_bottom());
+
+        // X axis: sample index or time
+        let axis_y = area.bottom() - 18.0;
+        let x_target = (area.width() / 110.0).max(2.0) as f64;
+        for (x, label) in self.x_ticks(self.frame.sample_x(left), self.frame.sample_x(right), x_target) {
+            let sx = view.to_screen(self.frame.view_x(x), 0.0).x;
+            painter.vline(sx, area.top()..=axis_y, Stroke::new(1.0, GRID_COLOR));
+            painter.vline(sx, axis_y..=axis_y + 4.0, Stroke::new(1.0, AXIS_COLOR));
+            painter.text(Pos2::new(sx, axis_y + 4.0), Align2::CENTER_TOP, label, font.clone(), AXIS_COLOR);
+        }
+        painter.hline(area.left()..=area.right(), axis_y, Stroke::new(1.0, AXIS_COLOR));
+
+        // Y axis: model value
+        let axis_x = area.left() + 4.0;
+        let (v_min, v_max) = (self.frame.value(bottom), self.frame.value(top));
+        let y_step = nice_step(v_max - v_min, (area.height() / 60.0).max(2.0) as f64);
+        for v in ticks(v_min, v_max, y_step) {
+            let sy = view.to_screen(0.0, self.frame.view_y(v)).y;
+            if sy > axis_y - 8.0 {
+                continue;
+            }
+            painter.hline(area.left()..=area.right(), sy, Stroke::new(1.0, GRID_COLOR));
+            painter.text(Pos2::new(axis_x, sy), Align2::LEFT_BOTTOM, format_tick(v, y_step), font.clone(), AXIS_COLOR);
+        }
+
+        // Axis titles
+        let x_title = match (self.x_axis, self.start) {
+            (PlotXAxis::Time, Some(t0)) => format!("time (UTC{})", t0.offset()),
+            (PlotXAxis::Time, None) => "time since first sample".to_string(),
+            (PlotXAxis::Samples, _) => "sample".to_string(),
+        };
+        painter.text(Pos2::new(area.right() - 4.0, axis_y - 4.0), Align2::RIGHT_BOTTOM, x_title, font.clone(), AXIS_COLOR);
+        let y_title = self.unit.as_deref().unwrap_or("value");
+        painter.text(area.left_top() + egui::vec2(4.0, 4.0), Align2::LEFT_TOP, y_title, font.clone(), AXIS_COLOR);
+
+        // Hover readout of evaluate(x) under the pointer
+        let Some(cursor) = state.cursor_pos.map(|[x, y]| Pos2::new(x, y)) else {
+            return;
+        };
+        if !area.contains(cursor) {
+            return;
+        }
+        let (view_x, _) = view.to_view(cursor);
+        let x = self.frame.sample_x(view_x);
+        let Some(value) = self.payload.evaluate(x).filter(|v| v.is_finite()) else {
+            return;
+        };
+        let point = view.to_screen(view_x, self.frame.view_y(value));
+        painter.vline(cursor.x, area.top()..=axis_y, Stroke::new(1.0, HOVER_COLOR.gamma_multiply(0.4)));
+        painter.circle_filled(point, 4.0, HOVER_COLOR);
+
+        let mut text = format!("x = {:.2}", x);
+        if let Some(time) = self.time_label(x) {
+            text.push_str(&format!("\nt = {}", time));
+        }
+        text.push_str(&format!("\ny = {:.4}", value));
+        if let Some(unit) = &self.unit {
+            text.push_str(&format!(" {}", unit));
+        }
+        let galley = painter.layout_no_wrap(text, font, Color32::WHITE);
+        let size = galley.size();
+        let mut pos = cursor + egui::vec2(14.0, -14.0 - size.y);
+        pos.x = pos.x.min(area.right() - size.x - 6.0);
+        pos.y = pos.y.max(area.top() + 6.0);
+        painter.rect_filled(Rect::from_min_size(pos, size).expand(4.0), 3.0, Color32::from_black_alpha(200));
+        painter.galley(pos, galley, Color32::WHITE);
+    }
+
+    /// X tick positions (samples) and labels between samples `lo` and `hi`
+    fn x_ticks(&self, lo: f64, hi: f64, target: f64) -> Vec<(f64, String)> {
+        let interval = match (self.x_axis, self.sample_interval) {
+            (PlotXAxis::Time, Some(dt)) => dt,
+            _ => {
+                let step = nice_step(hi - lo, target);
+                return ticks(lo, hi, step).map(|x| (x, format_tick(x, step))).collect();
+            }
+        };
+
+        let step = time_step((hi - lo) * interval, target);
+        match self.start {
+            Some(t0) => {
+                // Align ticks to the wall clock in the timestamp's own offset
+                let local0 = t0.naive_local().and_utc().timestamp_millis() as f64 / 1000.0;
+                let format = if step >= 86400.0 {
+                    "%Y-%m-%d"
+                } else if (hi - lo) * interval >= 86400.0 {
+                    "%m-%d %H:%M"
+                } else if step >= 60.0 {
+                    "%H:%M"
+                } else if step >= 1.0 {
+                    "%H:%M:%S"
+                } else {
+                    "%H:%M:%S%.3f"
+                };
+                ticks(local0 + lo * interval, local0 + hi * interval, step)
+                    .map(|local| {
+                        let secs = local - local0;
+                        let label = (t0 + seconds(secs)).format(format).to_string();
+                        (secs / interval, label)
+                    })
+                    .collect()
+            }
+            None => ticks(lo * interval, hi * interval, step)
+                .map(|secs| (secs / interval, format_elapsed(secs, step)))
+                .collect(),
+        }
+    }
+
+    /// Time of sample `x`, if the metadata defines one
+    fn time_label(&self, x: f64) -> Option<String> {
+        let secs = x * self.sample_interval?;
+        Some(match self.start {
+            Some(t0) => (t0 + seconds(secs)).format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+            None => format!("{:.3}s", secs),
+        })
+    }
+}
+
+fn seconds(secs: f64) -> Duration {
+    Duration::nanoseconds((secs * 1e9).round() as i64)
+}
+
+/// Tick step of the form {1, 2, 5}·10^k giving about `target` ticks over `span`
+fn nice_step(span: f64, target: f64) -> f64 {
+    let raw = (span / target).max(1e-12);
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let norm = raw / magnitude;
+    let nice = if norm <= 1.0 {
+        1.0
+    } else if norm <= 2.0 {
+        2.0
+    } else if norm <= 5.0 {
+        5.0
+    } else {
+        10.0
+    };
+    nice * magnitude
+}
+
+/// Clock-friendly tick step (seconds) for a time span
+fn time_step(span: f64, target: f64) -> f64 {
+    let raw = span / target;
+    if raw < 1.0 {
+        return nice_step(span, target);
+    }
+    TIME_STEPS
+        .iter()
+        .copied()
+        .find(|&s| s >= raw)
+        .unwrap_or_else(|| 86400.0 * nice_step(span / 86400.0, target))
+}
+
+/// Multiples of `step` within [lo, hi]
+fn ticks(lo: f64, hi: f64, step: f64) -> impl Iterator<Item = f64> {
+    let first = (lo / step).ceil();
+    let last = (hi / step).floor();
+    // Degenerate spans (zoomed past f64 resolution) draw no ticks
+    let count = if step > 0.0 && last >= first && last - first < 1000.0 { (last - first) as i64 + 1 } else { 0 };
+    (0..count).map(move |i| {
+        let v = (first + i as f64) * step;
+        if v.abs() < step * 1e-6 { 0.0 } else { v }
+    })
+}
+
+fn format_tick(v: f64, step: f64) -> String {
+    if v != 0.0 && (v.abs() >= 1e7 || step < 1e-5) {
+        return format!("{:.3e}", v);
+    }
+    let decimals = (-step.log10().floor()).clamp(0.0, 6.0) as usize;
+    format!("{:.*}", decimals, v)
+}
+
+fn format_elapsed(secs: f64, step: f64) -> String {
+    if step < 60.0 {
+        return format!("{}s", format_tick(secs, step));
+    }
+    let total = secs.round() as i64;
+    let sign = if total < 0 { "-" } else { "" };
+    let total = total.abs();
+    format!("{}{}:{:02}:{:02}", sign, total / 3600, total / 60 % 60, total % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::alice::LinearPayload;
+
+    fn overlay(x_axis: PlotXAxis, start: Option<&str>) -> PlotOverlay {
+        PlotOverlay {
+            frame: PlotFrame::new(100, (0.0, 10.0)),
+            payload: AlicePayload::Linear(LinearPayload { slope_q16: 1 << 16, intercept_q16: 0, sample_count: 100 }),
+            unit: None,
+            sample_interval: Some(1.0),
+            start: start.map(|ts| DateTime::parse_from_rfc3339(ts).unwrap()),
+            x_axis,
+        }
+    }
+
+    #[test]
+    fn test_nice_step() {
+        assert_eq!(nice_step(10.0, 5.0), 2.0);
+        assert_eq!(nice_step(100.0, 4.0), 50.0);
+        assert_eq!(nice_step(7.0, 1.0), 10.0);
+        assert!((nice_step(1.0, 5.0) - 0.2).abs() < 1e-12);
+
+        // Empty or inverted spans still give a positive step
+        assert_eq!(nice_step(0.0, 5.0), 1e-12);
+        assert_eq!(nice_step(-3.0, 5.0), 1e-12);
+    }
+
+    #[test]
+    fn test_time_step() {
+        assert_eq!(time_step(60.0, 4.0), 15.0);
+        assert_eq!(time_step(7200.0, 4.0), 1800.0);
+        assert!((time_step(0.5, 5.0) - 0.1).abs() < 1e-12);
+        // Beyond a day: whole days
+        assert_eq!(time_step(10.0 * 86400.0, 2.0), 5.0 * 86400.0);
+    }
+
+    #[test]
+    fn test_ticks() {
+        assert_eq!(ticks(0.0, 10.0, 2.0).collect::<Vec<_>>(), [0.0, 2.0, 4.0, 6.0, 8.0, 10.0]);
+        assert_eq!(ticks(-1.5, 1.5, 1.0).collect::<Vec<_>>(), [-1.0, 0.0, 1.0]);
+        assert_eq!(ticks(0.15, 0.35, 0.1).count(), 2);
+
+        // Equal min and max: one tick when it falls on a multiple of the step
+        assert_eq!(ticks(3.0, 3.0, 1.0).collect::<Vec<_>>(), [3.0]);
+        assert_eq!(ticks(2.5, 2.5, 1.0).count(), 0);
+        assert!(ticks(2.5, 2.5, nice_step(0.0, 5.0)).count() <= 1);
+
+        // Degenerate ranges draw nothing
+        assert_eq!(ticks(5.0, 1.0, 1.0).count(), 0);
+        assert_eq!(ticks(0.0, 1.0, 0.0).count(), 0);
+        assert_eq!(ticks(0.0, 1e9, 1.0).count(), 0);
+        assert_eq!(ticks(f64::NAN, 1.0, 1.0).count(), 0);
+    }
+
+    #[test]
+    fn test_format_tick_and_elapsed() {
+        assert_eq!(format_tick(2.5, 0.5), "2.5");
+        assert_eq!(format_tick(100.0, 10.0), "100");
+        assert_eq!(format_tick(1e8, 1e7), "1.000e8");
+        assert_eq!(format_tick(3e-6, 1e-6), "3.000e-6");
+
+        assert_eq!(format_elapsed(1.5, 0.5), "1.5s");
+        assert_eq!(format_elapsed(90.0, 60.0), "0:01:30");
+        assert_eq!(format_elapsed(-3600.0, 600.0), "-1:00:00");
+    }
+
+    #[test]
+    fn test_x_ticks() {
+        let labels = |overlay: &PlotOverlay, lo, hi| -> Vec<String> {
+            overlay.x_ticks(lo, hi, 4.0).into_iter().map(|(_, label)| label).collect()
+        };
+        assert_eq!(labels(&overlay(PlotXAxis::Samples, None), 0.0, 100.0), ["0", "50", "100"]);
+        assert_eq!(labels(&overlay(PlotXAxis::Time, None), 0.0, 60.0), ["0s", "15s", "30s", "45s", "60s"]);
+        assert_eq!(
+            labels(&overlay(PlotXAxis::Time, Some("2024-01-01T10:00:00+09:00")), 0.0, 240.0),
+            ["10:00", "10:01", "10:02", "10:03", "10:04"]
+        );
+
+        // A zero-width range gives at most one tick
+        for x_axis in [PlotXAxis::Samples, PlotXAxis::Time] {
+            assert!(overlay(x_axis, None).x_ticks(42.0, 42.0, 4.0).len() <= 1);
+        }
+    }
+
+    #[test]
+    fn test_hover_sample() {
+        let view = ViewTransform {
+            screen: Rect::from_min_size(Pos2::new(10.0, 20.0), egui::vec2(200.0, 100.0)),
+            zoom: 1.0,
+            pan: [0.0, 0.0],
+        };
+        let frame = overlay(PlotXAxis::Samples, None).frame;
+        let sample_at = |x: f32| frame.sample_x(view.to_view(Pos2::new(x, 70.0)).0);
+        assert!((sample_at(10.0) - 0.0).abs() < 1e-9);
+        assert!((sample_at(110.0) - 50.0).abs() < 1e-9);
+        assert!((sample_at(210.0) - 100.0).abs() < 1e-9);
+
+        // The hover point maps back to the cursor
+        let (view_x, view_y) = view.to_view(Pos2::new(60.0, 45.0));
+        let p = view.to_screen(view_x, view_y);
+        assert!((p.x - 60.0).abs() < 1e-3 && (p.y - 45.0).abs() < 1e-3, "{:?}", p);
+
+        // Zoomed 2x and panned right by a quarter view
+        let zoomed = ViewTransform { zoom: 2.0, pan: [0.25, 0.0], ..view };
+        assert!((frame.sample_x(zoomed.to_view(Pos2::new(110.0, 70.0)).0) - 75.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_time_label() {
+        assert_eq!(overlay(PlotXAxis::Time, None).time_label(2.5).as_deref(), Some("2.500s"));
+        let absolute = overlay(PlotXAxis::Time, Some("2024-01-01T10:00:00Z"));
+        assert_eq!(absolute.time_label(61.0).as_deref(), Some("2024-01-01 10:01:01.000"));
+        let untimed = PlotOverlay { sample_interval: None, ..overlay(PlotXAxis::Samples, None) };
+        assert_eq!(untimed.time_label(1.0), None);
+    }
+}
@@ -50,6 +50,12 @@ impl StatsCollector {
         self.head = (self.head + 1) % self.frame_times.len();
     }
 
+    /// Duration of the most recent frame in milliseconds
+    pub fn last_frame_ms(&self) -> f32 {
+        let len = self.frame_times.len();
+        self.frame_times[(self.head + len - 1) % len]
+    }
+
     /// Calculate average FPS (O(1) - uses cached sum)
     pub fn fps(&self) -> f32 {
         let avg_ms = self.total_time / self.frame_times.len() as f32;
@@ -190,6 +196,30 @@ pub fn render_stats_overlay(ctx: &egui::Context, state: &ViewerState, collector:
                             ui.label("Zoom:");
                             ui.label(format!("{:.4}x", state.zoom));
                             ui.end_row();
+
+                            // Live stream
+                            if let Some(stream) = &state.stats.stream {
+                                ui.label("Stream:");
+                                ui.label(format!("{} frames, {} packets", stream.frames, stream.packets));
+                                ui.end_row();
+
+                                ui.label("Received:");
+                                ui.label(format!("{:.1} KB", stream.bytes as f64 / 1024.0));
+                                ui.end_row();
+
+                                // Loss and recovery counters: nonzero means the link is lossy
+                                for (label, count) in [
+                                    ("Dropped:", stream.dropped),
+                                    ("Reordered:", stream.reordered),
+                                    ("Resyncs:", stream.resyncs),
+                                    ("Errors:", stream.errors),
+                                ] {
+                                    ui.label(label);
+                                    let color = if count > 0 { Color32::YELLOW } else { Color32::WHITE };
+                                    ui.colored_label(color, count.to_string());
+                                    ui.end_row();
+                                }
+                            }
                         });
                 });
         });
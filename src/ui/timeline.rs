@@ -0,0 +1,159 @@
+//! Playback timeline for recorded .asp streams and videos
+//!
+//! Playback itself is driven from the keyboard (Space, arrows, PageUp/Down,
+//! Home/End, and -/= for the video frame rate); this panel shows where the
+//! player is, and clicking or dragging along the track seeks.
+
+use crate::decoder::{AspPlayer, VideoPlayer};
+use egui::{Color32, Rect, Sense, Stroke, Vec2};
+
+const TRACK_COLOR: Color32 = Color32::from_rgb(50, 50, 60);
+const PROGRESS_COLOR: Color32 = Color32::from_rgb(100, 150, 255);
+const KEYFRAME_COLOR: Color32 = Color32::from_rgb(255, 200, 100);
+
+/// Snapshot of the player position, taken once per frame
+pub struct TimelineInfo {
+    frame: usize,
+    frame_count: usize,
+    /// Packet sequence number (.asp only)
+    sequence: Option<u32>,
+    time: f64,
+    duration: f64,
+    /// Whether the frame is an I-frame (.asp only)
+    is_keyframe: Option<bool>,
+    fps: f32,
+    /// Rate the video was made for, when playing at another
+    native_fps: Option<f32>,
+    /// Keyframe positions as fractions of the timeline
+    keyframes: Vec<f32>,
+    hints: &'static str,
+}
+
+impl TimelineInfo {
+    pub fn from_player(player: &AspPlayer) -> Self {
+        let recording = player.recording();
+        let current = player.current();
+        let last = recording.frame_count().saturating_sub(1).max(1) as f32;
+        Self {
+            frame: player.frame(),
+            frame_count: recording.frame_count(),
+            sequence: Some(current.sequence),
+            time: current.time,
+            duration: recording.duration(),
+            is_keyframe: Some(current.keyframe == player.frame()),
+            fps: player.state().keyframe.as_ref().map_or(0.0, |k| k.fps),
+            native_fps: None,
+            keyframes: recording.keyframes().iter().map(|&k| k as f32 / last).collect(),
+            hints: "Space: Play/Pause  ←/→: Step  PgUp/PgDn: Keyframe  Home/End: Start/End  Drag: Seek",
+        }
+    }
+
+    pub fn from_video(player: &VideoPlayer) -> Self {
+        let clip = player.clip();
+        let native_fps = clip.native_fps() as f32;
+        let fps = player.fps() as f32;
+        Self {
+            frame: player.frame(),
+            frame_count: clip.frame_count(),
+            sequence: None,
+            time: player.time(),
+            duration: clip.duration(),
+            is_keyframe: None,
+            fps,
+            native_fps: Some(native_fps).filter(|native| (native - fps).abs() > 0.05),
+            keyframes: Vec::new(),
+            hints: "Space: Play/Pause  ←/→: Step  -/=: Slower/Faster  Home/End: Start/End  Drag: Seek",
+        }
+    }
+
+    /// Draw the panel; returns the frame to seek to when the track is
+    /// clicked or dragged to another frame
+    pub fn render(&self, ctx: &egui::Context, paused: bool) -> Option<usize> {
+        let mut seek = None;
+        egui::TopBottomPanel::bottom("asp_timeline").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(if paused { "⏸" } else { "▶" });
+                ui.monospace(format!("Frame {}/{}", self.frame + 1, self.frame_count));
+                if let Some(sequence) = self.sequence {
+                    ui.monospace(format!("seq #{}", sequence));
+                }
+                ui.monospace(format!("{} / {}", format_time(self.time), format_time(self.duration)));
+                if let Some(is_keyframe) = self.is_keyframe {
+                    ui.label(if is_keyframe { "I" } else { "D" });
+                }
+                ui.label(format!("{:.1} fps", self.fps));
+                if let Some(native_fps) = self.native_fps {
+                    ui.label(egui::RichText::new(format!("(native {:.1})", native_fps)).weak());
+                }
+            });
+
+            let (rect, response) =
+                ui.allocate_exact_size(Vec2::new(ui.available_width(), 12.0), Sense::click_and_drag());
+            if let Some(pos) = response.interact_pointer_pos() {
+                let frame = frame_at_x(pos.x, rect.left(), rect.right(), self.frame_count);
+                seek = Some(frame).filter(|&frame| frame != self.frame);
+            }
+            let painter = ui.painter();
+            painter.rect_filled(rect, 2.0, TRACK_COLOR);
+            let progress = self.frame as f32 / self.frame_count.saturating_sub(1).max(1) as f32;
+            let filled = Rect::from_min_size(rect.min, Vec2::new(rect.width() * progress, rect.height()));
+            painter.rect_filled(filled, 2.0, PROGRESS_COLOR);
+            for &k in &self.keyframes {
+                let x = rect.left() + rect.width() * k;
+                painter.line_segment(
+                    [egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())],
+                    Stroke::new(1.0, KEYFRAME_COLOR),
+                );
+            }
+
+            ui.label(egui::RichText::new(self.hints).small().weak());
+        });
+        seek
+    }
+}
+
+/// Frame under `x` on a track spanning `left..right`, clamped to the ends
+///
+/// Frames are spaced evenly, the first at `left` and the last at `right`,
+/// matching the drawn progress.
+fn frame_at_x(x: f32, left: f32, right: f32, frame_count: usize) -> usize {
+    let last = frame_count.saturating_sub(1);
+    let fraction = ((x - left) / (right - left).max(1.0)).clamp(0.0, 1.0);
+    (fraction * last as f32).round() as usize
+}
+
+/// `mm:ss.mmm`
+fn format_time(seconds: f64) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!("{:02}:{:02}.{:03}", millis / 60_000, millis / 1000 % 60, millis % 1000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_at_x() {
+        // 11 frames on a 100 px track starting at x = 50
+        assert_eq!(frame_at_x(50.0, 50.0, 150.0, 11), 0);
+        assert_eq!(frame_at_x(150.0, 50.0, 150.0, 11), 10);
+        assert_eq!(frame_at_x(94.0, 50.0, 150.0, 11), 4);
+        assert_eq!(frame_at_x(96.0, 50.0, 150.0, 11), 5);
+
+        // Clamped outside the track
+        assert_eq!(frame_at_x(0.0, 50.0, 150.0, 11), 0);
+        assert_eq!(frame_at_x(500.0, 50.0, 150.0, 11), 10);
+
+        // Single frame and zero-width track
+        assert_eq!(frame_at_x(120.0, 50.0, 150.0, 1), 0);
+        assert_eq!(frame_at_x(50.0, 50.0, 50.0, 11), 0);
+        assert_eq!(frame_at_x(51.0, 50.0, 50.0, 11), 10);
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(0.0), "00:00.000");
+        assert_eq!(format_time(61.2345), "01:01.235");
+        assert_eq!(format_time(-1.0), "00:00.000");
+    }
+}